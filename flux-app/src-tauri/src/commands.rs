@@ -1,6 +1,18 @@
-use tauri::State;
-use crate::AppState;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::{AppState, EngineState, LogState, MetricsState, SamplePoolState, SAMPLE_RECORDED_EVENT};
+use crate::engine::device::{self, ActiveDevice, AudioDeviceState, AudioSettings, AudioStatus, HostInfo};
 use crate::engine::kernel::AudioCommand;
+use crate::engine::midi_engine::EngineCommand;
+use crate::preferences::Preferences;
+use crate::engine::diagnostics::LogRecord;
+use crate::engine::metrics::EngineMetrics;
+use crate::engine::midi_input::NoteInput;
+use crate::engine::recorder::{RecordOptions, Recording};
+use crate::engine::render::{self, RenderOptions};
+use crate::engine::sample_pool::SampleInfo;
+use crate::engine::tuning;
+use crate::shared::models::{Pattern, PatternSwitch, Song, Track, Tuning};
 
 #[tauri::command]
 pub fn set_playback_state(playing: bool, state: State<'_, AppState>) -> Result<(), String> {
@@ -17,22 +29,261 @@ pub fn set_playback_state(playing: bool, state: State<'_, AppState>) -> Result<(
 }
 
 #[tauri::command]
-pub fn toggle_step(track_id: usize, step_idx: usize, state: State<'_, AppState>) -> Result<(), String> {
+pub fn toggle_step(
+    track_id: usize,
+    subtrack: usize,
+    step_idx: usize,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
     let mut producer = state.command_producer.lock().map_err(|_| "Failed to lock mutex")?;
-    producer.push(AudioCommand::ToggleStep(track_id, step_idx)).map_err(|_| "Queue full")?;
+    producer.push(AudioCommand::ToggleStep(track_id, subtrack, step_idx)).map_err(|_| "Queue full")?;
     Ok(())
 }
 
 #[tauri::command]
 pub fn set_param_lock(
     track_id: usize, 
+    subtrack: usize,
     step_idx: usize, 
     param_id: usize, 
     value: Option<f32>, 
     state: State<'_, AppState>
 ) -> Result<(), String> {
     let mut producer = state.command_producer.lock().map_err(|_| "Lock fail")?;
-    producer.push(AudioCommand::SetParamLock(track_id, step_idx, param_id, value))
+    producer.push(AudioCommand::SetParamLock(track_id, subtrack, step_idx, param_id, value))
         .map_err(|_| "Queue full")?;
     Ok(())
 }
+
+/// Store a bank slot's pattern in the audio kernel and the MIDI engine.
+/// The playing pattern is hot-swapped at the next step boundary.
+#[tauri::command]
+pub fn update_pattern(
+    slot: usize,
+    pattern: Pattern,
+    state: State<'_, AppState>,
+    midi: State<'_, EngineState>,
+) -> Result<(), String> {
+    let midi_command = EngineCommand::UpdatePattern(slot, pattern.clone());
+    // Boxed here so the audio thread never allocates
+    let command = AudioCommand::SwapPattern(slot, Box::new(pattern));
+    push_to_engines(&state, &midi, command, midi_command)
+}
+
+/// Hot-swap a single existing track of a bank slot's pattern (applied at the next step boundary).
+#[tauri::command]
+pub fn update_track(
+    slot: usize,
+    track_id: usize,
+    track: Track,
+    state: State<'_, AppState>,
+    midi: State<'_, EngineState>,
+) -> Result<(), String> {
+    let midi_command = EngineCommand::UpdateTrack(slot, track_id, Box::new(track.clone()));
+    let command = AudioCommand::SwapTrack(slot, track_id, Box::new(track));
+    push_to_engines(&state, &midi, command, midi_command)
+}
+
+// Send a command to both engines, or to neither when either queue is full, so the kernel
+// and the MIDI engine never hold different patterns. Both queues are locked while checking.
+fn push_to_engines(
+    state: &AppState,
+    midi: &EngineState,
+    command: AudioCommand,
+    midi_command: EngineCommand,
+) -> Result<(), String> {
+    let mut producer = state.command_producer.lock().map_err(|_| "Failed to lock mutex")?;
+    let mut midi_producer = midi.command_producer.lock().map_err(|_| "Failed to lock mutex")?;
+    if producer.is_full() {
+        return Err("Command queue full, neither engine was updated".to_string());
+    }
+    if midi_producer.is_full() {
+        return Err("MIDI command queue full, neither engine was updated".to_string());
+    }
+    producer.push(command).map_err(|_| "Command queue full, neither engine was updated")?;
+    midi_producer
+        .push(midi_command)
+        .map_err(|_| "MIDI command queue full, the MIDI engine is stale".to_string())
+}
+
+/// Queue a switch to a bank slot's pattern, which the UI stored with `update_pattern` first.
+/// The kernel switches right away while stopped; the "playback-status" event reports the switch.
+#[tauri::command]
+pub fn queue_pattern(
+    slot: usize,
+    switch: PatternSwitch,
+    state: State<'_, AppState>,
+    midi: State<'_, EngineState>,
+) -> Result<(), String> {
+    push_to_engines(&state, &midi, AudioCommand::QueuePattern(slot, switch), EngineCommand::QueuePattern(slot, switch))
+}
+
+/// Replace the song both engines follow in song mode.
+#[tauri::command]
+pub fn update_song(
+    song: Song,
+    state: State<'_, AppState>,
+    midi: State<'_, EngineState>,
+) -> Result<(), String> {
+    let midi_command = EngineCommand::UpdateSong(song.clone());
+    push_to_engines(&state, &midi, AudioCommand::SetSong(Box::new(song)), midi_command)
+}
+
+/// Send a note from the MIDI input to both engines, where it feeds the track's arpeggiator.
+pub fn route_note_input(app: &AppHandle, note: NoteInput) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let mut producer = state.command_producer.lock().map_err(|_| "Failed to lock mutex")?;
+    producer
+        .push(AudioCommand::NoteInput(note.track, note.note, note.velocity))
+        .map_err(|_| "Command queue full")?;
+    drop(producer);
+
+    let midi = app.state::<EngineState>();
+    let mut midi_producer = midi.command_producer.lock().map_err(|_| "Failed to lock mutex")?;
+    midi_producer
+        .push(EngineCommand::NoteInput(note.track, note.note, note.velocity))
+        .map_err(|_| "MIDI command queue full")?;
+    Ok(())
+}
+
+/// Build a project tuning from a Scala scale (.scl) and an optional keyboard mapping (.kbm).
+/// The UI stores it in the pattern, which sends it to the engines.
+#[tauri::command]
+pub fn load_tuning(scl_path: String, kbm_path: Option<String>) -> Result<Tuning, String> {
+    let scl = std::fs::read_to_string(&scl_path).map_err(|e| e.to_string())?;
+    let kbm = kbm_path.map(std::fs::read_to_string).transpose().map_err(|e| e.to_string())?;
+    tuning::load_scala(&scl, kbm.as_deref())
+}
+
+/// Render the pattern offline and write the master mixdown plus stems to `directory`.
+/// Returns the written file paths.
+#[tauri::command]
+pub async fn export_audio(
+    pattern: Pattern,
+    directory: String,
+    options: Option<RenderOptions>,
+    pool: State<'_, SamplePoolState>,
+) -> Result<Vec<String>, String> {
+    let options = options.unwrap_or_default();
+    let samples = pool.pool.lock().map_err(|_| "Failed to lock mutex")?.table();
+    let files = render::export_pattern(&pattern, &samples, std::path::Path::new(&directory), &options)?;
+    Ok(files.iter().map(|p| p.display().to_string()).collect())
+}
+
+/// Audio hosts and their output devices, with supported sample rates and buffer sizes.
+#[tauri::command]
+pub async fn list_audio_devices() -> Result<Vec<HostInfo>, String> {
+    Ok(device::list_hosts())
+}
+
+/// The output the audio stream currently runs on, if any.
+#[tauri::command]
+pub async fn get_audio_device(state: State<'_, AudioDeviceState>) -> Result<Option<ActiveDevice>, String> {
+    state.active()
+}
+
+/// Output state (running, fallback, recovering, stopped), also emitted as "audio-status".
+#[tauri::command]
+pub async fn get_audio_status(state: State<'_, AudioDeviceState>) -> Result<AudioStatus, String> {
+    state.status()
+}
+
+/// Rebuild the audio stream with new device settings and save them in the preferences.
+/// Pattern and transport are kept; on failure the previous device stays active.
+#[tauri::command]
+pub async fn set_audio_device(
+    settings: AudioSettings,
+    app: AppHandle,
+    state: State<'_, AudioDeviceState>,
+) -> Result<ActiveDevice, String> {
+    let active = state.apply(settings.clone())?;
+
+    let mut preferences = Preferences::load(&app);
+    preferences.audio = settings;
+    preferences.save(&app)?;
+    Ok(active)
+}
+
+
+/// Arm the recorder: immediately or at the next bar, for `bars` bars or until stopped.
+/// Resampling the master, a track or a send is always quantized to whole bars.
+/// The result lands in the sample pool and is announced with a "sample-recorded" event.
+#[tauri::command]
+pub async fn start_recording(
+    options: RecordOptions,
+    state: State<'_, AppState>,
+    device: State<'_, AudioDeviceState>,
+) -> Result<(), String> {
+    // Size the buffer for the running stream; the kernel never allocates
+    let sample_rate = device.active()?.map(|d| d.sample_rate).unwrap_or(48000);
+    let command = AudioCommand::StartRecording(Box::new(Recording::new(options, sample_rate as f32)));
+    let mut producer = state.command_producer.lock().map_err(|_| "Failed to lock mutex")?;
+    producer.push(command).map_err(|_| "Command queue full")?;
+    Ok(())
+}
+
+/// Finish the current recording. A recording still waiting for its bar is cancelled.
+#[tauri::command]
+pub fn stop_recording(state: State<'_, AppState>) -> Result<(), String> {
+    let mut producer = state.command_producer.lock().map_err(|_| "Failed to lock mutex")?;
+    producer.push(AudioCommand::StopRecording).map_err(|_| "Command queue full")?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_samples(pool: State<'_, SamplePoolState>) -> Result<Vec<SampleInfo>, String> {
+    Ok(pool.pool.lock().map_err(|_| "Failed to lock mutex")?.list())
+}
+
+/// Remove a sample from the pool. Tracks still pointing at the slot fall back to the oscillator.
+#[tauri::command]
+pub fn delete_sample(
+    id: u16,
+    pool: State<'_, SamplePoolState>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    pool.pool.lock().map_err(|_| "Failed to lock mutex")?.remove(id)?;
+    let mut producer = state.command_producer.lock().map_err(|_| "Failed to lock mutex")?;
+    producer.push(AudioCommand::LoadSample(id, None)).map_err(|_| "Command queue full")?;
+    Ok(())
+}
+
+/// Analyze `track` next to the master, or only the master with `None`.
+#[tauri::command]
+pub fn set_analyzer_track(track: Option<usize>, state: State<'_, AppState>) -> Result<(), String> {
+    let mut producer = state.command_producer.lock().map_err(|_| "Failed to lock mutex")?;
+    producer.push(AudioCommand::SetAnalyzerTrack(track)).map_err(|_| "Command queue full")?;
+    Ok(())
+}
+
+/// Engine metrics of the last 30 seconds, oldest first.
+#[tauri::command]
+pub fn get_engine_metrics(state: State<'_, MetricsState>) -> Result<Vec<EngineMetrics>, String> {
+    Ok(state.history.lock().map_err(|_| "Failed to lock mutex")?.to_vec())
+}
+
+/// The last `RECENT_LOG_LEN` log records, oldest first.
+#[tauri::command]
+pub fn get_recent_logs(state: State<'_, LogState>) -> Result<Vec<LogRecord>, String> {
+    Ok(state.recent.lock().map_err(|_| "Failed to lock mutex")?.to_vec())
+}
+
+/// Store a finished recording in the pool, load it into the kernel and notify the UI.
+pub fn add_recording(app: &AppHandle, recording: Recording) -> Result<SampleInfo, String> {
+    let prefix = recording.options.source.name_prefix();
+    let buffer = Arc::new(recording.into_sample());
+    let pool_state = app.state::<SamplePoolState>();
+    let mut pool = pool_state.pool.lock().map_err(|_| "Failed to lock mutex")?;
+    let name = pool.next_name(prefix);
+    let id = pool.insert(name, Arc::clone(&buffer))?;
+    let info = pool.info(id).ok_or("Recorded sample missing from the pool")?;
+    drop(pool);
+
+    let state = app.state::<AppState>();
+    let mut producer = state.command_producer.lock().map_err(|_| "Failed to lock mutex")?;
+    producer.push(AudioCommand::LoadSample(id, Some(buffer))).map_err(|_| "Command queue full")?;
+    drop(producer);
+
+    let _ = app.emit(SAMPLE_RECORDED_EVENT, &info);
+    Ok(info)
+}
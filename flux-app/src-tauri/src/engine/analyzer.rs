@@ -0,0 +1,252 @@
+use crate::engine::kernel::FrameTaps;
+use rtrb::{Consumer, Producer, RingBuffer};
+use serde::Serialize;
+use std::f32::consts::PI;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Emitted with an `AnalyzerFrame` at most every `ANALYZER_INTERVAL`.
+pub const ANALYZER_EVENT: &str = "analyzer-frame";
+pub const ANALYZER_INTERVAL: Duration = Duration::from_millis(33); // ~30 FPS
+pub const ANALYZER_BLOCK_SIZE: usize = 1024; // Frames per block, also the FFT size
+pub const SCOPE_POINTS: usize = 512;
+const ANALYZER_QUEUE_BLOCKS: usize = 8;
+const SPECTRUM_FLOOR_DB: f32 = -120.0;
+
+/// Mono audio handed from the audio thread to the analyzer thread.
+/// Copied into pre-allocated queue slots, so pushing never allocates.
+#[derive(Clone, Copy)]
+pub struct AnalyzerBlock {
+    pub sample_rate: f32,
+    pub master: [f32; ANALYZER_BLOCK_SIZE],
+    pub track: Option<usize>, // Track captured in `track_samples`, if any
+    pub track_samples: [f32; ANALYZER_BLOCK_SIZE],
+}
+
+impl Default for AnalyzerBlock {
+    fn default() -> Self {
+        Self {
+            sample_rate: 0.0,
+            master: [0.0; ANALYZER_BLOCK_SIZE],
+            track: None,
+            track_samples: [0.0; ANALYZER_BLOCK_SIZE],
+        }
+    }
+}
+
+/// Audio-thread end of the analyzer queue. Collects frames from the kernel's `FrameTaps`.
+pub struct AnalyzerTap {
+    producer: Option<Producer<AnalyzerBlock>>,
+    block: AnalyzerBlock,
+    position: usize,
+    pub track: Option<usize>, // Selected track, applied from the next block
+}
+
+impl AnalyzerTap {
+    /// A tap without an analyzer thread: nothing is captured. Only for offline rendering and tests.
+    pub fn disconnected() -> Self {
+        Self { producer: None, block: AnalyzerBlock::default(), position: 0, track: None }
+    }
+
+    pub fn push(&mut self, taps: &FrameTaps, sample_rate: f32) {
+        let Some(producer) = &mut self.producer else {
+            return;
+        };
+        if self.position == 0 {
+            self.block.sample_rate = sample_rate;
+            self.block.track = self.track;
+        }
+        let [left, right] = taps.master;
+        self.block.master[self.position] = (left + right) * 0.5;
+        if let Some([left, right]) = self.block.track.and_then(|t| taps.track_post_fx.get(t).copied()) {
+            self.block.track_samples[self.position] = (left + right) * 0.5;
+        }
+        self.position += 1;
+
+        if self.position == ANALYZER_BLOCK_SIZE {
+            self.position = 0;
+            // A full queue means the analyzer is behind: drop the block
+            let _ = producer.push(self.block);
+        }
+    }
+}
+
+pub fn analyzer_queue() -> (AnalyzerTap, Consumer<AnalyzerBlock>) {
+    let (producer, consumer) = RingBuffer::new(ANALYZER_QUEUE_BLOCKS);
+    (AnalyzerTap { producer: Some(producer), ..AnalyzerTap::disconnected() }, consumer)
+}
+
+/// Scope trace and spectrum of one signal.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SignalAnalysis {
+    pub scope: Vec<f32>,    // SCOPE_POINTS samples from a rising zero crossing
+    pub spectrum: Vec<f32>, // dB per FFT bin, 0 Hz to Nyquist (ANALYZER_BLOCK_SIZE / 2 bins)
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct AnalyzerFrame {
+    pub sample_rate: f32,
+    pub master: SignalAnalysis,
+    pub track: Option<usize>,
+    pub track_analysis: Option<SignalAnalysis>,
+}
+
+pub fn analyze_block(block: &AnalyzerBlock) -> AnalyzerFrame {
+    AnalyzerFrame {
+        sample_rate: block.sample_rate,
+        master: analyze(&block.master),
+        track: block.track,
+        track_analysis: block.track.map(|_| analyze(&block.track_samples)),
+    }
+}
+
+fn analyze(samples: &[f32; ANALYZER_BLOCK_SIZE]) -> SignalAnalysis {
+    SignalAnalysis { scope: scope_trace(samples), spectrum: spectrum_db(samples) }
+}
+
+/// `SCOPE_POINTS` samples starting at the first rising zero crossing, for a steady trace.
+/// Starts at the beginning of the block if there is none early enough.
+pub fn scope_trace(samples: &[f32]) -> Vec<f32> {
+    let search = samples.len().saturating_sub(SCOPE_POINTS);
+    let start = (1..search).find(|&i| samples[i - 1] < 0.0 && samples[i] >= 0.0).unwrap_or(0);
+    samples[start..].iter().take(SCOPE_POINTS).copied().collect()
+}
+
+/// Hann-windowed magnitude spectrum in dB (0 dB = full-scale sine).
+pub fn spectrum_db(samples: &[f32]) -> Vec<f32> {
+    let n = samples.len();
+    let mut re: Vec<f32> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, s)| s * 0.5 * (1.0 - (2.0 * PI * i as f32 / n as f32).cos()))
+        .collect();
+    let mut im = vec![0.0; n];
+    fft(&mut re, &mut im);
+
+    // Hann window has a coherent gain of 0.5; a full-scale sine shows up as n/4
+    let scale = 4.0 / n as f32;
+    (0..n / 2)
+        .map(|k| {
+            let magnitude = (re[k] * re[k] + im[k] * im[k]).sqrt() * scale;
+            (20.0 * magnitude.max(1e-9).log10()).max(SPECTRUM_FLOOR_DB)
+        })
+        .collect()
+}
+
+/// In-place iterative radix-2 FFT. `re.len()` must be a power of two.
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    debug_assert!(n.is_power_of_two() && im.len() == n);
+
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Analyze blocks and pass the latest frame to `on_frame`, at most every `ANALYZER_INTERVAL`.
+/// Blocks in between are skipped. Exits when the kernel is gone.
+pub fn spawn_analyzer<F>(mut consumer: Consumer<AnalyzerBlock>, mut on_frame: F) -> thread::JoinHandle<()>
+where
+    F: FnMut(AnalyzerFrame) + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut last_frame: Option<Instant> = None;
+        loop {
+            let mut latest = None;
+            while let Ok(block) = consumer.pop() {
+                latest = Some(block);
+            }
+            if let Some(block) = latest {
+                if last_frame.is_none_or(|t| t.elapsed() >= ANALYZER_INTERVAL) {
+                    last_frame = Some(Instant::now());
+                    on_frame(analyze_block(&block));
+                }
+            }
+            if consumer.is_abandoned() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::domain::MAX_TRACKS;
+
+    fn sine(bin: usize, phase: f32) -> Vec<f32> {
+        (0..ANALYZER_BLOCK_SIZE)
+            .map(|i| (2.0 * PI * bin as f32 * i as f32 / ANALYZER_BLOCK_SIZE as f32 + phase).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_spectrum_peak_at_sine_bin() {
+        let spectrum = spectrum_db(&sine(32, 0.0));
+        assert_eq!(spectrum.len(), ANALYZER_BLOCK_SIZE / 2);
+        let peak = (0..spectrum.len()).max_by(|&a, &b| spectrum[a].total_cmp(&spectrum[b])).unwrap();
+        assert_eq!(peak, 32);
+        assert!(spectrum[32].abs() < 0.5, "Full-scale sine at {} dB", spectrum[32]);
+        assert!(spectrum[200] < -60.0);
+    }
+
+    #[test]
+    fn test_scope_starts_at_rising_zero_crossing() {
+        let trace = scope_trace(&sine(8, PI)); // Starts falling
+        assert_eq!(trace.len(), SCOPE_POINTS);
+        assert!(trace[0] >= 0.0 && trace[0] < 0.1);
+        assert!(trace[1] > trace[0]);
+    }
+
+    #[test]
+    fn test_tap_hands_over_full_blocks() {
+        let (mut tap, mut consumer) = analyzer_queue();
+        tap.track = Some(1);
+        let mut track_post_fx = [[0.0; 2]; MAX_TRACKS];
+        track_post_fx[1] = [0.25, 0.25];
+        let taps = FrameTaps { master: [0.5, 0.5], track_post_fx, ..Default::default() };
+
+        for _ in 0..ANALYZER_BLOCK_SIZE - 1 {
+            tap.push(&taps, 48000.0);
+        }
+        assert!(consumer.pop().is_err());
+        tap.push(&taps, 48000.0);
+
+        let block = consumer.pop().unwrap();
+        assert_eq!(block.sample_rate, 48000.0);
+        assert_eq!(block.track, Some(1));
+        assert!(block.master.iter().all(|&s| s == 0.5));
+        assert!(block.track_samples.iter().all(|&s| s == 0.25));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::engine::domain::{PARAM_ARP_GATE, PARAM_ARP_MODE, PARAM_ARP_OCTAVES, PARAM_ARP_RATE};
+use crate::engine::lfo::random_bipolar;
+
+pub const MAX_ARP_NOTES: usize = 16; // Per input: trig notes and held MIDI notes
+pub const TICKS_PER_STEP: u32 = 6; // 24 PPQN, one step is a 16th note
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ArpMode {
+    #[default]
+    Up,
+    Down,
+    UpDown, // Ends are not repeated
+    Random,
+    AsPlayed, // Order the notes arrived in
+}
+
+const ARP_MODES: [ArpMode; 5] = [ArpMode::Up, ArpMode::Down, ArpMode::UpDown, ArpMode::Random, ArpMode::AsPlayed];
+// 1/32, 1/16T, 1/16, 1/8T, 1/8, 1/4
+const ARP_RATE_TICKS: [u32; 6] = [3, 4, 6, 8, 12, 24];
+
+// Entry of `table` selected by a normalized parameter
+fn pick<T: Copy>(table: &[T], value: f32) -> T {
+    table[(value.clamp(0.0, 1.0) * (table.len() - 1) as f32).round() as usize]
+}
+
+/// Arpeggiator settings decoded from the normalized arp parameters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArpSettings {
+    pub mode: ArpMode,
+    pub rate_ticks: u32, // Note interval in 24 PPQN ticks
+    pub octaves: u8,     // 1 to 4
+    pub gate: f32,       // Note length as a fraction of the interval
+}
+
+impl Default for ArpSettings {
+    fn default() -> Self {
+        Self { mode: ArpMode::Up, rate_ticks: TICKS_PER_STEP, octaves: 1, gate: 0.5 }
+    }
+}
+
+impl ArpSettings {
+    /// Decode the settings from parameter values, e.g. `|id| resolve_param(track, step, id)`.
+    pub fn from_params(param: impl Fn(usize) -> f32) -> Self {
+        Self {
+            mode: pick(&ARP_MODES, param(PARAM_ARP_MODE)),
+            rate_ticks: pick(&ARP_RATE_TICKS, param(PARAM_ARP_RATE)),
+            octaves: pick(&[1, 2, 3, 4], param(PARAM_ARP_OCTAVES)),
+            gate: param(PARAM_ARP_GATE).clamp(0.05, 1.0),
+        }
+    }
+
+    /// Note interval in steps.
+    pub fn rate_steps(&self) -> f32 {
+        self.rate_ticks as f32 / TICKS_PER_STEP as f32
+    }
+}
+
+/// Notes in the order they arrived, without duplicates. Fixed capacity, extra notes are ignored.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoteSet {
+    notes: [u8; MAX_ARP_NOTES],
+    len: usize,
+}
+
+impl NoteSet {
+    pub fn insert(&mut self, note: u8) {
+        if self.len < MAX_ARP_NOTES && !self.as_slice().contains(&note) {
+            self.notes[self.len] = note;
+            self.len += 1;
+        }
+    }
+
+    pub fn remove(&mut self, note: u8) {
+        if let Some(index) = self.as_slice().iter().position(|&n| n == note) {
+            self.notes.copy_within(index + 1..self.len, index);
+            self.len -= 1;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.notes[..self.len]
+    }
+}
+
+/// Arpeggiator of one track. Plays its input notes one at a time, the engine asks for the next
+/// note at each interval of `settings.rate_ticks`. The input is the notes of the last trig, held
+/// until the trig's gate ends, plus any held MIDI input notes.
+/// Clock units are the caller's (frames in the kernel, ticks in the MIDI engine).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ArpState {
+    pub settings: ArpSettings,
+    trig_notes: NoteSet,
+    trig_velocity: u8,
+    trig_end: u64, // Clock at which the trig's notes are released
+    held_notes: NoteSet,
+    held_velocity: u8,
+    position: u64, // Notes played since the input started
+    seed: u64,     // Random mode seed, deterministic so offline renders match playback
+}
+
+impl ArpState {
+    /// Start over on a trig's notes, held until `end`.
+    pub fn trigger(&mut self, settings: ArpSettings, notes: &[u8], velocity: u8, end: u64, seed: u64) {
+        self.settings = settings;
+        self.trig_notes.clear();
+        for &note in notes {
+            self.trig_notes.insert(note);
+        }
+        self.trig_velocity = velocity;
+        self.trig_end = end;
+        self.position = 0;
+        self.seed = seed;
+    }
+
+    /// Add notes of another trig on the same step, held until the later of both ends.
+    pub fn add_trig_notes(&mut self, notes: &[u8], end: u64) {
+        for &note in notes {
+            self.trig_notes.insert(note);
+        }
+        self.trig_end = self.trig_end.max(end);
+    }
+
+    pub fn release_trig(&mut self) {
+        self.trig_notes.clear();
+    }
+
+    /// Hold a MIDI input note. True if the arp was idle and has to be started.
+    pub fn note_on(&mut self, note: u8, velocity: u8) -> bool {
+        let was_idle = self.is_idle();
+        if was_idle {
+            self.position = 0;
+        }
+        self.held_notes.insert(note);
+        self.held_velocity = velocity;
+        was_idle
+    }
+
+    pub fn note_off(&mut self, note: u8) {
+        self.held_notes.remove(note);
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.trig_notes.is_empty() && self.held_notes.is_empty()
+    }
+
+    /// The next note and its velocity at clock `now`; `None` once the input is released.
+    pub fn next_note(&mut self, now: u64) -> Option<(u8, u8)> {
+        if now >= self.trig_end {
+            self.trig_notes.clear();
+        }
+
+        let mut input = NoteSet::default();
+        for &note in self.trig_notes.as_slice().iter().chain(self.held_notes.as_slice()) {
+            input.insert(note);
+        }
+        if input.is_empty() {
+            return None;
+        }
+        let notes = &mut input.notes[..input.len];
+        if self.settings.mode != ArpMode::AsPlayed {
+            notes.sort_unstable();
+        }
+
+        let count = notes.len() as u64;
+        let total = count * self.settings.octaves.max(1) as u64;
+        let index = match self.settings.mode {
+            ArpMode::Up | ArpMode::AsPlayed => self.position % total,
+            ArpMode::Down => total - 1 - self.position % total,
+            ArpMode::UpDown => {
+                let period = (2 * total - 2).max(1);
+                let phase = self.position % period;
+                if phase < total { phase } else { period - phase }
+            }
+            ArpMode::Random => {
+                let random = (random_bipolar(self.seed.wrapping_add(self.position)) + 1.0) * 0.5;
+                ((random * total as f32) as u64).min(total - 1)
+            }
+        };
+        self.position += 1;
+
+        let note = notes[(index % count) as usize] as u64 + 12 * (index / count);
+        let velocity = if self.trig_notes.is_empty() { self.held_velocity } else { self.trig_velocity };
+        Some((note.min(127) as u8, velocity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(settings: ArpSettings, notes: &[u8], count: usize) -> Vec<u8> {
+        let mut arp = ArpState::default();
+        arp.trigger(settings, notes, 100, u64::MAX, 0);
+        (0..count).filter_map(|i| arp.next_note(i as u64)).map(|(note, _)| note).collect()
+    }
+
+    #[test]
+    fn test_arp_modes_and_octaves() {
+        let notes = [64, 60, 67];
+        let settings = |mode, octaves| ArpSettings { mode, octaves, ..ArpSettings::default() };
+        assert_eq!(run(settings(ArpMode::Up, 1), &notes, 4), [60, 64, 67, 60]);
+        assert_eq!(run(settings(ArpMode::Down, 1), &notes, 4), [67, 64, 60, 67]);
+        assert_eq!(run(settings(ArpMode::AsPlayed, 1), &notes, 4), [64, 60, 67, 64]);
+        assert_eq!(run(settings(ArpMode::UpDown, 1), &notes, 6), [60, 64, 67, 64, 60, 64]);
+        assert_eq!(run(settings(ArpMode::Up, 2), &notes, 7), [60, 64, 67, 72, 76, 79, 60]);
+
+        let random = run(settings(ArpMode::Random, 2), &notes, 32);
+        assert!(random.iter().all(|note| [60, 64, 67, 72, 76, 79].contains(note)));
+        assert_eq!(random, run(settings(ArpMode::Random, 2), &notes, 32)); // Deterministic
+    }
+
+    #[test]
+    fn test_arp_input_from_trig_and_held_notes() {
+        let mut arp = ArpState::default();
+        arp.trigger(ArpSettings::default(), &[60], 90, 10, 0);
+        assert_eq!(arp.next_note(0), Some((60, 90)));
+        // Held notes join the trig's notes, then play alone once the trig ends
+        assert!(!arp.note_on(55, 70));
+        assert_eq!(arp.next_note(5), Some((60, 90)));
+        assert_eq!(arp.next_note(10), Some((55, 70)));
+        arp.note_off(55);
+        assert_eq!(arp.next_note(15), None);
+        assert!(arp.is_idle());
+        assert!(arp.note_on(48, 70));
+    }
+
+    #[test]
+    fn test_settings_decode_parameters() {
+        let settings = ArpSettings::from_params(|id| match id {
+            PARAM_ARP_MODE => 0.5,
+            PARAM_ARP_RATE => 0.4,
+            PARAM_ARP_OCTAVES => 1.0,
+            _ => 0.0,
+        });
+        assert_eq!(settings.mode, ArpMode::UpDown);
+        assert_eq!(settings.rate_ticks, TICKS_PER_STEP);
+        assert_eq!(settings.octaves, 4);
+        assert_eq!(settings.gate, 0.05);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::engine::domain::MAX_TRACKS;
+use crate::shared::models::{Song, SongRow};
+
+const PATTERN_STEPS: u32 = 16; // Steps per pass of a row without a length override
+const MAX_ROW_LENGTH: u32 = 1024;
+
+/// What plays on a step in song mode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SongStep {
+    pub row: usize,
+    pub pattern: usize,        // Pattern slot of the row
+    pub step: usize,           // Pattern step, rows longer than the pattern wrap around it
+    pub tempo: Option<f32>,    // Row tempo, instead of the pattern's
+    pub mutes: [bool; MAX_TRACKS],
+}
+
+/// Playback position in a song. The kernel and MIDI engine each own one and advance it once per step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Arranger {
+    row: usize,
+    pass: u32, // Passes of the row done
+    step: u32, // Step within the current pass
+    started: bool,
+}
+
+impl Arranger {
+    /// Back to the top of the song.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Row playing, `None` before the first step.
+    pub fn row(&self) -> Option<usize> {
+        self.started.then_some(self.row)
+    }
+
+    /// Move to the next step. `None` once a song without a loop has played its last row,
+    /// or when it has no rows.
+    pub fn advance(&mut self, song: &Song) -> Option<SongStep> {
+        if song.rows.is_empty() {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+        } else {
+            self.step += 1;
+            // Rows removed while playing end the pass early
+            let row = song.rows.get(self.row);
+            if row.is_none_or(|row| self.step >= row_length(row)) {
+                self.step = 0;
+                self.pass += 1;
+            }
+            if row.is_none_or(|row| self.pass >= row.repeats.max(1)) {
+                self.row = self.next_row(song)?;
+                self.pass = 0;
+            }
+        }
+
+        let row = &song.rows[self.row];
+        Some(SongStep {
+            row: self.row,
+            pattern: row.pattern,
+            step: (self.step % PATTERN_STEPS) as usize,
+            tempo: row.tempo,
+            mutes: row.mutes,
+        })
+    }
+
+    // Row after the current one, `None` at the end of a song without a loop
+    fn next_row(&self, song: &Song) -> Option<usize> {
+        let loop_start = song.loop_start.min(song.rows.len() - 1);
+        let at_loop_end = self.row >= song.loop_end || self.row + 1 >= song.rows.len();
+        match (song.loop_enabled, at_loop_end) {
+            (true, true) => Some(loop_start),
+            _ if self.row + 1 < song.rows.len() => Some(self.row + 1),
+            _ => None,
+        }
+    }
+}
+
+// Steps in one pass of a row
+fn row_length(row: &SongRow) -> u32 {
+    row.length.unwrap_or(PATTERN_STEPS).clamp(1, MAX_ROW_LENGTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song(rows: Vec<SongRow>) -> Song {
+        Song { enabled: true, rows, ..Song::default() }
+    }
+
+    // (row, step) of the next `count` steps, stopping at the song's end
+    fn run(arranger: &mut Arranger, song: &Song, count: usize) -> Vec<(usize, usize)> {
+        (0..count).map_while(|_| arranger.advance(song)).map(|s| (s.row, s.step)).collect()
+    }
+
+    #[test]
+    fn test_rows_play_their_repeats_and_lengths() {
+        let song = song(vec![
+            SongRow { repeats: 2, length: Some(2), ..SongRow::default() },
+            SongRow { length: Some(3), tempo: Some(90.0), ..SongRow::default() },
+        ]);
+        let mut arranger = Arranger::default();
+        assert_eq!(arranger.row(), None);
+        assert_eq!(run(&mut arranger, &song, 10), [(0, 0), (0, 1), (0, 0), (0, 1), (1, 0), (1, 1), (1, 2)]);
+        assert_eq!(arranger.advance(&song), None); // No loop: the song ends
+
+        arranger.reset();
+        let tempos: Vec<_> = (0..7).filter_map(|_| arranger.advance(&song)).map(|s| s.tempo).collect();
+        assert_eq!(tempos, [None, None, None, None, Some(90.0), Some(90.0), Some(90.0)]);
+    }
+
+    #[test]
+    fn test_loop_points_and_long_rows() {
+        let mut song = song(vec![
+            SongRow { length: Some(1), ..SongRow::default() },
+            SongRow { length: Some(1), ..SongRow::default() },
+            SongRow { length: Some(18), ..SongRow::default() },
+        ]);
+        song.loop_enabled = true;
+        song.loop_start = 1;
+        song.loop_end = 1;
+        let mut arranger = Arranger::default();
+        assert_eq!(run(&mut arranger, &song, 4), [(0, 0), (1, 0), (1, 0), (1, 0)]);
+
+        // Rows longer than the pattern wrap around it
+        song.loop_end = 2;
+        let steps: Vec<_> = run(&mut arranger, &song, 18).into_iter().map(|(_, step)| step).collect();
+        assert_eq!(&steps[14..], [14, 15, 0, 1]);
+        assert_eq!(arranger.advance(&song).map(|s| s.row), Some(1)); // Back to the loop start
+    }
+}
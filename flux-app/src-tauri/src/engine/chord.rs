@@ -0,0 +1,106 @@
+use crate::shared::models::{Chord, ChordKind, Voicing};
+
+pub const MAX_CHORD_NOTES: usize = 8; // Voices per track channel
+
+// Semitones above the root, the root included
+fn preset_intervals(kind: ChordKind) -> &'static [u8] {
+    match kind {
+        ChordKind::Single | ChordKind::Custom => &[0],
+        ChordKind::Major => &[0, 4, 7],
+        ChordKind::Minor => &[0, 3, 7],
+        ChordKind::Diminished => &[0, 3, 6],
+        ChordKind::Augmented => &[0, 4, 8],
+        ChordKind::Sus2 => &[0, 2, 7],
+        ChordKind::Sus4 => &[0, 5, 7],
+        ChordKind::Power => &[0, 7, 12],
+        ChordKind::Major7 => &[0, 4, 7, 11],
+        ChordKind::Minor7 => &[0, 3, 7, 10],
+        ChordKind::Dominant7 => &[0, 4, 7, 10],
+        ChordKind::HalfDiminished7 => &[0, 3, 6, 10],
+        ChordKind::Diminished7 => &[0, 3, 6, 9],
+        ChordKind::Add9 => &[0, 4, 7, 14],
+        ChordKind::Major9 => &[0, 4, 7, 11, 14],
+        ChordKind::Minor9 => &[0, 3, 7, 10, 14],
+    }
+}
+
+/// Notes of a chord on `root` (a MIDI note, fractional for pitch P-Locks), lowest first.
+/// Writes at most `MAX_CHORD_NOTES` into `out` and returns the count; notes past 0-127 are dropped.
+/// Never allocates, so the audio thread can call it.
+pub fn chord_notes(root: f32, chord: &Chord, out: &mut [f32; MAX_CHORD_NOTES]) -> usize {
+    let mut len = 0;
+    let custom = match chord.kind {
+        ChordKind::Custom => chord.intervals.as_slice(),
+        _ => &[],
+    };
+    for &interval in preset_intervals(chord.kind).iter().chain(custom) {
+        let note = root + interval as f32;
+        if len < MAX_CHORD_NOTES && !out[..len].contains(&note) {
+            out[len] = note;
+            len += 1;
+        }
+    }
+    let notes = &mut out[..len];
+    notes.sort_unstable_by(f32::total_cmp);
+
+    // Each inversion takes the lowest note up an octave
+    for _ in 0..(chord.inversion as usize).min(len.saturating_sub(1)) {
+        notes[0] += 12.0;
+        notes.sort_unstable_by(f32::total_cmp);
+    }
+
+    match chord.voicing {
+        Voicing::Close => {}
+        Voicing::Drop2 if len >= 2 => notes[len - 2] -= 12.0,
+        Voicing::Drop3 if len >= 3 => notes[len - 3] -= 12.0,
+        Voicing::Spread => {
+            for note in notes.iter_mut().skip(1).step_by(2) {
+                *note += 12.0;
+            }
+        }
+        _ => {}
+    }
+    notes.sort_unstable_by(f32::total_cmp);
+
+    // Keep what is playable, a contiguous run once sorted
+    let playable = |note: &f32| (0.0..=127.0).contains(note);
+    let first = out[..len].iter().position(playable).unwrap_or(len);
+    let count = out[first..len].iter().take_while(|note| playable(note)).count();
+    out.copy_within(first..first + count, 0);
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes(root: f32, chord: Chord) -> Vec<f32> {
+        let mut out = [0.0; MAX_CHORD_NOTES];
+        let len = chord_notes(root, &chord, &mut out);
+        out[..len].to_vec()
+    }
+
+    #[test]
+    fn test_chord_types_and_inversions() {
+        assert_eq!(notes(60.0, Chord::default()), [60.0]);
+        let c_major = Chord { kind: ChordKind::Major, ..Chord::default() };
+        assert_eq!(notes(60.0, c_major.clone()), [60.0, 64.0, 67.0]);
+        assert_eq!(notes(60.0, Chord { inversion: 1, ..c_major.clone() }), [64.0, 67.0, 72.0]);
+        assert_eq!(notes(60.0, Chord { inversion: 2, ..c_major.clone() }), [67.0, 72.0, 76.0]);
+        assert_eq!(notes(60.0, Chord { inversion: 9, ..c_major }), [67.0, 72.0, 76.0]); // Clamped
+
+        // Custom intervals on top of the root, duplicates ignored
+        let custom = Chord { kind: ChordKind::Custom, intervals: vec![7, 3, 7], ..Chord::default() };
+        assert_eq!(notes(57.5, custom), [57.5, 60.5, 64.5]);
+    }
+
+    #[test]
+    fn test_voicings_and_range() {
+        let c_maj7 = Chord { kind: ChordKind::Major7, ..Chord::default() };
+        assert_eq!(notes(60.0, Chord { voicing: Voicing::Drop2, ..c_maj7.clone() }), [55.0, 60.0, 64.0, 71.0]);
+        assert_eq!(notes(60.0, Chord { voicing: Voicing::Drop3, ..c_maj7.clone() }), [52.0, 60.0, 67.0, 71.0]);
+        assert_eq!(notes(60.0, Chord { voicing: Voicing::Spread, ..c_maj7.clone() }), [60.0, 67.0, 76.0, 83.0]);
+        // Notes above 127 are dropped
+        assert_eq!(notes(120.0, c_maj7), [120.0, 124.0, 127.0]);
+    }
+}
@@ -0,0 +1,677 @@
+use crate::engine::kernel::FluxKernel;
+use crate::engine::null_output::{self, NullStream, NULL_DEVICE, NULL_HOST};
+use crate::engine::recorder::{push_input, InputProducer};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// Offered when a device supports a sample rate range
+const COMMON_SAMPLE_RATES: [u32; 6] = [44_100, 48_000, 88_200, 96_000, 176_400, 192_000];
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2); // Polling for the preferred device while on a fallback
+const RESTART_ATTEMPTS: usize = 3;
+const RESTART_DELAY: Duration = Duration::from_millis(500);
+
+/// Event emitted to the UI whenever the output changes state.
+pub const AUDIO_STATUS_EVENT: &str = "audio-status";
+
+/// The user's output choice, saved in the preferences.
+/// `None` falls back to the host/device default.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub host: Option<String>,
+    pub device: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub buffer_size: Option<u32>, // Frames
+    pub input_device: Option<String>, // Recording input on the same host
+}
+
+/// The configuration the running stream actually uses.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ActiveDevice {
+    pub host: String,
+    pub device: String,
+    pub sample_rate: u32,
+    pub buffer_size: Option<u32>, // None = driver default
+    pub channels: u16,
+    pub input_device: Option<String>, // None when no input could be opened
+}
+
+impl ActiveDevice {
+    pub fn settings(&self) -> AudioSettings {
+        AudioSettings {
+            host: Some(self.host.clone()),
+            device: Some(self.device.clone()),
+            sample_rate: Some(self.sample_rate),
+            buffer_size: self.buffer_size,
+            input_device: self.input_device.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamState {
+    Running,    // On the preferred output
+    Fallback,   // On the default output or headless until the preferred device returns
+    Recovering, // Restarting after a stream error
+    Stopped,    // No output at all
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AudioStatus {
+    pub state: StreamState,
+    pub device: Option<ActiveDevice>,
+    pub message: Option<String>, // Last error, if any
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+    pub channels: u16,
+    pub sample_rates: Vec<u32>,
+    pub default_sample_rate: Option<u32>,
+    pub min_buffer_size: Option<u32>, // None when the driver doesn't report a range
+    pub max_buffer_size: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HostInfo {
+    pub name: String,
+    pub is_default: bool,
+    pub devices: Vec<DeviceInfo>,
+    pub input_devices: Vec<String>,
+}
+
+/// Enumerate the audio hosts (ALSA, JACK, CoreAudio, WASAPI, ...) and their output devices.
+/// The headless null host is always listed last.
+pub fn list_hosts() -> Vec<HostInfo> {
+    let default_host = cpal::default_host().id();
+    let mut hosts: Vec<HostInfo> = cpal::available_hosts()
+        .into_iter()
+        .filter_map(|id| {
+            let host = cpal::host_from_id(id).ok()?;
+            let default_device = host.default_output_device().and_then(|d| d.name().ok());
+            let devices = host
+                .output_devices()
+                .map(|devices| {
+                    devices
+                        .filter_map(|device| device_info(&device, default_device.as_deref()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let input_devices = host
+                .input_devices()
+                .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+                .unwrap_or_default();
+            Some(HostInfo {
+                name: id.name().to_string(),
+                is_default: id == default_host,
+                devices,
+                input_devices,
+            })
+        })
+        .collect();
+    hosts.push(null_host_info());
+    hosts
+}
+
+fn null_host_info() -> HostInfo {
+    HostInfo {
+        name: NULL_HOST.to_string(),
+        is_default: false,
+        devices: vec![DeviceInfo {
+            name: NULL_DEVICE.to_string(),
+            is_default: true,
+            channels: null_output::CHANNELS,
+            sample_rates: COMMON_SAMPLE_RATES.to_vec(),
+            default_sample_rate: Some(null_output::DEFAULT_SAMPLE_RATE),
+            min_buffer_size: Some(null_output::MIN_BUFFER_SIZE),
+            max_buffer_size: Some(null_output::MAX_BUFFER_SIZE),
+        }],
+        input_devices: Vec::new(),
+    }
+}
+
+fn device_info(device: &cpal::Device, default_device: Option<&str>) -> Option<DeviceInfo> {
+    let name = device.name().ok()?;
+    let ranges: Vec<_> = device.supported_output_configs().ok()?.collect();
+
+    let sample_rates = COMMON_SAMPLE_RATES
+        .into_iter()
+        .filter(|&rate| {
+            ranges
+                .iter()
+                .any(|r| r.min_sample_rate().0 <= rate && rate <= r.max_sample_rate().0)
+        })
+        .collect();
+
+    let buffer_ranges = ranges.iter().filter_map(|r| match r.buffer_size() {
+        cpal::SupportedBufferSize::Range { min, max } => Some((*min, *max)),
+        cpal::SupportedBufferSize::Unknown => None,
+    });
+    let min_buffer_size = buffer_ranges.clone().map(|(min, _)| min).min();
+    let max_buffer_size = buffer_ranges.map(|(_, max)| max).max();
+
+    Some(DeviceInfo {
+        is_default: default_device == Some(name.as_str()),
+        channels: ranges.iter().map(|r| r.channels()).max().unwrap_or(0),
+        sample_rates,
+        default_sample_rate: device.default_output_config().ok().map(|c| c.sample_rate().0),
+        min_buffer_size,
+        max_buffer_size,
+        name,
+    })
+}
+
+fn find_host(settings: &AudioSettings) -> Result<cpal::Host, String> {
+    let Some(name) = &settings.host else {
+        return Ok(cpal::default_host());
+    };
+    let id = cpal::available_hosts()
+        .into_iter()
+        .find(|id| id.name() == name)
+        .ok_or_else(|| format!("Audio host '{}' is not available", name))?;
+    cpal::host_from_id(id).map_err(|e| e.to_string())
+}
+
+fn find_device(host: &cpal::Host, settings: &AudioSettings) -> Result<cpal::Device, String> {
+    match &settings.device {
+        Some(name) => host
+            .output_devices()
+            .map_err(|e| e.to_string())?
+            .find(|d| d.name().ok().as_deref() == Some(name.as_str()))
+            .ok_or_else(|| format!("Output device '{}' not found", name)),
+        None => host.default_output_device().ok_or_else(|| "No output device available".to_string()),
+    }
+}
+
+fn device_available(settings: &AudioSettings) -> bool {
+    if settings.host.as_deref() == Some(NULL_HOST) {
+        return true;
+    }
+    find_host(settings).and_then(|host| find_device(&host, settings)).is_ok()
+}
+
+/// Whether `active` is the host and device `settings` asks for. Unset fields match any value,
+/// except that only an explicit request selects the null backend.
+fn satisfies(settings: &AudioSettings, active: &ActiveDevice) -> bool {
+    let null_requested = settings.host.as_deref() == Some(NULL_HOST);
+    (active.host == NULL_HOST) == null_requested
+        && settings.host.as_ref().is_none_or(|host| *host == active.host)
+        && settings.device.as_ref().is_none_or(|device| *device == active.device)
+}
+
+/// Routes stream errors back to the device thread, tagged with the stream they came from.
+struct ErrorReporter {
+    requests: Sender<DeviceRequest>,
+    generation: u64,
+}
+
+/// A running output. Dropping it stops the kernel callbacks.
+#[allow(dead_code)] // Only held to keep the stream alive
+enum OutputStream {
+    Device(cpal::Stream, Option<cpal::Stream>), // Output, input
+    Null(NullStream),
+}
+
+/// Open the output selected by `settings`: a cpal device or the null backend.
+fn open_output(
+    kernel: &Arc<Mutex<FluxKernel>>,
+    input: &InputProducer,
+    settings: &AudioSettings,
+    errors: ErrorReporter,
+) -> Result<(OutputStream, ActiveDevice), String> {
+    if settings.host.as_deref() == Some(NULL_HOST) {
+        let (stream, active) = open_null(kernel, settings)?;
+        return Ok((OutputStream::Null(stream), active));
+    }
+    let (stream, mut active) = open_stream(kernel, settings, errors)?;
+
+    // Recording input is optional, the output runs without it
+    let host = find_host(settings)?;
+    let input_stream = match open_input(&host, settings, active.sample_rate, input) {
+        Ok((input_stream, name)) => {
+            active.input_device = Some(name);
+            Some(input_stream)
+        }
+        Err(e) => {
+            eprintln!("Audio input unavailable: {}", e);
+            None
+        }
+    };
+    Ok((OutputStream::Device(stream, input_stream), active))
+}
+
+fn open_null(
+    kernel: &Arc<Mutex<FluxKernel>>,
+    settings: &AudioSettings,
+) -> Result<(NullStream, ActiveDevice), String> {
+    let sample_rate = settings.sample_rate.unwrap_or(null_output::DEFAULT_SAMPLE_RATE);
+    let buffer_size = settings
+        .buffer_size
+        .unwrap_or(null_output::DEFAULT_BUFFER_SIZE)
+        .clamp(null_output::MIN_BUFFER_SIZE, null_output::MAX_BUFFER_SIZE);
+    let stream = NullStream::start(Arc::clone(kernel), sample_rate, buffer_size)?;
+    let active = ActiveDevice {
+        host: NULL_HOST.to_string(),
+        device: NULL_DEVICE.to_string(),
+        sample_rate,
+        buffer_size: Some(buffer_size),
+        channels: null_output::CHANNELS,
+        input_device: None,
+    };
+    Ok((stream, active))
+}
+
+fn null_settings() -> AudioSettings {
+    AudioSettings { host: Some(NULL_HOST.to_string()), ..AudioSettings::default() }
+}
+
+/// Open an output stream that drives the shared kernel.
+/// The kernel is re-initialized for the device sample rate before the stream starts.
+fn open_stream(
+    kernel: &Arc<Mutex<FluxKernel>>,
+    settings: &AudioSettings,
+    errors: ErrorReporter,
+) -> Result<(cpal::Stream, ActiveDevice), String> {
+    let host = find_host(settings)?;
+    let device = find_device(&host, settings)?;
+    let default_config = device.default_output_config().map_err(|e| e.to_string())?;
+
+    let sample_rate = settings.sample_rate.unwrap_or(default_config.sample_rate().0);
+    let channels = default_config.channels();
+    let config = cpal::StreamConfig {
+        channels,
+        sample_rate: cpal::SampleRate(sample_rate),
+        buffer_size: match settings.buffer_size {
+            Some(frames) => cpal::BufferSize::Fixed(frames),
+            None => cpal::BufferSize::Default,
+        },
+    };
+
+    // No stream is running here, so the kernel may allocate
+    kernel
+        .lock()
+        .map_err(|_| "Audio kernel lock poisoned")?
+        .set_sample_rate(sample_rate as f32);
+
+    let callback_kernel = Arc::clone(kernel);
+    let frame_channels = channels as usize;
+    let stream = device
+        .build_output_stream(
+            &config,
+            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                // Only contended while the device thread reconfigures the kernel
+                match callback_kernel.try_lock() {
+                    Ok(mut kernel) => kernel.process(data, frame_channels),
+                    Err(_) => data.fill(0.0),
+                }
+            },
+            move |err| {
+                eprintln!("Stream error: {}", err);
+                let _ = errors.requests.send(DeviceRequest::StreamError(errors.generation, err.to_string()));
+            },
+            None, // Timeout
+        )
+        .map_err(|e| e.to_string())?;
+    stream.play().map_err(|e| e.to_string())?;
+
+    let active = ActiveDevice {
+        host: host.id().name().to_string(),
+        device: device.name().unwrap_or_else(|_| "Unknown".to_string()),
+        sample_rate,
+        buffer_size: settings.buffer_size,
+        channels,
+        input_device: None,
+    };
+    Ok((stream, active))
+}
+
+/// Open an input stream at the output's sample rate that feeds the kernel's input queue.
+fn open_input(
+    host: &cpal::Host,
+    settings: &AudioSettings,
+    sample_rate: u32,
+    input: &InputProducer,
+) -> Result<(cpal::Stream, String), String> {
+    let device = match &settings.input_device {
+        Some(name) => host
+            .input_devices()
+            .map_err(|e| e.to_string())?
+            .find(|d| d.name().ok().as_deref() == Some(name.as_str()))
+            .ok_or_else(|| format!("Input device '{}' not found", name))?,
+        None => host.default_input_device().ok_or_else(|| "No input device available".to_string())?,
+    };
+    let channels = device.default_input_config().map_err(|e| e.to_string())?.channels();
+    let config = cpal::StreamConfig {
+        channels,
+        sample_rate: cpal::SampleRate(sample_rate),
+        buffer_size: cpal::BufferSize::Default,
+    };
+
+    let producer = Arc::clone(input);
+    let frame_channels = channels as usize;
+    let stream = device
+        .build_input_stream(
+            &config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                // Only contended while a rebuilt input stream takes over
+                if let Ok(mut producer) = producer.try_lock() {
+                    push_input(&mut producer, data, frame_channels);
+                }
+            },
+            |err| eprintln!("Input stream error: {}", err),
+            None, // Timeout
+        )
+        .map_err(|e| e.to_string())?;
+    stream.play().map_err(|e| e.to_string())?;
+
+    let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+    Ok((stream, name))
+}
+
+pub enum DeviceRequest {
+    Apply(AudioSettings, Sender<Result<ActiveDevice, String>>),
+    Status(Sender<AudioStatus>),
+    StreamError(u64, String), // Stream generation, error
+}
+
+/// Handle to the device thread, managed as Tauri state.
+pub struct AudioDeviceState {
+    requests: Mutex<Sender<DeviceRequest>>,
+}
+
+impl AudioDeviceState {
+    /// Rebuild the output stream with new settings. Keeps the previous stream on failure.
+    pub fn apply(&self, settings: AudioSettings) -> Result<ActiveDevice, String> {
+        let (reply, response) = mpsc::channel();
+        self.send(DeviceRequest::Apply(settings, reply))?;
+        response.recv_timeout(REQUEST_TIMEOUT).map_err(|e| e.to_string())?
+    }
+
+    pub fn status(&self) -> Result<AudioStatus, String> {
+        let (reply, response) = mpsc::channel();
+        self.send(DeviceRequest::Status(reply))?;
+        response.recv_timeout(REQUEST_TIMEOUT).map_err(|e| e.to_string())
+    }
+
+    pub fn active(&self) -> Result<Option<ActiveDevice>, String> {
+        self.status().map(|status| status.device)
+    }
+
+    fn send(&self, request: DeviceRequest) -> Result<(), String> {
+        self.requests
+            .lock()
+            .map_err(|_| "Failed to lock mutex")?
+            .send(request)
+            .map_err(|_| "Audio device thread is not running".to_string())
+    }
+}
+
+/// State owned by the device thread. The kernel outlives every stream,
+/// so transport and pattern survive restarts and device changes.
+struct DeviceThread<F> {
+    kernel: Arc<Mutex<FluxKernel>>,
+    input: InputProducer,
+    requests: Sender<DeviceRequest>, // Cloned into stream error callbacks
+    on_status: F,
+    preferred: AudioSettings, // Last applied settings, restored when the device returns
+    preferred_seen: bool,     // Preferred device was present at the last check
+    current: Option<(OutputStream, ActiveDevice)>,
+    generation: u64, // Incremented per opened stream; errors from older streams are ignored
+    message: Option<String>,
+}
+
+impl<F: Fn(&AudioStatus)> DeviceThread<F> {
+    /// Stop the current stream (devices are often exclusive) and open `settings`.
+    fn open(&mut self, settings: &AudioSettings) -> Result<ActiveDevice, String> {
+        self.current = None;
+        self.generation += 1;
+        let errors = ErrorReporter { requests: self.requests.clone(), generation: self.generation };
+        let (stream, active) = open_output(&self.kernel, &self.input, settings, errors)?;
+        self.current = Some((stream, active.clone()));
+        Ok(active)
+    }
+
+    /// Open `settings`, falling back to the system default output and then to the null backend,
+    /// so the kernel keeps running on machines without a sound card.
+    fn open_with_fallback(&mut self, settings: &AudioSettings) {
+        let mut error = match self.open(settings) {
+            Ok(_) => return,
+            Err(e) => e,
+        };
+        if *settings != AudioSettings::default() {
+            eprintln!("Audio device unavailable ({}), using the default output", error);
+            error = match self.open(&AudioSettings::default()) {
+                Ok(_) => return,
+                Err(e) => e,
+            };
+        }
+        eprintln!("Audio output unavailable ({}), running headless", error);
+        if let Err(e) = self.open(&null_settings()) {
+            eprintln!("Failed to start the null audio backend: {}", e);
+        }
+    }
+
+    fn is_fallback(&self) -> bool {
+        self.current
+            .as_ref()
+            .is_some_and(|(_, active)| !satisfies(&self.preferred, active))
+    }
+
+    fn status(&self) -> AudioStatus {
+        let state = match &self.current {
+            None => StreamState::Stopped,
+            Some(_) if self.is_fallback() => StreamState::Fallback,
+            Some(_) => StreamState::Running,
+        };
+        AudioStatus {
+            state,
+            device: self.current.as_ref().map(|(_, active)| active.clone()),
+            message: self.message.clone(),
+        }
+    }
+
+    fn emit(&self) {
+        (self.on_status)(&self.status());
+    }
+
+    fn handle(&mut self, request: DeviceRequest) {
+        match request {
+            DeviceRequest::Apply(settings, reply) => {
+                let previous = self.current.as_ref().map(|(_, active)| active.settings());
+                let result = self.open(&settings);
+                match &result {
+                    Ok(_) => {
+                        self.preferred = settings;
+                        self.preferred_seen = true;
+                        self.message = None;
+                    }
+                    Err(e) => {
+                        self.open_with_fallback(&previous.unwrap_or_default());
+                        self.message = Some(e.clone());
+                    }
+                }
+                self.emit();
+                let _ = reply.send(result);
+            }
+            DeviceRequest::Status(reply) => {
+                let _ = reply.send(self.status());
+            }
+            DeviceRequest::StreamError(generation, error) => self.recover(generation, error),
+        }
+    }
+
+    /// Restart the failed stream; if its device is gone, fall back until it returns.
+    fn recover(&mut self, generation: u64, error: String) {
+        if generation != self.generation {
+            return; // From a stream that was already replaced
+        }
+        let Some((stream, failed)) = self.current.take() else {
+            return;
+        };
+        drop(stream);
+        self.message = Some(error);
+        (self.on_status)(&AudioStatus {
+            state: StreamState::Recovering,
+            device: None,
+            message: self.message.clone(),
+        });
+
+        // Transient errors (driver resets, xruns) recover on the same device
+        for _ in 0..RESTART_ATTEMPTS {
+            thread::sleep(RESTART_DELAY);
+            if self.open(&failed.settings()).is_ok() {
+                self.emit();
+                return;
+            }
+        }
+
+        self.preferred_seen = false;
+        let preferred = self.preferred.clone();
+        self.open_with_fallback(&preferred);
+        self.emit();
+    }
+
+    /// Switch back to the preferred device once it reappears.
+    fn poll_reconnect(&mut self) {
+        if !self.is_fallback() {
+            return;
+        }
+        let available = device_available(&self.preferred);
+        let appeared = available && !self.preferred_seen;
+        self.preferred_seen = available;
+        if !appeared {
+            return; // Missing, or present but failed to open: don't retry in a loop
+        }
+        let preferred = self.preferred.clone();
+        self.open_with_fallback(&preferred);
+        if !self.is_fallback() {
+            self.message = None;
+        }
+        self.emit();
+    }
+}
+
+/// Start the device thread. It owns the output and input streams (cpal streams are not `Send`)
+/// and opens `settings`, falling back to the default output or the null backend.
+/// The input stream, if any, feeds `input`.
+/// `on_status` is called on every state change.
+pub fn spawn_device_thread<F>(
+    kernel: Arc<Mutex<FluxKernel>>,
+    input: InputProducer,
+    settings: AudioSettings,
+    on_status: F,
+) -> AudioDeviceState
+where
+    F: Fn(&AudioStatus) + Send + 'static,
+{
+    let (requests, receiver) = mpsc::channel::<DeviceRequest>();
+    let thread_requests = requests.clone();
+
+    thread::spawn(move || {
+        let mut device = DeviceThread {
+            kernel,
+            input,
+            requests: thread_requests,
+            on_status,
+            preferred: settings.clone(),
+            preferred_seen: false,
+            current: None,
+            generation: 0,
+            message: None,
+        };
+        device.open_with_fallback(&settings);
+        device.preferred_seen = !device.is_fallback() || device_available(&settings);
+        device.emit();
+
+        loop {
+            match receiver.recv_timeout(RECONNECT_INTERVAL) {
+                Ok(request) => device.handle(request),
+                Err(RecvTimeoutError::Timeout) => device.poll_reconnect(),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    });
+
+    AudioDeviceState { requests: Mutex::new(requests) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::domain::AudioSnapshot;
+    use crate::engine::kernel::AudioCommand;
+    use rtrb::RingBuffer;
+
+    fn active(host: &str, device: &str) -> ActiveDevice {
+        ActiveDevice {
+            host: host.to_string(),
+            device: device.to_string(),
+            sample_rate: 48000,
+            buffer_size: None,
+            channels: 2,
+            input_device: None,
+        }
+    }
+
+    #[test]
+    fn test_satisfies_preferred_device() {
+        let any = AudioSettings::default();
+        assert!(satisfies(&any, &active("ALSA", "default")));
+        assert!(!satisfies(&any, &active(NULL_HOST, NULL_DEVICE)));
+        assert!(satisfies(&null_settings(), &active(NULL_HOST, NULL_DEVICE)));
+
+        let usb = AudioSettings {
+            host: Some("ALSA".to_string()),
+            device: Some("USB Audio".to_string()),
+            ..AudioSettings::default()
+        };
+        assert!(satisfies(&usb, &active("ALSA", "USB Audio")));
+        assert!(!satisfies(&usb, &active("ALSA", "default")));
+        assert!(!satisfies(&usb, &active(NULL_HOST, NULL_DEVICE)));
+    }
+
+    #[test]
+    fn test_stream_error_restarts_and_keeps_state() {
+        let (mut producer, consumer) = RingBuffer::new(16);
+        let (snapshot_prod, _) = triple_buffer::TripleBuffer::new(&AudioSnapshot::default()).split();
+        let kernel = Arc::new(Mutex::new(FluxKernel::new(44100.0, consumer, snapshot_prod)));
+        kernel.lock().unwrap().pattern.bpm = 140.0;
+        producer.push(AudioCommand::Play).unwrap();
+
+        let (status_tx, statuses) = mpsc::channel();
+        let (input, _) = crate::engine::recorder::input_queue();
+        let state = spawn_device_thread(Arc::clone(&kernel), input, null_settings(), move |status| {
+            let _ = status_tx.send(status.clone());
+        });
+        let initial = statuses.recv_timeout(REQUEST_TIMEOUT).unwrap();
+        assert_eq!(initial.state, StreamState::Running);
+        assert_eq!(initial.device.unwrap().host, NULL_HOST);
+
+        // Errors from a replaced stream are ignored
+        state.send(DeviceRequest::StreamError(0, "stale".to_string())).unwrap();
+        assert_eq!(state.status().unwrap().state, StreamState::Running);
+        assert!(statuses.try_recv().is_err());
+
+        state.send(DeviceRequest::StreamError(1, "device lost".to_string())).unwrap();
+        let recovering = statuses.recv_timeout(REQUEST_TIMEOUT).unwrap();
+        assert_eq!(recovering.state, StreamState::Recovering);
+        assert_eq!(recovering.message.as_deref(), Some("device lost"));
+        let restarted = statuses.recv_timeout(REQUEST_TIMEOUT).unwrap();
+        assert_eq!(restarted.state, StreamState::Running);
+
+        let playhead = kernel.lock().unwrap().playhead_sample;
+        thread::sleep(Duration::from_millis(50));
+        let kernel = kernel.lock().unwrap();
+        assert!(kernel.is_playing);
+        assert_eq!(kernel.pattern.bpm, 140.0);
+        assert!(kernel.playhead_sample > playhead);
+    }
+}
@@ -0,0 +1,295 @@
+use rtrb::{Consumer, Producer, RingBuffer};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Emitted with each batch of new `LogRecord`s.
+pub const LOG_EVENT: &str = "log-records";
+pub const DIAGNOSTICS_QUEUE_SIZE: usize = 1024; // Events per real-time thread
+pub const RECENT_LOG_LEN: usize = 500; // Kept for `get_recent_logs`
+pub const LOG_FILE_NAME: &str = "flux.log";
+pub const LOG_FILE_MAX_BYTES: u64 = 1024 * 1024;
+pub const LOG_FILES_KEPT: usize = 3; // flux.log, flux.log.1, flux.log.2
+const LOGGER_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// Thread that reported an event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogSource {
+    Audio,
+    Midi,
+}
+
+/// A diagnostic event from a real-time thread. `Copy` and fixed size, so sending never allocates;
+/// the logger thread turns it into a `LogRecord`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(tag = "event")]
+pub enum Diagnostic {
+    Trigger { step: usize, track: usize, frequency: f32 },
+    RecordingDropped { frames: usize }, // Finished recording with nowhere to go
+    ThreadPriority { source: LogSource, raised: bool },
+    MidiHeartbeat { tick: u64, drift_ms: f32 },
+    MidiJitter { tick: u64, drift_ms: f32 }, // Drift over MIDI_JITTER_WARN_MS
+    Dropped { count: u64 }, // Events lost because the queue was full, reported by the logger
+}
+
+pub const MIDI_JITTER_WARN_MS: f32 = 0.5;
+
+impl Diagnostic {
+    pub fn level(&self) -> LogLevel {
+        match self {
+            Diagnostic::Trigger { .. } | Diagnostic::MidiHeartbeat { .. } => LogLevel::Debug,
+            Diagnostic::ThreadPriority { raised: true, .. } => LogLevel::Info,
+            Diagnostic::ThreadPriority { raised: false, .. }
+            | Diagnostic::MidiJitter { .. }
+            | Diagnostic::Dropped { .. } => LogLevel::Warn,
+            Diagnostic::RecordingDropped { .. } => LogLevel::Error,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Diagnostic::Trigger { step, track, frequency } => {
+                format!("Step {} trig on track {} ({:.2} Hz)", step, track, frequency)
+            }
+            Diagnostic::RecordingDropped { frames } => {
+                format!("Recording of {} frames dropped, the recordings queue is full", frames)
+            }
+            Diagnostic::ThreadPriority { source, raised: true } => format!("{:?} thread priority raised", source),
+            Diagnostic::ThreadPriority { source, raised: false } => {
+                format!("Could not raise the {:?} thread priority", source)
+            }
+            Diagnostic::MidiHeartbeat { tick, drift_ms } => format!("Heartbeat: tick {}, drift {:.3} ms", tick, drift_ms),
+            Diagnostic::MidiJitter { tick, drift_ms } => {
+                format!("High MIDI clock jitter at tick {}: {:.3} ms", tick, drift_ms)
+            }
+            Diagnostic::Dropped { count } => format!("{} diagnostic events dropped", count),
+        }
+    }
+}
+
+/// A timestamped, leveled log line, as written to the log file and sent to the UI.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LogRecord {
+    pub timestamp_ms: u64, // Unix time
+    pub level: LogLevel,
+    pub source: LogSource,
+    pub message: String,
+    pub fields: Diagnostic,
+}
+
+impl LogRecord {
+    fn new(source: LogSource, diagnostic: Diagnostic) -> Self {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        Self { timestamp_ms, level: diagnostic.level(), source, message: diagnostic.message(), fields: diagnostic }
+    }
+}
+
+/// Real-time end of a diagnostics queue. Never blocks; events are dropped when the queue is full.
+pub struct DiagnosticSender {
+    producer: Option<Producer<Diagnostic>>,
+    dropped: u64, // Since the last event that got through
+}
+
+impl DiagnosticSender {
+    /// A sender without a logger: events are discarded. Only for offline rendering and tests.
+    pub fn disconnected() -> Self {
+        Self { producer: None, dropped: 0 }
+    }
+
+    pub fn send(&mut self, diagnostic: Diagnostic) {
+        let Some(producer) = &mut self.producer else {
+            return;
+        };
+        if self.dropped > 0 && producer.slots() >= 2 {
+            let _ = producer.push(Diagnostic::Dropped { count: self.dropped });
+            self.dropped = 0;
+        }
+        if producer.push(diagnostic).is_err() {
+            self.dropped += 1;
+        }
+    }
+}
+
+/// Logger-thread end of a diagnostics queue, tagged with its thread.
+pub struct DiagnosticReceiver {
+    source: LogSource,
+    consumer: Consumer<Diagnostic>,
+}
+
+pub fn diagnostics_queue(source: LogSource) -> (DiagnosticSender, DiagnosticReceiver) {
+    let (producer, consumer) = RingBuffer::new(DIAGNOSTICS_QUEUE_SIZE);
+    (DiagnosticSender { producer: Some(producer), dropped: 0 }, DiagnosticReceiver { source, consumer })
+}
+
+/// Log file that rolls over to `<name>.1`, `<name>.2`, ... at `max_bytes`.
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, max_bytes: u64, keep: usize) -> std::io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let written = file.metadata()?.len();
+        Ok(Self { path: path.to_path_buf(), max_bytes, keep: keep.max(1), file, written })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        for index in (1..self.keep).rev() {
+            let from = if index == 1 { self.path.clone() } else { self.rotated_path(index - 1) };
+            if from.exists() {
+                std::fs::rename(&from, self.rotated_path(index))?;
+            }
+        }
+        if self.keep == 1 {
+            std::fs::remove_file(&self.path)?;
+        }
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+
+    pub fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        if self.written > 0 && self.written + line.len() as u64 + 1 > self.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.written += line.len() as u64 + 1;
+        Ok(())
+    }
+}
+
+/// Recent records for the debug panel.
+#[derive(Debug, Default)]
+pub struct RecentLogs {
+    records: VecDeque<LogRecord>,
+}
+
+impl RecentLogs {
+    pub fn extend(&mut self, records: &[LogRecord]) {
+        for record in records {
+            if self.records.len() == RECENT_LOG_LEN {
+                self.records.pop_front();
+            }
+            self.records.push_back(record.clone());
+        }
+    }
+
+    /// Oldest first.
+    pub fn to_vec(&self) -> Vec<LogRecord> {
+        self.records.iter().cloned().collect()
+    }
+}
+
+/// Drain the diagnostics queues, append JSON lines to `file` and pass each batch to `on_records`.
+/// Runs until every sender is gone.
+pub fn spawn_logger<F>(
+    mut receivers: Vec<DiagnosticReceiver>,
+    mut file: Option<RotatingFile>,
+    mut on_records: F,
+) -> thread::JoinHandle<()>
+where
+    F: FnMut(&[LogRecord]) + Send + 'static,
+{
+    thread::spawn(move || loop {
+        let mut records = Vec::new();
+        for receiver in &mut receivers {
+            while let Ok(diagnostic) = receiver.consumer.pop() {
+                records.push(LogRecord::new(receiver.source, diagnostic));
+            }
+        }
+        if !records.is_empty() {
+            if let Some(log) = &mut file {
+                for record in &records {
+                    let written = serde_json::to_string(record)
+                        .map_err(std::io::Error::other)
+                        .and_then(|line| log.write_line(&line));
+                    if let Err(e) = written {
+                        eprintln!("Failed to write the log file, logging to the UI only: {}", e);
+                        file = None;
+                        break;
+                    }
+                }
+            }
+            on_records(&records);
+        }
+        if receivers.iter().all(|r| r.consumer.is_abandoned()) {
+            break;
+        }
+        thread::sleep(LOGGER_INTERVAL);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_queue_reports_dropped_events() {
+        let (mut sender, mut receiver) = diagnostics_queue(LogSource::Audio);
+        let trig = Diagnostic::Trigger { step: 0, track: 0, frequency: 440.0 };
+        for _ in 0..DIAGNOSTICS_QUEUE_SIZE + 3 {
+            sender.send(trig);
+        }
+        for _ in 0..DIAGNOSTICS_QUEUE_SIZE {
+            assert_eq!(receiver.consumer.pop(), Ok(trig));
+        }
+        sender.send(trig);
+        assert_eq!(receiver.consumer.pop(), Ok(Diagnostic::Dropped { count: 3 }));
+        assert_eq!(receiver.consumer.pop(), Ok(trig));
+    }
+
+    #[test]
+    fn test_record_levels_and_json() {
+        let record = LogRecord::new(LogSource::Midi, Diagnostic::MidiJitter { tick: 1000, drift_ms: 0.75 });
+        assert_eq!(record.level, LogLevel::Warn);
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["source"], "Midi");
+        assert_eq!(json["fields"]["event"], "MidiJitter");
+        assert_eq!(json["fields"]["tick"], 1000);
+    }
+
+    #[test]
+    fn test_file_rotation() {
+        let dir = std::env::temp_dir().join(format!("flux_log_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join(LOG_FILE_NAME);
+
+        let mut file = RotatingFile::open(&path, 20, 3).unwrap();
+        for line in ["first line", "second line", "third line", "fourth line"] {
+            file.write_line(line).unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "fourth line\n");
+        assert_eq!(std::fs::read_to_string(dir.join("flux.log.1")).unwrap(), "third line\n");
+        assert_eq!(std::fs::read_to_string(dir.join("flux.log.2")).unwrap(), "second line\n");
+        assert!(!dir.join("flux.log.3").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,12 +1,51 @@
+use crate::engine::meters::MeterLevels;
+use crate::engine::metrics::EngineMetrics;
 use serde::Serialize;
 
-#[derive(Clone, Debug, Default, Serialize)]
+/// Transport and engine state, published by the audio thread after every callback.
+/// Fixed size and `Copy`, so publishing and reading it never allocates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
 pub struct AudioSnapshot {
+    pub sequence: u64, // Steps advanced since the kernel started; a jump of more than 1 means skipped steps
     pub current_step: usize,
     pub is_playing: bool,
-    pub triggered_tracks: Vec<bool>,
+    pub triggered_tracks: [bool; MAX_TRACKS], // Tracks with a trig on `current_step`
+    pub pattern_slot: usize, // Bank slot playing
+    pub queued_pattern: Option<usize>, // Bank slot waiting to take over
+    pub song_row: Option<usize>, // Song row playing, None outside song mode
+    pub metrics: EngineMetrics,
+    pub levels: MeterLevels,
 }
 
+// Engine capacity
+pub const MAX_TRACKS: usize = 16; // Tonverk standard
+pub const NUM_SEND_BUSES: usize = 2; // Reverb, Delay
+pub const MAX_SAMPLES: usize = 128; // Sample pool slots
+
 // Parameter Indices
 pub const PARAM_PITCH: usize = 0; // MIDI Note Number (0.0 - 127.0)
 pub const PARAM_DECAY: usize = 1; // 0.0 to 1.0
+pub const PARAM_FILTER_CUTOFF: usize = 2; // 0.0 (20 Hz) to 1.0 (20 kHz)
+pub const PARAM_RESONANCE: usize = 3; // 0.0 to 1.0
+pub const PARAM_DRIVE: usize = 4; // 0.0 (clean) to 1.0
+pub const PARAM_SUSTAIN: usize = 5; // 0.0 to 1.0
+pub const PARAM_REVERB_SEND: usize = 6; // 0.0 to 1.0
+pub const PARAM_DELAY_SEND: usize = 7; // 0.0 to 1.0
+pub const PARAM_VOLUME: usize = 8; // 0.0 to 1.0
+pub const PARAM_PAN: usize = 9; // 0.0 (left) to 1.0 (right), 0.5 = center
+pub const PARAM_TUNING: usize = 10; // Fine tune, 0.0 (-100 cents) to 1.0 (+100 cents), 0.5 = no detune
+pub const PARAM_COARSE_TUNE: usize = 11; // 0.0 (-24 semitones) to 1.0 (+24 semitones), 0.5 = none
+
+// Parameters a track channel can be modulated on (IDs 0..NUM_SYNTH_PARAMS)
+pub const NUM_SYNTH_PARAMS: usize = 16;
+
+// Arpeggiator (P-Lockable, read at the trig)
+pub const PARAM_ARP_MODE: usize = 16; // Up, Down, Up-Down, Random, As Played across 0.0 to 1.0
+pub const PARAM_ARP_RATE: usize = 17; // 1/32, 1/16T, 1/16, 1/8T, 1/8, 1/4 across 0.0 to 1.0
+pub const PARAM_ARP_OCTAVES: usize = 18; // 1 to 4 octaves across 0.0 to 1.0
+pub const PARAM_ARP_GATE: usize = 19; // Note length, fraction of the arp rate
+
+// Mod Matrix
+pub const MAX_MOD_SLOTS: usize = 16; // Per track
+pub const PARAM_MOD_DEPTH_BASE: usize = 64; // P-Lock IDs 64..80 hold slot depths (-1.0 to 1.0)
+pub const MOD_ENVELOPES_PER_TRACK: usize = 2;
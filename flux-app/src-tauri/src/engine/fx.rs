@@ -0,0 +1,120 @@
+// Send bus effects. All buffers are allocated up front; `process` never allocates.
+
+const MAX_DELAY_SECONDS: f32 = 2.0;
+
+/// Stereo feedback delay, tempo-synced by the kernel.
+pub struct DelayBus {
+    buffers: [Vec<f32>; 2],
+    write_pos: usize,
+    delay_samples: usize,
+    feedback: f32,
+}
+
+impl DelayBus {
+    pub fn new(sample_rate: f32) -> Self {
+        let len = (sample_rate * MAX_DELAY_SECONDS) as usize + 1;
+        Self {
+            buffers: [vec![0.0; len], vec![0.0; len]],
+            write_pos: 0,
+            delay_samples: (sample_rate * 0.375) as usize, // 3/16 at 120 BPM
+            feedback: 0.4,
+        }
+    }
+
+    pub fn set_delay_samples(&mut self, samples: usize) {
+        self.delay_samples = samples.clamp(1, self.buffers[0].len() - 1);
+    }
+
+    pub fn process(&mut self, input: [f32; 2]) -> [f32; 2] {
+        let len = self.buffers[0].len();
+        let read_pos = (self.write_pos + len - self.delay_samples) % len;
+        let out = [self.buffers[0][read_pos], self.buffers[1][read_pos]];
+        // Ping-pong: feed each channel into the other
+        self.buffers[0][self.write_pos] = input[0] + out[1] * self.feedback;
+        self.buffers[1][self.write_pos] = input[1] + out[0] * self.feedback;
+        self.write_pos = (self.write_pos + 1) % len;
+        out
+    }
+}
+
+struct Comb {
+    buffer: Vec<f32>,
+    pos: usize,
+    feedback: f32,
+    damp: f32,
+    store: f32,
+}
+
+impl Comb {
+    fn new(len: usize) -> Self {
+        Self { buffer: vec![0.0; len.max(1)], pos: 0, feedback: 0.84, damp: 0.2, store: 0.0 }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let out = self.buffer[self.pos];
+        self.store = out * (1.0 - self.damp) + self.store * self.damp;
+        self.buffer[self.pos] = input + self.store * self.feedback;
+        self.pos = (self.pos + 1) % self.buffer.len();
+        out
+    }
+}
+
+struct Allpass {
+    buffer: Vec<f32>,
+    pos: usize,
+}
+
+impl Allpass {
+    fn new(len: usize) -> Self {
+        Self { buffer: vec![0.0; len.max(1)], pos: 0 }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.pos];
+        let out = delayed - input;
+        self.buffer[self.pos] = input + delayed * 0.5;
+        self.pos = (self.pos + 1) % self.buffer.len();
+        out
+    }
+}
+
+// Freeverb tunings at 44.1 kHz
+const COMB_TUNING: [usize; 4] = [1116, 1188, 1277, 1356];
+const ALLPASS_TUNING: [usize; 2] = [556, 441];
+const STEREO_SPREAD: usize = 23;
+
+/// Small Schroeder/Freeverb style stereo reverb.
+pub struct ReverbBus {
+    combs: [Vec<Comb>; 2],
+    allpasses: [Vec<Allpass>; 2],
+}
+
+impl ReverbBus {
+    pub fn new(sample_rate: f32) -> Self {
+        let scale = sample_rate / 44100.0;
+        let scaled = |len: usize, spread: usize| ((len + spread) as f32 * scale) as usize;
+        let combs_for = |spread| COMB_TUNING.iter().map(|&l| Comb::new(scaled(l, spread))).collect();
+        let allpasses_for =
+            |spread| ALLPASS_TUNING.iter().map(|&l| Allpass::new(scaled(l, spread))).collect();
+        Self {
+            combs: [combs_for(0), combs_for(STEREO_SPREAD)],
+            allpasses: [allpasses_for(0), allpasses_for(STEREO_SPREAD)],
+        }
+    }
+
+    pub fn process(&mut self, input: [f32; 2]) -> [f32; 2] {
+        let mono = (input[0] + input[1]) * 0.5 * 0.1; // Fixed input gain
+        let mut out = [0.0; 2];
+        for (ch, sample) in out.iter_mut().enumerate() {
+            let mut acc = 0.0;
+            for comb in self.combs[ch].iter_mut() {
+                acc += comb.process(mono);
+            }
+            for allpass in self.allpasses[ch].iter_mut() {
+                acc = allpass.process(acc);
+            }
+            *sample = acc;
+        }
+        out
+    }
+}
@@ -1,24 +1,68 @@
-use crate::shared::models::{AtomicStep, MachineType, Pattern, Subtrack, Track, TrigType};
-use crate::engine::domain::AudioSnapshot;
+use crate::shared::models::{
+    AtomicStep, MachineType, Pattern, PatternSwitch, Song, Subtrack, Track, TrigType, Tuning, MAX_SUBTRACKS, PATTERN_SLOTS,
+};
+use crate::engine::analyzer::AnalyzerTap;
+use crate::engine::arpeggiator::{ArpSettings, ArpState};
+use crate::engine::arranger::Arranger;
+use crate::engine::chord::{chord_notes, MAX_CHORD_NOTES};
+use crate::engine::diagnostics::{Diagnostic, DiagnosticSender};
+use crate::engine::domain::{
+    AudioSnapshot, MAX_SAMPLES, MAX_TRACKS, MOD_ENVELOPES_PER_TRACK, NUM_SEND_BUSES, NUM_SYNTH_PARAMS,
+    PARAM_COARSE_TUNE, PARAM_PITCH,
+};
+use crate::engine::fx::{DelayBus, ReverbBus};
+use crate::engine::lfo::random_bipolar;
+use crate::engine::meters::MeterBank;
+use crate::engine::metrics::LoadMeter;
+use crate::engine::modulation::{self, EnvelopeState, TrigSources};
+use crate::engine::recorder::{RecordSource, RecordStart, Recording, RecordingSender, MAX_INPUT_LATENCY};
+use crate::engine::render_pool::{RenderPool, MIN_PARALLEL_FRAMES};
+use crate::engine::sample_pool::SampleBuffer;
+use crate::engine::scheduler::{BlockEvent, EventQueue};
+use crate::engine::sync::{Garbage, GarbageSender};
+use crate::engine::tuning::{coarse_tune_semitones, note_frequency};
+use crate::engine::voice::{midi_to_freq, resolve_param, ChannelFrame, TrackChannel};
 use rtrb::Consumer;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use triple_buffer::Input;
-use std::f32::consts::PI;
 
-// Helper to convert MIDI note to Hz
-fn midi_to_freq(note: f32) -> f32 {
-    440.0 * 2.0_f32.powf((note - 69.0) / 12.0)
-}
+pub const CONTROL_RATE_SAMPLES: usize = 64; // Modulation update interval
+pub const MAX_BLOCK_FRAMES: usize = 512; // Longer device buffers are rendered in several blocks
 
 pub enum AudioCommand {
     Play,
     Stop,
     SetGlobalVolume(f32),
-    ToggleStep(usize, usize),
-    SetParamLock(usize, usize, usize, Option<f32>), // Track, Step, Param, Value
+    ToggleStep(usize, usize, usize),                       // Track, Subtrack, Step
+    SetParamLock(usize, usize, usize, usize, Option<f32>), // Track, Subtrack, Step, Param, Value
+    SwapPattern(usize, Box<Pattern>),    // Bank slot; the playing pattern is replaced at the next step boundary
+    SwapTrack(usize, usize, Box<Track>), // Bank slot, Track index; the playing pattern's at the next step boundary
+    QueuePattern(usize, PatternSwitch),  // Bank slot to play next, right away while stopped
+    SetSong(Box<Song>),
+    LoadSample(u16, Option<Arc<SampleBuffer>>), // Pool slot, None clears it
+    StartRecording(Box<Recording>), // Replaces (cancels) a recording in progress
+    StopRecording,
+    SetAnalyzerTrack(Option<usize>), // Track analyzed next to the master
+    NoteInput(usize, u8, u8),        // Track, Note, Velocity (0 releases): held notes for the arpeggiator
+}
+
+/// Signal taps for the most recently rendered frame.
+/// Read by the offline renderer to capture stems.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameTaps {
+    pub track_pre_fx: [[f32; 2]; MAX_TRACKS],
+    pub track_post_fx: [[f32; 2]; MAX_TRACKS],
+    pub sends: [[f32; 2]; NUM_SEND_BUSES], // Reverb, Delay returns
+    pub master: [f32; 2],
 }
 
 pub struct FluxKernel {
     pub pattern: Pattern,
+    pub pattern_slot: usize, // Bank slot of `pattern`
+    pub patterns: Vec<Option<Box<Pattern>>>, // The other bank slots, PATTERN_SLOTS long; the playing slot is kept empty
+    pub queued_pattern: Option<(usize, PatternSwitch)>,
+    pub song: Box<Song>,
     pub is_playing: bool,
     pub playhead_sample: usize,
     pub sample_rate: f32,
@@ -30,10 +74,44 @@ pub struct FluxKernel {
     pub samples_per_step: f32,
     pub step_phase: f32,
     pub current_step: usize,
+    pub step_sequence: u64, // Steps advanced since the kernel started, published as `AudioSnapshot::sequence`
+    pub frame_clock: u64, // Frames rendered so far, playing or not
+    pub events: EventQueue, // Events of the block being rendered
+    pub gate_ends: [[Option<u64>; MAX_SUBTRACKS]; MAX_TRACKS], // `frame_clock` at which each voice layer's gate closes
+    pub control_countdown: usize, // Samples until the next modulation update
+    pub trig_sources: [TrigSources; MAX_TRACKS], // Mod matrix inputs latched per track
+    pub mod_envelopes: [[EnvelopeState; MOD_ENVELOPES_PER_TRACK]; MAX_TRACKS],
+    pub arps: [ArpState; MAX_TRACKS],
+    pub arp_next: [Option<f64>; MAX_TRACKS], // Frame of each track's next arp note, fractional so synced rates don't drift
+    pub bar_step: usize, // Step within the transport's bar, for bar-synced pattern switches
+    pub arranger: Arranger, // Song position while song mode is on
+    pub song_mutes: [bool; MAX_TRACKS], // Tracks muted by the song row playing
+
+    // Pattern Hot-Swap State (boxes are allocated by the sender, freed by the garbage thread)
+    pub pending_pattern: Option<Box<Pattern>>,
+    pub pending_tracks: [Option<Box<Track>>; MAX_TRACKS],
+    pub garbage: GarbageSender,
 
-    // Voice State
-    pub current_frequency: f32,
-    pub current_decay: f32,
+    // Mixer State
+    pub channels: Vec<TrackChannel>, // One per track slot, pre-allocated
+    pub track_frames: Vec<Vec<ChannelFrame>>, // Per-channel render buffers, MAX_BLOCK_FRAMES each
+    pub render_pool: Option<RenderPool>, // Worker threads for the channels; None renders them inline
+    pub reverb: ReverbBus,
+    pub delay: DelayBus,
+    pub master_volume: f32,
+    pub block: Vec<[f32; 2]>, // Stereo render buffer, MAX_BLOCK_FRAMES long
+    pub taps: FrameTaps,
+    pub meters: MeterBank, // Track and master levels, fed from `taps`
+    pub analyzer: AnalyzerTap, // Scope and spectrum blocks for the analyzer thread
+
+    // Sampling
+    pub samples: Vec<Option<Arc<SampleBuffer>>>, // Mirror of the sample pool, pre-allocated
+    pub input: Option<Consumer<[f32; 2]>>,       // Audio input frames, None without an input queue
+    pub recording: Option<Box<Recording>>,
+    pub recordings: RecordingSender, // Finished recordings go to the collector thread
+
+    pub load_meter: LoadMeter,
+    pub diagnostics: DiagnosticSender, // Log events for the logger thread, never printed here
 }
 
 impl FluxKernel {
@@ -67,15 +145,24 @@ impl FluxKernel {
             subtracks: vec![subtrack],
             length: 16,
             scale: 1.0,
+            default_params: crate::shared::models::default_track_params(),
             lfos: Vec::new(),
+            mod_matrix: Vec::new(),
+            mod_envelopes: Default::default(),
+            sample: None,
+            arp_enabled: false,
         };
 
         let mut pattern = Pattern::default();
         pattern.tracks.push(track);
         pattern.bpm = tempo;
 
-        Self {
+        let mut kernel = Self {
             pattern,
+            pattern_slot: 0,
+            patterns: (0..PATTERN_SLOTS).map(|_| None).collect(),
+            queued_pattern: None,
+            song: Box::default(),
             is_playing: false,
             playhead_sample: 0,
             sample_rate,
@@ -85,26 +172,105 @@ impl FluxKernel {
             samples_per_step,
             step_phase: samples_per_step, // Start ready to trigger
             current_step: 15, // Start at end so next step is 0
-            current_frequency: 440.0,
-            current_decay: 0.5,
+            step_sequence: 0,
+            frame_clock: 0,
+            events: EventQueue::default(),
+            gate_ends: [[None; MAX_SUBTRACKS]; MAX_TRACKS],
+            control_countdown: 0,
+            trig_sources: [TrigSources::default(); MAX_TRACKS],
+            mod_envelopes: [[EnvelopeState::default(); MOD_ENVELOPES_PER_TRACK]; MAX_TRACKS],
+            arps: [ArpState::default(); MAX_TRACKS],
+            arp_next: [None; MAX_TRACKS],
+            bar_step: 15,
+            arranger: Arranger::default(),
+            song_mutes: [false; MAX_TRACKS],
+            pending_pattern: None,
+            pending_tracks: Default::default(),
+            garbage: GarbageSender::disconnected(),
+            channels: (0..MAX_TRACKS).map(|_| TrackChannel::new(sample_rate)).collect(),
+            track_frames: vec![vec![ChannelFrame::default(); MAX_BLOCK_FRAMES]; MAX_TRACKS],
+            render_pool: None,
+            reverb: ReverbBus::new(sample_rate),
+            delay: DelayBus::new(sample_rate),
+            master_volume: 1.0,
+            block: vec![[0.0; 2]; MAX_BLOCK_FRAMES],
+            taps: FrameTaps::default(),
+            meters: MeterBank::new(sample_rate),
+            analyzer: AnalyzerTap::disconnected(),
+            samples: (0..MAX_SAMPLES).map(|_| None).collect(),
+            input: None,
+            recording: None,
+            recordings: RecordingSender::disconnected(),
+            load_meter: LoadMeter::default(),
+            diagnostics: DiagnosticSender::disconnected(),
+        };
+        kernel.set_tempo(tempo);
+        kernel
+    }
+
+    /// Set the sequencer tempo. Also re-syncs the delay bus (3/16 note).
+    pub fn set_tempo(&mut self, bpm: f32) {
+        self.tempo = bpm;
+        self.samples_per_step = self.sample_rate * 60.0 / (bpm * 4.0);
+        self.delay.set_delay_samples((self.samples_per_step * 3.0) as usize);
+    }
+
+    /// Re-initialize the DSP for a new device sample rate, keeping pattern and transport.
+    /// Allocates, so only call it while no stream is running.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.load_meter.reset_clock(); // Called when a new stream opens
+        if sample_rate == self.sample_rate || sample_rate <= 0.0 {
+            return;
+        }
+        let ratio = sample_rate / self.sample_rate;
+        self.sample_rate = sample_rate;
+        self.channels = (0..MAX_TRACKS).map(|_| TrackChannel::new(sample_rate)).collect();
+        self.reverb = ReverbBus::new(sample_rate);
+        self.delay = DelayBus::new(sample_rate);
+        self.meters = MeterBank::new(sample_rate);
+
+        // Same musical position at the new rate
+        self.playhead_sample = (self.playhead_sample as f64 * ratio as f64) as usize;
+        self.step_phase *= ratio;
+        self.set_tempo(self.tempo);
+    }
+
+    /// Start (or resume) playback. `stop` rewinds to the top of the pattern.
+    pub fn play(&mut self) {
+        self.is_playing = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.is_playing = false;
+        self.playhead_sample = 0;
+        self.current_step = 15;
+        self.bar_step = 15;
+        self.step_phase = self.samples_per_step;
+        self.arranger.reset();
+        self.song_mutes = [false; MAX_TRACKS];
+        // Arps keep running on held input notes only
+        for (arp, next) in self.arps.iter_mut().zip(self.arp_next.iter_mut()) {
+            arp.release_trig();
+            if arp.is_idle() {
+                *next = None;
+            }
         }
     }
 
     pub fn process(&mut self, output_buffer: &mut [f32], channels: usize) {
+        let started = Instant::now();
+        self.load_meter.begin(started);
+        let queue_depth = self.command_consumer.slots();
+
         // 1. Process Commands
         while let Ok(cmd) = self.command_consumer.pop() {
             match cmd {
-                AudioCommand::Play => self.is_playing = true,
-                AudioCommand::Stop => {
-                    self.is_playing = false;
-                    self.playhead_sample = 0;
-                    self.current_step = 15;
-                    self.step_phase = self.samples_per_step;
-                }
-                AudioCommand::SetGlobalVolume(_) => {} // TODO
-                AudioCommand::ToggleStep(track_id, step_idx) => {
+                AudioCommand::Play => self.play(),
+                AudioCommand::Stop => self.stop(),
+                AudioCommand::SetGlobalVolume(volume) => self.master_volume = volume.clamp(0.0, 1.0),
+                AudioCommand::ToggleStep(track_id, subtrack_id, step_idx) => {
                     if let Some(track) = self.pattern.tracks.get_mut(track_id) {
-                        if let Some(subtrack) = track.subtracks.get_mut(0) {
+                        if let Some(subtrack) = track.subtracks.get_mut(subtrack_id) {
                             if let Some(step) = subtrack.steps.get_mut(step_idx) {
                                 step.trig_type = match step.trig_type {
                                     TrigType::None => TrigType::Note,
@@ -114,9 +280,9 @@ impl FluxKernel {
                         }
                     }
                 }
-                AudioCommand::SetParamLock(track_id, step_idx, param_id, val) => {
+                AudioCommand::SetParamLock(track_id, subtrack_id, step_idx, param_id, val) => {
                     if let Some(track) = self.pattern.tracks.get_mut(track_id) {
-                        if let Some(subtrack) = track.subtracks.get_mut(0) {
+                        if let Some(subtrack) = track.subtracks.get_mut(subtrack_id) {
                             if let Some(step) = subtrack.steps.get_mut(step_idx) {
                                 // Safety check for param array bounds if needed, though fixed [128] is safe
                                 if param_id < 128 {
@@ -126,90 +292,714 @@ impl FluxKernel {
                         }
                     }
                 }
-            }
-        }
-
-        // 2. Audio Generation
-        for frame in output_buffer.chunks_mut(channels) {
-            let mut sample = 0.0;
-            
-            if self.is_playing {
-                self.step_phase += 1.0;
-                
-                // Check if we crossed a step boundary
-                if self.step_phase >= self.samples_per_step {
-                    self.step_phase -= self.samples_per_step;
-                    self.current_step = (self.current_step + 1) % 16;
-                    
-                    // CHECK FOR TRIGGER
-                    // Safety check: Ensure track and subtrack exist
-                    if let Some(track) = self.pattern.tracks.get(0) {
-                        if let Some(subtrack) = track.subtracks.get(0) {
-                            if let Some(step) = subtrack.steps.get(self.current_step) {
-                                if step.trig_type != TrigType::None {
-                                    // Trigger the sound!
-                                    
-                                    // 1. Resolve Pitch
-                                    // Check for P-Lock first, then fallback to Step Note
-                                    let note_val = step.p_locks[crate::engine::domain::PARAM_PITCH]
-                                        .unwrap_or(step.note as f32);
-                                        
-                                    self.current_frequency = midi_to_freq(note_val);
-
-                                    // 2. Trigger Envelope (Reset Phase)
-                                    self.playhead_sample = 0;
-                                    println!("Step: {} [TRIG] Freq: {:.2}", self.current_step, self.current_frequency);
-                                } else {
-                                    // println!("Step: {}", self.current_step);
-                                }
+                AudioCommand::SwapPattern(slot, pattern) if slot != self.pattern_slot => self.store_pattern(slot, pattern),
+                AudioCommand::SwapPattern(_, pattern) => {
+                    // A full pattern supersedes anything still queued
+                    if let Some(stale) = self.pending_pattern.replace(pattern) {
+                        self.garbage.retire(Garbage::Pattern(stale));
+                    }
+                    for slot in self.pending_tracks.iter_mut() {
+                        if let Some(stale) = slot.take() {
+                            self.garbage.retire(Garbage::Track(stale));
+                        }
+                    }
+                }
+                AudioCommand::SwapTrack(slot, track_id, track) if slot != self.pattern_slot => {
+                    self.store_track(slot, track_id, track);
+                }
+                AudioCommand::SwapTrack(_, track_id, track) => {
+                    match self.pending_tracks.get_mut(track_id) {
+                        Some(slot) => {
+                            if let Some(stale) = slot.replace(track) {
+                                self.garbage.retire(Garbage::Track(stale));
                             }
                         }
+                        None => self.garbage.retire(Garbage::Track(track)),
                     }
                 }
+                AudioCommand::QueuePattern(slot, switch) => {
+                    self.queued_pattern = (slot < PATTERN_SLOTS).then_some((slot, switch));
+                }
+                AudioCommand::SetSong(song) => {
+                    let old = std::mem::replace(&mut self.song, song);
+                    self.garbage.retire(Garbage::Song(old));
+                }
+                AudioCommand::LoadSample(slot, sample) => {
+                    match self.samples.get_mut(slot as usize) {
+                        Some(current) => {
+                            if let Some(old) = std::mem::replace(current, sample) {
+                                self.garbage.retire(Garbage::Sample(old));
+                            }
+                        }
+                        None => {
+                            if let Some(sample) = sample {
+                                self.garbage.retire(Garbage::Sample(sample));
+                            }
+                        }
+                    }
+                }
+                AudioCommand::StartRecording(mut recording) => {
+                    // Resampling is always bar-quantized
+                    if recording.options.start == RecordStart::Now && !recording.options.source.is_resample() {
+                        recording.start(self.sample_rate, self.samples_per_step * 16.0);
+                    }
+                    if let Some(cancelled) = self.recording.replace(recording) {
+                        self.garbage.retire(Garbage::Recording(cancelled));
+                    }
+                }
+                AudioCommand::SetAnalyzerTrack(track) => self.analyzer.track = track,
+                AudioCommand::NoteInput(track, note, velocity) => {
+                    let enabled = self.pattern.tracks.get(track).is_some_and(|t| t.arp_enabled);
+                    if let Some(arp) = self.arps.get_mut(track).filter(|_| enabled) {
+                        if velocity == 0 {
+                            arp.note_off(note);
+                        } else if arp.note_on(note, velocity) && self.arp_next[track].is_none() {
+                            // Held notes start an idle arp right away, with the last trig's settings
+                            self.arp_next[track] = Some(self.frame_clock as f64);
+                        }
+                    }
+                }
+                AudioCommand::StopRecording => {
+                    // Resampling runs on to the bar line while the transport plays
+                    let finish_now = match &mut self.recording {
+                        Some(recording) if recording.options.source.is_resample() && self.is_playing => {
+                            recording.stop_at_bar_end()
+                        }
+                        _ => true,
+                    };
+                    if finish_now {
+                        if let Some(recording) = self.recording.take() {
+                            self.finish_recording(recording);
+                        }
+                    }
+                }
+            }
+        }
 
-                self.playhead_sample += 1;
-                // Test Tone: Sine Wave with current_frequency
-                let t = self.playhead_sample as f32 / self.sample_rate;
-                sample = (t * self.current_frequency * 2.0 * PI).sin() * 0.1;
+        // Nothing to keep in time with while stopped
+        if !self.is_playing {
+            self.apply_pending_swaps();
+            if let Some((slot, _)) = self.queued_pattern.take() {
+                self.switch_pattern(slot);
             }
+        }
+
+        // 2. Audio Generation, in blocks of at most MAX_BLOCK_FRAMES
+        let mut block = std::mem::take(&mut self.block);
+        for chunk in output_buffer.chunks_mut(MAX_BLOCK_FRAMES * channels) {
+            let frames = &mut block[..chunk.len() / channels];
+            self.render_block(frames);
 
-            // Write to all channels
-            for out in frame.iter_mut() {
-                *out = sample;
+            // Stereo to the first two channels, mono sum for mono devices
+            for (frame, &[left, right]) in chunk.chunks_mut(channels).zip(frames.iter()) {
+                match frame {
+                    [mono] => *mono = (left + right) * 0.5,
+                    [l, r, rest @ ..] => {
+                        *l = left;
+                        *r = right;
+                        rest.fill(0.0);
+                    }
+                    [] => {}
+                }
             }
         }
+        self.block = block;
 
         // 3. Update Snapshot
         // Check which tracks are triggered at the current step
-        let mut triggered_tracks = Vec::with_capacity(self.pattern.tracks.len());
-        for track in &self.pattern.tracks {
-            let is_triggered = if let Some(subtrack) = track.subtracks.get(0) {
-                if let Some(step) = subtrack.steps.get(self.current_step) {
-                    step.trig_type != TrigType::None
-                } else {
-                    false
-                }
-            } else {
-                false
-            };
-            triggered_tracks.push(is_triggered);
+        let mut triggered_tracks = [false; MAX_TRACKS];
+        for ((triggered, track), muted) in triggered_tracks.iter_mut().zip(&self.pattern.tracks).zip(self.song_mutes) {
+            *triggered = !muted && track
+                .subtracks
+                .iter()
+                .filter_map(|subtrack| subtrack.steps.get(self.current_step))
+                .any(|step| step.trig_type != TrigType::None);
         }
 
+        // 4. Measure against the buffer deadline
+        let frames = output_buffer.len() / channels.max(1);
+        let period = Duration::from_secs_f64(frames as f64 / self.sample_rate as f64);
+        self.load_meter.end(started.elapsed(), period);
+        let active_voices = self.channels.iter().map(TrackChannel::active_voices).sum();
+
         self.snapshot_producer.write(AudioSnapshot {
+            sequence: self.step_sequence,
             current_step: self.current_step,
             is_playing: self.is_playing,
             triggered_tracks,
+            pattern_slot: self.pattern_slot,
+            queued_pattern: self.queued_pattern.map(|(slot, _)| slot),
+            song_row: self.arranger.row(),
+            metrics: self.load_meter.metrics(active_voices, queue_depth),
+            levels: self.meters.levels(),
         });
     }
+
+    /// Advance the sequencer by one sample and render one stereo frame.
+    /// Updates `taps` with the per-track and send bus signals of this frame.
+    pub fn render_frame(&mut self) -> [f32; 2] {
+        let mut frame = [[0.0; 2]];
+        self.render_block(&mut frame);
+        frame[0]
+    }
+
+    /// Render `out.len()` frames. Step boundaries, gate-offs and modulation updates are scheduled
+    /// at their exact frame first; the voices then render uninterrupted between them.
+    pub fn render_block(&mut self, out: &mut [[f32; 2]]) {
+        let frames = out.len();
+        self.events.clear();
+        if self.is_playing && self.frames_until_step() < frames {
+            self.events.schedule(self.frames_until_step(), BlockEvent::Step);
+        }
+        if self.control_countdown < frames {
+            self.events.schedule(self.control_countdown, BlockEvent::Control);
+        }
+        for (track, gate_ends) in self.gate_ends.iter().enumerate() {
+            for (layer, gate_end) in gate_ends.iter().enumerate() {
+                if let Some(offset) = gate_end.map(|end| end.saturating_sub(self.frame_clock) as usize) {
+                    if offset < frames {
+                        self.events.schedule(offset, BlockEvent::GateOff(track, layer));
+                    }
+                }
+            }
+        }
+        for (track, next) in self.arp_next.iter().enumerate() {
+            if let Some(offset) = next.map(|next| (next - self.frame_clock as f64).ceil().max(0.0) as usize) {
+                if offset < frames {
+                    self.events.schedule(offset, BlockEvent::Arp(track));
+                }
+            }
+        }
+
+        let mut position = 0;
+        while position < frames {
+            while let Some(event) = self.events.pop_due(position) {
+                self.handle_event(event, position, frames);
+            }
+            let end = self.events.next_offset().map_or(frames, |next| next.min(frames));
+            self.render_frames(&mut out[position..end]);
+
+            let rendered = end - position;
+            if self.is_playing {
+                self.step_phase += rendered as f32;
+                self.playhead_sample += rendered;
+            }
+            self.control_countdown = self.control_countdown.saturating_sub(rendered);
+            position = end;
+        }
+        self.frame_clock += frames as u64;
+    }
+
+    // Frames until the next step boundary.
+    // A step fires on the frame whose phase (counting that frame) reaches `samples_per_step`.
+    fn frames_until_step(&self) -> usize {
+        (self.samples_per_step - self.step_phase - 1.0).ceil().max(0.0) as usize
+    }
+
+    fn handle_event(&mut self, event: BlockEvent, position: usize, frames: usize) {
+        match event {
+            BlockEvent::GateOff(track, layer) => {
+                self.gate_ends[track][layer] = None;
+                self.channels[track].layer_mut(layer).release();
+            }
+            BlockEvent::Step => {
+                // The phase counts this frame once it is rendered
+                self.step_phase -= self.samples_per_step;
+                self.apply_pending_swaps();
+                self.bar_step = (self.bar_step + 1) % 16;
+                if !self.advance_step() {
+                    // The song has played its last row
+                    self.stop();
+                    return;
+                }
+                self.step_sequence += 1;
+                if self.current_step == 0 {
+                    self.start_armed_recording();
+                }
+                self.trigger_step(self.current_step, position, frames);
+
+                // At most one step per frame, as with a per-frame clock
+                let next = position + self.frames_until_step().max(1);
+                if next < frames {
+                    self.events.schedule(next, BlockEvent::Step);
+                }
+            }
+            BlockEvent::Arp(track) => self.play_arp_note(track, position, frames),
+            BlockEvent::Control => {
+                self.update_modulation();
+                self.control_countdown = CONTROL_RATE_SAMPLES;
+                if position + CONTROL_RATE_SAMPLES < frames {
+                    self.events.schedule(position + CONTROL_RATE_SAMPLES, BlockEvent::Control);
+                }
+            }
+        }
+    }
+
+    // Render frames with no events in between: the channels first (on the render pool for
+    // long enough sub-blocks), then the mix in track order, sends, taps, meters and capture
+    fn render_frames(&mut self, out: &mut [[f32; 2]]) {
+        let frames = out.len();
+        match &mut self.render_pool {
+            Some(pool) if frames >= MIN_PARALLEL_FRAMES => {
+                pool.render(&mut self.channels, &mut self.track_frames, &self.samples, frames);
+            }
+            _ => {
+                for (channel, output) in self.channels.iter_mut().zip(self.track_frames.iter_mut()) {
+                    channel.render_block(&self.samples, &mut output[..frames]);
+                }
+            }
+        }
+
+        for (i, frame) in out.iter_mut().enumerate() {
+            let input = self.read_input();
+
+            let mut mix = [0.0; 2];
+            let mut reverb_in = [0.0; 2];
+            let mut delay_in = [0.0; 2];
+            for (track, output) in self.track_frames.iter().enumerate() {
+                let out = output[i];
+                for ch in 0..2 {
+                    mix[ch] += out.post_fx[ch];
+                    reverb_in[ch] += out.reverb_send[ch];
+                    delay_in[ch] += out.delay_send[ch];
+                }
+                self.taps.track_pre_fx[track] = out.pre_fx;
+                self.taps.track_post_fx[track] = out.post_fx;
+            }
+
+            let reverb_out = self.reverb.process(reverb_in);
+            let delay_out = self.delay.process(delay_in);
+            self.taps.sends = [reverb_out, delay_out];
+
+            for ch in 0..2 {
+                mix[ch] = (mix[ch] + reverb_out[ch] + delay_out[ch]) * self.master_volume;
+            }
+            self.taps.master = mix;
+            self.meters.process(&self.taps);
+            self.analyzer.push(&self.taps, self.sample_rate);
+            self.capture(input);
+            *frame = mix;
+        }
+    }
+
+    // Next input frame. Drops the backlog if the input device runs ahead of the output clock.
+    fn read_input(&mut self) -> [f32; 2] {
+        let Some(input) = &mut self.input else {
+            return [0.0; 2];
+        };
+        let backlog = input.slots();
+        if backlog > MAX_INPUT_LATENCY {
+            if let Ok(stale) = input.read_chunk(backlog - MAX_INPUT_LATENCY) {
+                stale.commit_all();
+            }
+        }
+        input.pop().unwrap_or([0.0; 2])
+    }
+
+    // Bar-synced recordings (and all resampling) start on the first step of the pattern
+    fn start_armed_recording(&mut self) {
+        if let Some(recording) = &mut self.recording {
+            let bar_synced = recording.options.start == RecordStart::NextBar || recording.options.source.is_resample();
+            if !recording.is_started() && bar_synced {
+                recording.start(self.sample_rate, self.samples_per_step * 16.0);
+            }
+        }
+    }
+
+    // Feed the recorder with this frame's signal
+    fn capture(&mut self, input: [f32; 2]) {
+        let Some(recording) = &mut self.recording else {
+            return;
+        };
+        let frame = match recording.options.source {
+            RecordSource::Input => input,
+            RecordSource::Master => self.taps.master,
+            RecordSource::Track(track) => self.taps.track_post_fx.get(track).copied().unwrap_or([0.0; 2]),
+            RecordSource::Send(bus) => self.taps.sends.get(bus).copied().unwrap_or([0.0; 2]),
+        };
+        if recording.push(frame) {
+            if let Some(recording) = self.recording.take() {
+                self.finish_recording(recording);
+            }
+        }
+    }
+
+    // Recordings that never started (or captured nothing) are discarded
+    fn finish_recording(&mut self, recording: Box<Recording>) {
+        if recording.is_empty() {
+            self.garbage.retire(Garbage::Recording(recording));
+        } else if let Err(recording) = self.recordings.finish(recording) {
+            self.diagnostics.send(Diagnostic::RecordingDropped { frames: recording.len() });
+            self.garbage.retire(Garbage::Recording(recording));
+        }
+    }
+
+    /// Evaluate every track's LFOs, envelopes and mod matrix and push the offsets to its channel.
+    /// Runs at control rate; LFOs are synced to the pattern playhead.
+    fn update_modulation(&mut self) {
+        let bar_samples = self.samples_per_step as f64 * 16.0;
+        let bar_position = self.playhead_sample as f64 / bar_samples;
+        let step_position = self.current_step as f32 / 15.0;
+
+        for (((track, channel), sources), envelopes) in self
+            .pattern
+            .tracks
+            .iter()
+            .zip(self.channels.iter_mut())
+            .zip(self.trig_sources.iter())
+            .zip(self.mod_envelopes.iter_mut())
+        {
+            let mut levels = [0.0; MOD_ENVELOPES_PER_TRACK];
+            for ((state, env), level) in envelopes.iter_mut().zip(track.mod_envelopes.iter()).zip(levels.iter_mut()) {
+                *level = state.advance(env, CONTROL_RATE_SAMPLES, self.sample_rate);
+            }
+
+            let mut offsets = [0.0; NUM_SYNTH_PARAMS];
+            modulation::evaluate(track, sources, &levels, bar_position, step_position, &mut offsets);
+            channel.set_modulation(offsets);
+        }
+    }
+
+    // Move `current_step` on, following the song's rows when song mode is on.
+    // A queued pattern takes over when its switch is due. Returns false once the song is over.
+    fn advance_step(&mut self) -> bool {
+        if !self.song.enabled || self.song.rows.is_empty() {
+            self.current_step = (self.current_step + 1) % 16;
+            self.arranger.reset();
+            self.song_mutes = [false; MAX_TRACKS];
+            if let Some((slot, switch)) = self.queued_pattern {
+                let due = match switch {
+                    PatternSwitch::EndOfPattern => self.current_step == 0,
+                    PatternSwitch::Immediate => true,
+                    PatternSwitch::NextBar => self.bar_step == 0,
+                };
+                if due {
+                    self.queued_pattern = None;
+                    if self.switch_pattern(slot) {
+                        self.current_step = 0;
+                    }
+                }
+            }
+            return true;
+        }
+        let Some(song_step) = self.arranger.advance(&self.song) else {
+            return false;
+        };
+        // The rows choose the pattern in song mode
+        self.queued_pattern = None;
+        self.switch_pattern(song_step.pattern);
+        self.current_step = song_step.step;
+        self.song_mutes = song_step.mutes;
+        let bpm = song_step.tempo.unwrap_or(self.pattern.bpm);
+        if bpm > 0.0 && bpm != self.tempo {
+            self.set_tempo(bpm);
+        }
+        true
+    }
+
+    // Make a bank slot's pattern the playing one, putting the playing pattern back in its slot.
+    // Returns false for slots never written, which leave the playing pattern in place.
+    fn switch_pattern(&mut self, slot: usize) -> bool {
+        if slot == self.pattern_slot {
+            return true;
+        }
+        let Some(mut next) = self.patterns.get_mut(slot).and_then(Option::take) else {
+            return false;
+        };
+        std::mem::swap(&mut self.pattern, &mut *next);
+        self.patterns[self.pattern_slot] = Some(next);
+        self.pattern_slot = slot;
+        if self.pattern.bpm > 0.0 && self.pattern.bpm != self.tempo {
+            self.set_tempo(self.pattern.bpm);
+        }
+        true
+    }
+
+    // Replace a bank slot other than the playing one
+    fn store_pattern(&mut self, slot: usize, pattern: Box<Pattern>) {
+        match self.patterns.get_mut(slot) {
+            Some(stored) => {
+                if let Some(old) = stored.replace(pattern) {
+                    self.garbage.retire(Garbage::Pattern(old));
+                }
+            }
+            None => self.garbage.retire(Garbage::Pattern(pattern)),
+        }
+    }
+
+    // Replace a track of a bank slot other than the playing one
+    fn store_track(&mut self, slot: usize, track_id: usize, mut track: Box<Track>) {
+        let stored = self.patterns.get_mut(slot).and_then(|p| p.as_mut()).and_then(|p| p.tracks.get_mut(track_id));
+        if let Some(current) = stored {
+            std::mem::swap(current, &mut *track);
+        }
+        self.garbage.retire(Garbage::Track(track));
+    }
+
+    /// Swap in queued patterns/tracks. Runs at step boundaries so playback never glitches.
+    /// The swapped-out data goes to the garbage thread.
+    fn apply_pending_swaps(&mut self) {
+        if let Some(mut pattern) = self.pending_pattern.take() {
+            std::mem::swap(&mut self.pattern, &mut *pattern);
+            self.garbage.retire(Garbage::Pattern(pattern));
+            if self.pattern.bpm > 0.0 && self.pattern.bpm != self.tempo {
+                self.set_tempo(self.pattern.bpm);
+            }
+        }
+
+        for (track_id, slot) in self.pending_tracks.iter_mut().enumerate() {
+            if let Some(mut track) = slot.take() {
+                // Adding tracks needs a full pattern swap, the track list never grows here
+                if let Some(current) = self.pattern.tracks.get_mut(track_id) {
+                    std::mem::swap(current, &mut *track);
+                }
+                self.garbage.retire(Garbage::Track(track));
+            }
+        }
+    }
+
+    // Fire every subtrack's trig at `step_idx`, `position` frames into a block of `frames`.
+    // Subtracks play on their own voice layer and share the track's parameters, arp and modulation.
+    fn trigger_step(&mut self, step_idx: usize, position: usize, frames: usize) {
+        let gate_per_step = self.samples_per_step;
+        let tuning = &self.pattern.tuning;
+        let now = self.frame_clock + position as u64;
+        let mut gates = [[None; MAX_SUBTRACKS]; MAX_TRACKS]; // Gate length of each voice layer that triggered
+        let mut arp_starts = [false; MAX_TRACKS]; // Tracks whose arp restarts on this trig
+        for (track_idx, (track, channel)) in
+            self.pattern.tracks.iter().zip(self.channels.iter_mut()).enumerate()
+        {
+            if self.song_mutes[track_idx] {
+                continue;
+            }
+            let sources = &mut self.trig_sources[track_idx];
+            // Per-trig random value, deterministic so offline renders match playback
+            let seed = self.playhead_sample as u64 * MAX_TRACKS as u64 + track_idx as u64;
+            let random = random_bipolar(seed);
+
+            for subtrack in track.subtracks.iter().take(MAX_SUBTRACKS) {
+                // Safety check: Ensure the step exists
+                let Some(step) = subtrack.steps.get(step_idx) else {
+                    continue;
+                };
+                let layer = subtrack.voice_id % MAX_SUBTRACKS;
+
+                let gate_samples = (step.length.max(0.0) * gate_per_step) as usize;
+                // Slide trigs glide pitch and the smoothed parameters over one step
+                let slide = step.is_slide.then_some(gate_per_step as usize);
+                match step.trig_type {
+                    TrigType::None => {}
+                    TrigType::Lock => {
+                        // Trigless Lock: parameters only, no envelope
+                        channel.apply_params(track, step, slide);
+                        sources.latch_locks(step);
+                        if track.arp_enabled {
+                            self.arps[track_idx].settings = ArpSettings::from_params(|id| resolve_param(track, step, id));
+                        }
+                        let coarse = coarse_tune_semitones(channel.effective_param(PARAM_COARSE_TUNE));
+                        let locked = step.p_locks[PARAM_PITCH].and_then(|note| note_frequency(tuning, note + coarse));
+                        if let Some(frequency) = locked {
+                            let voice = &mut channel.layer_mut(layer).voice;
+                            match slide {
+                                Some(samples) => voice.slide_frequency(frequency, samples),
+                                None => voice.set_frequency(frequency),
+                            }
+                        }
+                    }
+                    TrigType::SynthTrigger => {
+                        // Trigless Trig: envelope only, keep the current pitch
+                        channel.apply_params(track, step, slide);
+                        sources.latch_trig(step, None, random);
+                        for envelope in self.mod_envelopes[track_idx].iter_mut() {
+                            envelope.trigger(gate_samples);
+                        }
+                        channel.layer_mut(layer).voice.retrigger(step.velocity);
+                        gates[track_idx][layer] = Some(gate_samples);
+                    }
+                    TrigType::Note | TrigType::OneShot => {
+                        channel.apply_params(track, step, slide);
+
+                        // Resolve Pitch
+                        // Check for P-Lock first, then fallback to Step Note
+                        let note_val = step.p_locks[PARAM_PITCH].unwrap_or(step.note as f32);
+                        sources.latch_trig(step, Some(note_val), random);
+                        for envelope in self.mod_envelopes[track_idx].iter_mut() {
+                            envelope.trigger(gate_samples);
+                        }
+                        // The step's chord, a single note unless it has one
+                        let mut notes = [0.0; MAX_CHORD_NOTES];
+                        let count = chord_notes(note_val, &step.chord, &mut notes);
+                        if track.arp_enabled {
+                            // The arp plays the trig's notes for the trig length, starting now.
+                            // Trigs of several subtracks on one step all feed it.
+                            let settings = ArpSettings::from_params(|id| resolve_param(track, step, id));
+                            let mut keys = [0; MAX_CHORD_NOTES];
+                            for (key, note) in keys.iter_mut().zip(&notes[..count]) {
+                                *key = note.round() as u8;
+                            }
+                            let arp = &mut self.arps[track_idx];
+                            let end = now + gate_samples as u64;
+                            if arp_starts[track_idx] {
+                                arp.add_trig_notes(&keys[..count], end);
+                            } else {
+                                arp.trigger(settings, &keys[..count], step.velocity, end, seed);
+                            }
+                            arp_starts[track_idx] = true;
+                            continue;
+                        }
+                        let sample = loaded_sample(track, &self.samples);
+                        if !start_notes(channel, layer, sample, tuning, &notes[..count], step.velocity, slide) {
+                            continue;
+                        }
+                        gates[track_idx][layer] = Some(gate_samples);
+                        self.diagnostics.send(Diagnostic::Trigger {
+                            step: step_idx,
+                            track: track.id,
+                            frequency: channel.layer(layer).voice.frequency(),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Gates close after the step length, on the exact frame
+        for (track_idx, layers) in gates.into_iter().enumerate() {
+            for (layer, gate) in layers.into_iter().enumerate() {
+                if let Some(gate_samples) = gate {
+                    self.open_gate(track_idx, layer, gate_samples, position, frames);
+                }
+            }
+        }
+        for (track_idx, started) in arp_starts.into_iter().enumerate() {
+            if started {
+                self.arp_next[track_idx] = Some(now as f64);
+                self.events.schedule(position, BlockEvent::Arp(track_idx));
+            }
+        }
+    }
+
+    // Close a voice layer's gate `gate_samples` after `position`, on the exact frame
+    fn open_gate(&mut self, track_idx: usize, layer: usize, gate_samples: usize, position: usize, frames: usize) {
+        self.gate_ends[track_idx][layer] = Some(self.frame_clock + (position + gate_samples) as u64);
+        if position + gate_samples < frames {
+            self.events.schedule(position + gate_samples, BlockEvent::GateOff(track_idx, layer));
+        } else {
+            self.events.cancel(BlockEvent::GateOff(track_idx, layer));
+        }
+    }
+
+    // Play a track's next arp note on its first voice layer and schedule the one after it.
+    // The arp stops once its input is released or the track turns it off.
+    fn play_arp_note(&mut self, track_idx: usize, position: usize, frames: usize) {
+        let now = self.frame_clock + position as u64;
+        let due = self.arp_next[track_idx].take().unwrap_or(now as f64);
+        let Some(track) = self.pattern.tracks.get(track_idx).filter(|t| t.arp_enabled) else {
+            return;
+        };
+        let arp = &mut self.arps[track_idx];
+        let Some((note, velocity)) = arp.next_note(now) else {
+            return;
+        };
+        let interval = (arp.settings.rate_steps() * self.samples_per_step).max(1.0) as f64;
+        let gate_samples = ((arp.settings.gate as f64 * interval) as usize).max(1);
+
+        let channel = &mut self.channels[track_idx];
+        let sample = loaded_sample(track, &self.samples);
+        if start_notes(channel, 0, sample, &self.pattern.tuning, &[note as f32], velocity, None) {
+            self.open_gate(track_idx, 0, gate_samples, position, frames);
+        }
+        let next = due + interval;
+        self.arp_next[track_idx] = Some(next);
+        let offset = (next - self.frame_clock as f64).ceil() as usize;
+        if offset < frames {
+            self.events.schedule(offset, BlockEvent::Arp(track_idx));
+        }
+    }
+}
+
+// The track's sample slot, if one is loaded there
+fn loaded_sample(track: &Track, samples: &[Option<Arc<SampleBuffer>>]) -> Option<u16> {
+    track.sample.filter(|&slot| samples.get(slot as usize).is_some_and(Option::is_some))
+}
+
+// Start notes (a chord, lowest first) on a channel's voice layer in the project tuning, with the
+// channel's coarse tune, one voice each. Chord voices left over from a bigger chord are released.
+// A loaded `sample` plays instead of the oscillator, pitched relative to C4. False if every key is unmapped.
+fn start_notes(
+    channel: &mut TrackChannel,
+    layer: usize,
+    sample: Option<u16>,
+    tuning: &Tuning,
+    notes: &[f32],
+    velocity: u8,
+    slide: Option<usize>,
+) -> bool {
+    let coarse = coarse_tune_semitones(channel.effective_param(PARAM_COARSE_TUNE));
+    let voices = channel.layer_mut(layer);
+    // Samples play at their original pitch on C4
+    let sample_root = note_frequency(tuning, 60.0).unwrap_or(midi_to_freq(60.0));
+    let mut started = 0;
+    for &note in notes {
+        // Keys the tuning leaves unmapped are silent
+        let Some(frequency) = note_frequency(tuning, note + coarse) else {
+            continue;
+        };
+        let Some(voice) = voices.voice_mut(started) else {
+            break;
+        };
+        match sample {
+            Some(slot) => voice.play_sample(slot, frequency / sample_root, velocity),
+            None => match slide {
+                Some(samples) => voice.slide_on(frequency, velocity, samples),
+                None => voice.note_on(frequency, velocity),
+            },
+        }
+        started += 1;
+    }
+    voices.release_from(started.max(1));
+    started > 0
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use rtrb::RingBuffer;
-    use crate::shared::models::{AtomicStep, TrigType};
+    use crate::shared::models::{AtomicStep, Chord, ChordKind, SongRow, TrigType};
     use crate::engine::domain::{PARAM_PITCH, AudioSnapshot};
 
+    // Counts heap allocations per thread, to check the callback never allocates
+    mod alloc_counter {
+        use std::alloc::{GlobalAlloc, Layout, System};
+        use std::cell::Cell;
+
+        thread_local! {
+            static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        struct CountingAllocator;
+
+        unsafe impl GlobalAlloc for CountingAllocator {
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+                System.alloc(layout)
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                System.dealloc(ptr, layout)
+            }
+        }
+
+        #[global_allocator]
+        static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+        /// Allocations made by this thread while running `f`.
+        pub fn allocations_during(f: impl FnOnce()) -> usize {
+            let before = ALLOCATIONS.with(Cell::get);
+            f();
+            ALLOCATIONS.with(Cell::get) - before
+        }
+    }
+
     // Helper to setup a kernel for testing
     fn setup_kernel() -> (FluxKernel, rtrb::Producer<AudioCommand>) {
         let (producer, consumer) = RingBuffer::new(1024);
@@ -309,7 +1099,726 @@ mod tests {
         let expected_freq = 440.0 * 2.0_f32.powf((72.0 - 69.0) / 12.0);
         
         // Use epsilon for float comparison
-        assert!((kernel.current_frequency - expected_freq).abs() < 0.1, 
-            "Expected freq {}, got {}", expected_freq, kernel.current_frequency);
+        let freq = kernel.channels[0].layer(0).voice.frequency();
+        assert!((freq - expected_freq).abs() < 0.1, 
+            "Expected freq {}, got {}", expected_freq, freq);
+    }
+
+    #[test]
+    fn test_trig_pitch_uses_project_tuning_and_coarse_tune() {
+        let (mut kernel, mut producer) = setup_kernel();
+        let mut pattern = pattern_with_bpm(120.0);
+        // 24-EDO: each key is a quarter tone, A4 stays at 440 Hz
+        pattern.tuning.cents = (1..=24).map(|degree| degree as f64 * 50.0).collect();
+        pattern.tuning.keyboard.octave_degree = 24;
+        let steps = &mut pattern.tracks[0].subtracks[0].steps;
+        steps[0].trig_type = TrigType::Note;
+        steps[0].note = 71;
+        steps[1].trig_type = TrigType::Note;
+        steps[1].note = 69;
+        steps[1].p_locks[PARAM_COARSE_TUNE] = Some(0.75); // +12 keys
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        let mut buffer = vec![0.0; 100 * 2];
+        kernel.process(&mut buffer, 2);
+        let expected = 440.0 * 2.0_f32.powf(1.0 / 12.0); // Two quarter tones up
+        assert!((kernel.channels[0].layer(0).voice.frequency() - expected).abs() < 0.01);
+
+        let mut buffer = vec![0.0; kernel.samples_per_step as usize * 2];
+        kernel.process(&mut buffer, 2);
+        let expected = 440.0 * 2.0_f32.sqrt(); // Twelve quarter tones up
+        assert!((kernel.channels[0].layer(0).voice.frequency() - expected).abs() < 0.01);
+    }
+
+    fn pattern_with_bpm(bpm: f32) -> Pattern {
+        let mut pattern = Pattern { bpm, ..Pattern::default() };
+        pattern.tracks.push(Track {
+            id: 0,
+            machine: MachineType::FmTone,
+            subtracks: vec![Subtrack { voice_id: 0, steps: vec![AtomicStep::default(); 16] }],
+            length: 16,
+            scale: 1.0,
+            default_params: crate::shared::models::default_track_params(),
+            lfos: Vec::new(),
+            mod_matrix: Vec::new(),
+            mod_envelopes: Default::default(),
+            sample: None,
+            arp_enabled: false,
+        });
+        pattern
+    }
+
+    #[test]
+    fn test_step_fires_on_exact_frame_in_any_buffer() {
+        let (mut kernel, mut producer) = setup_kernel();
+        producer.push(AudioCommand::Play).unwrap();
+
+        // 5512.5 samples per step: step 0 on frame 0, step 1 on frame 5512
+        let mut buffer = vec![0.0; 5512 * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.current_step, 0);
+        let mut buffer = [0.0; 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.current_step, 1);
+    }
+
+    #[test]
+    fn test_output_is_independent_of_buffer_size() {
+        let render = |buffer_frames: usize| {
+            let (mut kernel, mut producer) = setup_kernel();
+            let mut pattern = pattern_with_bpm(120.0);
+            for (i, step) in pattern.tracks[0].subtracks[0].steps.iter_mut().enumerate().step_by(3) {
+                step.trig_type = TrigType::Note;
+                step.length = 0.1 + i as f32 * 0.05;
+            }
+            pattern.tracks[0].default_params[crate::engine::domain::PARAM_SUSTAIN] = 0.5;
+            producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+            producer.push(AudioCommand::Play).unwrap();
+
+            let total = 4 * 5513;
+            let mut output = Vec::with_capacity(total * 2);
+            let mut buffer = vec![0.0; buffer_frames * 2];
+            while output.len() < total * 2 {
+                kernel.process(&mut buffer, 2);
+                output.extend_from_slice(&buffer);
+            }
+            output.truncate(total * 2);
+            output
+        };
+
+        let reference = render(1);
+        assert!(reference.iter().any(|&s| s != 0.0));
+        for buffer_frames in [64, 333, 2048] {
+            assert!(render(buffer_frames) == reference, "Output differs at {} frames", buffer_frames);
+        }
+    }
+
+    #[test]
+    fn test_render_pool_output_matches_inline() {
+        let render = |workers: Option<usize>| {
+            let (mut kernel, mut producer) = setup_kernel();
+            kernel.render_pool = workers.map(RenderPool::new);
+            let mut pattern = pattern_with_bpm(120.0);
+            for i in 1..4 {
+                let mut track = pattern.tracks[0].clone();
+                track.id = i;
+                track.subtracks[0].steps[i * 2].trig_type = TrigType::Note;
+                track.subtracks[0].steps[i * 2].note = 48 + i as u8 * 7;
+                pattern.tracks.push(track);
+            }
+            pattern.tracks[0].subtracks[0].steps[0].trig_type = TrigType::Note;
+            producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+            producer.push(AudioCommand::Play).unwrap();
+
+            let mut output = vec![0.0; 8 * 5513 * 2];
+            for buffer in output.chunks_mut(256 * 2) {
+                kernel.process(buffer, 2);
+            }
+            output
+        };
+
+        let inline = render(None);
+        assert!(render(Some(3)) == inline);
+        assert!(render(Some(0)) == inline);
+    }
+
+    #[test]
+    fn test_gate_closes_after_step_length() {
+        let (mut kernel, mut producer) = setup_kernel();
+        let mut pattern = pattern_with_bpm(120.0);
+        pattern.tracks[0].subtracks[0].steps[0].trig_type = TrigType::Note;
+        pattern.tracks[0].subtracks[0].steps[0].length = 0.5; // 2756.25 samples
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        let mut buffer = vec![0.0; 2756 * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.gate_ends[0][0], Some(2756));
+        let mut buffer = vec![0.0; 100 * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.gate_ends[0][0], None);
+    }
+
+    #[test]
+    fn test_p_locked_volume_ramps_in() {
+        use crate::engine::domain::PARAM_VOLUME;
+
+        let (mut kernel, mut producer) = setup_kernel();
+        let mut pattern = pattern_with_bpm(120.0);
+        let steps = &mut pattern.tracks[0].subtracks[0].steps;
+        steps[0].trig_type = TrigType::Note;
+        steps[1].trig_type = TrigType::Note;
+        steps[1].p_locks[PARAM_VOLUME] = Some(0.2);
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        // A few samples into step 1, the volume is on its way down from the 0.8 default
+        let mut buffer = vec![0.0; (kernel.samples_per_step as usize + 100) * 2];
+        kernel.process(&mut buffer, 2);
+        let volume = kernel.channels[0].effective_param(PARAM_VOLUME);
+        assert!(volume > 0.2 && volume < 0.8, "volume jumped to {}", volume);
+
+        // 20 ms later it has arrived
+        let mut buffer = vec![0.0; 1000 * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.channels[0].effective_param(PARAM_VOLUME), 0.2);
+    }
+
+    #[test]
+    fn test_slide_trig_glides_over_the_step() {
+        use crate::engine::domain::PARAM_FILTER_CUTOFF;
+
+        let (mut kernel, mut producer) = setup_kernel();
+        let mut pattern = pattern_with_bpm(120.0);
+        let steps = &mut pattern.tracks[0].subtracks[0].steps;
+        steps[0].trig_type = TrigType::Note;
+        steps[1].trig_type = TrigType::Note;
+        steps[1].is_slide = true;
+        steps[1].p_locks[PARAM_FILTER_CUTOFF] = Some(0.0);
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        // Halfway through step 1, halfway from fully open to closed
+        let mut buffer = vec![0.0; (kernel.samples_per_step * 1.5) as usize * 2];
+        kernel.process(&mut buffer, 2);
+        let cutoff = kernel.channels[0].effective_param(PARAM_FILTER_CUTOFF);
+        assert!((cutoff - 0.5).abs() < 0.01, "cutoff {}", cutoff);
+    }
+
+    #[test]
+    fn test_slide_trig_jumps_stepped_parameters() {
+        let (mut kernel, mut producer) = setup_kernel();
+        let mut pattern = pattern_with_bpm(120.0);
+        let steps = &mut pattern.tracks[0].subtracks[0].steps;
+        steps[0].trig_type = TrigType::Note;
+        steps[1].trig_type = TrigType::Note;
+        steps[1].is_slide = true;
+        steps[1].p_locks[PARAM_COARSE_TUNE] = Some(1.0);
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        // Right after the slide trig, coarse tune is already at +24 semitones
+        let mut buffer = vec![0.0; (kernel.samples_per_step + 10.0) as usize * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.channels[0].effective_param(PARAM_COARSE_TUNE), 1.0);
+    }
+
+    #[test]
+    fn test_arp_plays_trig_note_across_octaves() {
+        let (mut kernel, mut producer) = setup_kernel();
+        let mut pattern = pattern_with_bpm(120.0);
+        pattern.tracks[0].arp_enabled = true;
+        let step = &mut pattern.tracks[0].subtracks[0].steps[0];
+        step.trig_type = TrigType::Note;
+        step.note = 57; // A3
+        step.length = 2.0;
+        step.p_locks[crate::engine::domain::PARAM_ARP_RATE] = Some(0.0); // 1/32
+        step.p_locks[crate::engine::domain::PARAM_ARP_OCTAVES] = Some(1.0 / 3.0); // 2 octaves
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        // 2756.25 samples per note: A3, A4, A3, A4 over the two-step trig, then nothing
+        let mut frequencies = Vec::new();
+        for frames in [2000, 2000, 2000, 3000] {
+            let mut buffer = vec![0.0; frames * 2];
+            kernel.process(&mut buffer, 2);
+            frequencies.push(kernel.channels[0].layer(0).voice.frequency().round());
+        }
+        assert_eq!(frequencies, [220.0, 440.0, 220.0, 440.0]);
+        let mut buffer = vec![0.0; 3000 * 2];
+        kernel.process(&mut buffer, 2);
+        assert!(kernel.arp_next[0].is_none());
+
+        // Held input notes start it again
+        producer.push(AudioCommand::NoteInput(0, 69, 100)).unwrap();
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.channels[0].layer(0).voice.frequency().round(), 880.0); // A4, then A5 an octave up
+        producer.push(AudioCommand::NoteInput(0, 69, 0)).unwrap();
+        kernel.process(&mut buffer, 2);
+        kernel.process(&mut buffer, 2);
+        assert!(kernel.arp_next[0].is_none());
+    }
+
+    #[test]
+    fn test_chord_trig_plays_a_voice_per_note() {
+        let (mut kernel, mut producer) = setup_kernel();
+        let mut pattern = pattern_with_bpm(120.0);
+        let step = &mut pattern.tracks[0].subtracks[0].steps[0];
+        step.trig_type = TrigType::Note;
+        step.note = 57; // A3
+        step.chord = Chord { kind: ChordKind::Minor, inversion: 1, ..Chord::default() };
+        pattern.tracks[0].subtracks[0].steps[1].trig_type = TrigType::Note;
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        let mut buffer = vec![0.0; 1000 * 2];
+        kernel.process(&mut buffer, 2);
+        let channel = &kernel.channels[0];
+        assert_eq!(channel.active_voices(), 3);
+        // First inversion of A minor: C4, E4, A4
+        let voices = channel.layer(0);
+        assert_eq!(voices.voice.frequency().round(), 262.0);
+        assert_eq!(voices.chord_voices[0].frequency().round(), 330.0);
+        assert_eq!(voices.chord_voices[1].frequency().round(), 440.0);
+
+        // A single note on the next step releases the chord voices
+        let mut buffer = vec![0.0; 6000 * 2];
+        kernel.process(&mut buffer, 2);
+        assert!(kernel.channels[0].layer(0).chord_voices.iter().all(|voice| !voice.is_gate_open()));
+    }
+
+    #[test]
+    fn test_subtracks_play_in_parallel_on_their_own_voices() {
+        let (mut kernel, mut producer) = setup_kernel();
+        let mut pattern = pattern_with_bpm(120.0);
+        let mut layer = Subtrack { voice_id: 1, steps: vec![AtomicStep::default(); 16] };
+        layer.steps[0].trig_type = TrigType::Note;
+        layer.steps[0].note = 64; // E4
+        layer.steps[0].length = 2.0;
+        pattern.tracks[0].subtracks.push(layer);
+        let step = &mut pattern.tracks[0].subtracks[0].steps[0];
+        step.trig_type = TrigType::Note;
+        step.note = 57; // A3
+        step.length = 0.5; // 2756.25 samples
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        let mut buffer = vec![0.0; 1000 * 2];
+        kernel.process(&mut buffer, 2);
+        let channel = &kernel.channels[0];
+        assert_eq!(channel.active_voices(), 2);
+        assert_eq!(channel.layer(0).voice.frequency().round(), 220.0);
+        assert_eq!(channel.layer(1).voice.frequency().round(), 330.0);
+
+        // Each layer's gate follows its own trig length
+        let mut buffer = vec![0.0; 2000 * 2];
+        kernel.process(&mut buffer, 2);
+        assert!(!kernel.channels[0].layer(0).voice.is_gate_open());
+        assert!(kernel.channels[0].layer(1).voice.is_gate_open());
+    }
+
+    #[test]
+    fn test_step_edits_reach_the_selected_subtrack() {
+        let (mut kernel, mut producer) = setup_kernel();
+        let mut pattern = pattern_with_bpm(120.0);
+        let second = Subtrack { voice_id: 1, steps: vec![AtomicStep::default(); 16] };
+        pattern.tracks[0].subtracks.push(second);
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        let mut buffer = [0.0; 2];
+        kernel.process(&mut buffer, 2);
+        producer.push(AudioCommand::ToggleStep(0, 1, 3)).unwrap();
+        producer.push(AudioCommand::SetParamLock(0, 1, 3, 0, Some(72.0))).unwrap();
+        kernel.process(&mut buffer, 2);
+
+        let [first, second] = &kernel.pattern.tracks[0].subtracks[..] else { panic!("two subtracks") };
+        assert_eq!(second.steps[3].trig_type, TrigType::Note);
+        assert_eq!(second.steps[3].p_locks[0], Some(72.0));
+        assert_eq!(first.steps[3].trig_type, TrigType::None);
+        assert_eq!(first.steps[3].p_locks[0], None);
+    }
+
+    #[test]
+    fn test_pattern_swap_while_stopped_is_immediate() {
+        let (mut kernel, mut producer) = setup_kernel();
+        let (garbage_sender, mut garbage) = crate::engine::sync::garbage_queue();
+        kernel.garbage = garbage_sender;
+
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern_with_bpm(90.0)))).unwrap();
+        let mut buffer = [0.0; 2];
+        kernel.process(&mut buffer, 2);
+
+        assert_eq!(kernel.pattern.bpm, 90.0);
+        assert_eq!(kernel.pattern.tracks[0].machine, MachineType::FmTone);
+        assert_eq!(kernel.tempo, 90.0);
+        // The old pattern is handed to the garbage thread, not dropped in the callback
+        assert!(matches!(garbage.pop(), Ok(crate::engine::sync::Garbage::Pattern(_))));
+    }
+
+    #[test]
+    fn test_pattern_swap_while_playing_waits_for_step_boundary() {
+        let (mut kernel, mut producer) = setup_kernel();
+        producer.push(AudioCommand::Play).unwrap();
+
+        // Step 0 fires on the first sample
+        let mut buffer = vec![0.0; 100 * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.current_step, 0);
+
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern_with_bpm(120.0)))).unwrap();
+        kernel.process(&mut buffer, 2);
+        assert!(kernel.pending_pattern.is_some(), "Swap must wait for the step boundary");
+        assert_eq!(kernel.pattern.tracks[0].machine, MachineType::OneShot);
+
+        // Cross into step 1 (~5512 samples per step at 44.1 kHz / 120 BPM)
+        let mut buffer = vec![0.0; 6000 * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.current_step, 1);
+        assert!(kernel.pending_pattern.is_none());
+        assert_eq!(kernel.pattern.tracks[0].machine, MachineType::FmTone);
+    }
+
+    #[test]
+    fn test_queued_pattern_switches_at_the_end_of_the_pattern() {
+        let (mut kernel, mut producer) = setup_kernel();
+        producer.push(AudioCommand::SwapPattern(1, Box::new(pattern_with_bpm(90.0)))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+        let mut buffer = vec![0.0; 100 * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!((kernel.pattern_slot, kernel.current_step), (0, 0));
+        assert_eq!(kernel.patterns[1].as_ref().map(|p| p.bpm), Some(90.0));
+
+        // Queued on step 0, the switch waits for the pattern's last step
+        producer.push(AudioCommand::QueuePattern(1, PatternSwitch::EndOfPattern)).unwrap();
+        let step = |kernel: &mut FluxKernel| {
+            let mut buffer = vec![0.0; kernel.samples_per_step.ceil() as usize * 2];
+            kernel.process(&mut buffer, 2);
+        };
+        for _ in 0..15 {
+            step(&mut kernel);
+        }
+        assert_eq!((kernel.pattern_slot, kernel.current_step), (0, 15));
+        assert!(kernel.queued_pattern.is_some());
+        step(&mut kernel);
+        assert_eq!((kernel.pattern_slot, kernel.current_step), (1, 0));
+        assert_eq!(kernel.pattern.tracks[0].machine, MachineType::FmTone);
+        assert_eq!(kernel.tempo, 90.0);
+        assert!(kernel.queued_pattern.is_none());
+        assert!(kernel.patterns[1].is_none());
+
+        // Edits to a slot that isn't playing go straight to the bank
+        let mut track = kernel.pattern.tracks[0].clone();
+        track.machine = MachineType::Slice;
+        producer.push(AudioCommand::SwapTrack(0, 0, Box::new(track))).unwrap();
+
+        // Immediate switches restart the new pattern on the next step
+        step(&mut kernel);
+        producer.push(AudioCommand::QueuePattern(0, PatternSwitch::Immediate)).unwrap();
+        step(&mut kernel);
+        assert_eq!((kernel.pattern_slot, kernel.current_step), (0, 0));
+        assert_eq!(kernel.pattern.tracks[0].machine, MachineType::Slice);
+
+        // While stopped the switch is instant
+        producer.push(AudioCommand::Stop).unwrap();
+        producer.push(AudioCommand::QueuePattern(1, PatternSwitch::NextBar)).unwrap();
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.pattern_slot, 1);
+    }
+
+    #[test]
+    fn test_loading_a_project_switches_to_its_current_slot() {
+        let (mut kernel, mut producer) = setup_kernel();
+        producer.push(AudioCommand::Play).unwrap();
+        let mut buffer = vec![0.0; 100 * 2];
+        kernel.process(&mut buffer, 2);
+        let step = |kernel: &mut FluxKernel| {
+            let mut buffer = vec![0.0; kernel.samples_per_step.ceil() as usize * 2];
+            kernel.process(&mut buffer, 2);
+        };
+
+        // A switch to a slot never written leaves the playing pattern in place
+        producer.push(AudioCommand::QueuePattern(3, PatternSwitch::Immediate)).unwrap();
+        step(&mut kernel);
+        assert_eq!(kernel.pattern_slot, 0);
+
+        // Loading sends the project's current pattern to its slot before switching to it
+        producer.push(AudioCommand::SwapPattern(1, Box::new(pattern_with_bpm(120.0)))).unwrap();
+        producer.push(AudioCommand::SwapPattern(3, Box::new(pattern_with_bpm(90.0)))).unwrap();
+        producer.push(AudioCommand::QueuePattern(3, PatternSwitch::Immediate)).unwrap();
+        step(&mut kernel);
+        assert_eq!((kernel.pattern_slot, kernel.current_step), (3, 0));
+        assert_eq!(kernel.pattern.tracks[0].machine, MachineType::FmTone);
+        assert_eq!(kernel.tempo, 90.0);
+        assert!(kernel.patterns[1].is_some());
+    }
+
+    #[test]
+    fn test_song_mode_steps_through_rows_and_stops_at_the_end() {
+        let (mut kernel, mut producer) = setup_kernel();
+        producer.push(AudioCommand::SwapPattern(1, Box::new(pattern_with_bpm(90.0)))).unwrap();
+        let song = Song {
+            enabled: true,
+            rows: vec![
+                SongRow { length: Some(2), ..SongRow::default() },
+                SongRow { pattern: 1, length: Some(1), tempo: Some(240.0), mutes: [true; MAX_TRACKS], ..SongRow::default() },
+            ],
+            ..Song::default()
+        };
+        producer.push(AudioCommand::SetSong(Box::new(song))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        let mut buffer = vec![0.0; 100 * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!((kernel.arranger.row(), kernel.current_step), (Some(0), 0));
+
+        let mut buffer = vec![0.0; 5512 * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!((kernel.arranger.row(), kernel.current_step), (Some(0), 1));
+        kernel.process(&mut buffer, 2);
+        assert_eq!((kernel.arranger.row(), kernel.current_step), (Some(1), 0));
+        assert_eq!(kernel.pattern_slot, 1);
+        assert_eq!(kernel.tempo, 240.0);
+        assert!(kernel.song_mutes[0]);
+
+        // No loop: playback stops after the last row
+        kernel.process(&mut buffer, 2);
+        assert!(!kernel.is_playing);
+        assert_eq!(kernel.arranger.row(), None);
+        assert_eq!(kernel.current_step, 15);
+    }
+
+    #[test]
+    fn test_track_swap_replaces_only_that_track() {
+        let (mut kernel, mut producer) = setup_kernel();
+        let mut pattern = pattern_with_bpm(120.0);
+        pattern.tracks.push(pattern.tracks[0].clone());
+        pattern.tracks[1].id = 1;
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+
+        let mut track = kernel.pattern.tracks[0].clone();
+        track.id = 1;
+        track.machine = MachineType::Slice;
+        track.subtracks[0].steps[3].trig_type = TrigType::Note;
+        producer.push(AudioCommand::SwapTrack(0, 1, Box::new(track.clone()))).unwrap();
+        // Out-of-range tracks are discarded, the track list does not grow
+        producer.push(AudioCommand::SwapTrack(0, 5, Box::new(track))).unwrap();
+
+        let mut buffer = [0.0; 2];
+        kernel.process(&mut buffer, 2);
+
+        assert_eq!(kernel.pattern.tracks.len(), 2);
+        assert_eq!(kernel.pattern.tracks[0].machine, MachineType::FmTone);
+        assert_eq!(kernel.pattern.tracks[1].machine, MachineType::Slice);
+        assert_eq!(kernel.pattern.tracks[1].subtracks[0].steps[3].trig_type, TrigType::Note);
+    }
+
+    #[test]
+    fn test_lfo_modulates_channel_params() {
+        use crate::engine::domain::PARAM_FILTER_CUTOFF;
+        use crate::shared::models::{LFOShape, LFO};
+
+        let (mut kernel, mut producer) = setup_kernel();
+        let mut pattern = pattern_with_bpm(120.0);
+        pattern.tracks[0].default_params[PARAM_FILTER_CUTOFF] = 0.5;
+        pattern.tracks[0].subtracks[0].steps[0].trig_type = TrigType::Note;
+        pattern.tracks[0].lfos.push(LFO {
+            shape: LFOShape::Square,
+            destination: 74, // Filter Cutoff
+            amount: 0.25,
+            speed: 1.0,
+            phase: 0.0,
+        });
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        // First half of the bar: square is high
+        let mut buffer = vec![0.0; 1000 * 2];
+        kernel.process(&mut buffer, 2);
+        assert!((kernel.channels[0].effective_param(PARAM_FILTER_CUTOFF) - 0.75).abs() < 1e-6);
+
+        // Second half of the bar (8.5 steps in): square is low
+        let samples = (kernel.samples_per_step * 8.5) as usize;
+        let mut buffer = vec![0.0; samples * 2];
+        kernel.process(&mut buffer, 2);
+        assert!((kernel.channels[0].effective_param(PARAM_FILTER_CUTOFF) - 0.25).abs() < 1e-6);
+
+        // Unmodulated tracks keep their base values
+        assert_eq!(kernel.channels[1].effective_param(PARAM_FILTER_CUTOFF), 1.0);
+    }
+
+    #[test]
+    fn test_mod_envelope_follows_trigs() {
+        use crate::engine::domain::PARAM_FILTER_CUTOFF;
+
+        let (mut kernel, mut producer) = setup_kernel();
+        let mut pattern = pattern_with_bpm(120.0);
+        pattern.tracks[0].subtracks[0].steps[0].trig_type = TrigType::SynthTrigger;
+        pattern.tracks[0].mod_envelopes[0].destination = PARAM_FILTER_CUTOFF as u8;
+        pattern.tracks[0].mod_envelopes[0].depth = -0.5;
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+
+        // No trig yet: the envelope is idle
+        let mut buffer = vec![0.0; 256 * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.channels[0].effective_param(PARAM_FILTER_CUTOFF), 1.0);
+
+        // The trigless trig on step 0 fires the envelope (1 ms attack)
+        producer.push(AudioCommand::Play).unwrap();
+        kernel.process(&mut buffer, 2);
+        assert!(kernel.channels[0].effective_param(PARAM_FILTER_CUTOFF) < 0.6);
+    }
+
+    #[test]
+    fn test_sample_rate_change_keeps_transport() {
+        let (mut kernel, mut producer) = setup_kernel();
+        producer.push(AudioCommand::Play).unwrap();
+        let mut buffer = vec![0.0; 6000 * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.current_step, 1);
+
+        kernel.set_sample_rate(96000.0);
+        assert_eq!(kernel.sample_rate, 96000.0);
+        assert!(kernel.is_playing);
+        assert_eq!(kernel.current_step, 1);
+        assert!((kernel.samples_per_step - 12000.0).abs() < 1e-3);
+
+        // Step 2 arrives after the rest of step 1 at the new rate
+        let mut buffer = vec![0.0; 12000 * 2];
+        kernel.process(&mut buffer, 2);
+        assert_eq!(kernel.current_step, 2);
+    }
+
+    #[test]
+    fn test_recording_starts_on_next_bar() {
+        use crate::engine::recorder::{input_queue, recording_queue, RecordOptions};
+
+        let (mut kernel, mut producer) = setup_kernel();
+        let (input, input_consumer) = input_queue();
+        kernel.input = Some(input_consumer);
+        let (recordings, mut finished) = recording_queue();
+        kernel.recordings = recordings;
+
+        let options = RecordOptions { source: RecordSource::Input, start: RecordStart::NextBar, bars: Some(1) };
+        producer.push(AudioCommand::StartRecording(Box::new(Recording::new(options, 44100.0)))).unwrap();
+
+        let process_block = |kernel: &mut FluxKernel| {
+            let mut input = input.lock().unwrap();
+            for _ in 0..512 {
+                input.push([0.25, -0.25]).unwrap();
+            }
+            drop(input);
+            let mut buffer = vec![0.0; 512 * 2];
+            kernel.process(&mut buffer, 2);
+        };
+
+        // Armed, but the transport is stopped
+        process_block(&mut kernel);
+        assert!(!kernel.recording.as_ref().unwrap().is_started());
+
+        producer.push(AudioCommand::Play).unwrap();
+        let bar_samples = (kernel.samples_per_step * 16.0).round() as usize;
+        for _ in 0..bar_samples / 512 + 2 {
+            process_block(&mut kernel);
+        }
+
+        let sample = finished.pop().expect("recording finished").into_sample();
+        assert!(kernel.recording.is_none());
+        assert_eq!(sample.frames.len(), bar_samples);
+        assert!(sample.frames.iter().all(|&frame| frame == [0.25, -0.25]));
+    }
+
+    #[test]
+    fn test_track_plays_loaded_sample() {
+        use crate::engine::sample_pool::SampleBuffer;
+
+        let (mut kernel, mut producer) = setup_kernel();
+        let sample = SampleBuffer { sample_rate: 44100.0, frames: vec![[0.5, 0.5]; 1000] };
+        producer.push(AudioCommand::LoadSample(3, Some(Arc::new(sample)))).unwrap();
+        let mut pattern = pattern_with_bpm(120.0);
+        pattern.tracks[0].subtracks[0].steps[0].trig_type = TrigType::Note;
+        pattern.tracks[0].sample = Some(3);
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        let mut buffer = vec![0.0; 10 * 2];
+        kernel.process(&mut buffer, 2);
+        // Constant sample level instead of a rising sine
+        assert!(kernel.taps.track_pre_fx[0][0] > 0.3);
+        assert!(kernel.samples[3].is_some());
+    }
+
+    #[test]
+    fn test_resampling_stops_on_bar_line() {
+        use crate::engine::recorder::{recording_queue, RecordOptions};
+
+        let (mut kernel, mut producer) = setup_kernel();
+        let (recordings, mut finished) = recording_queue();
+        kernel.recordings = recordings;
+
+        // "Now" is still quantized to the bar for resampling
+        let options = RecordOptions { source: RecordSource::Master, start: RecordStart::Now, bars: None };
+        producer.push(AudioCommand::StartRecording(Box::new(Recording::new(options, 44100.0)))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        let bar_samples = (kernel.samples_per_step * 16.0).round() as usize;
+        let mut buffer = vec![0.0; bar_samples * 3 / 2 * 2];
+        kernel.process(&mut buffer, 2);
+        assert!(kernel.recording.as_ref().unwrap().is_started());
+
+        producer.push(AudioCommand::StopRecording).unwrap();
+        let mut buffer = vec![0.0; bar_samples * 2];
+        kernel.process(&mut buffer, 2);
+
+        let sample = finished.pop().expect("recording finished").into_sample();
+        assert_eq!(sample.frames.len(), bar_samples * 2);
+    }
+
+    #[test]
+    fn test_process_does_not_allocate() {
+        let (mut producer, consumer) = RingBuffer::new(16);
+        let (snapshot_prod, mut snapshot_cons) = triple_buffer::TripleBuffer::new(&AudioSnapshot::default()).split();
+        let mut kernel = FluxKernel::new(44100.0, consumer, snapshot_prod);
+        let (diagnostics, _log) = crate::engine::diagnostics::diagnostics_queue(crate::engine::diagnostics::LogSource::Audio);
+        kernel.diagnostics = diagnostics;
+        let mut pattern = pattern_with_bpm(120.0);
+        for step in pattern.tracks[0].subtracks[0].steps.iter_mut() {
+            step.trig_type = TrigType::Note;
+        }
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        let mut buffer = vec![0.0; 512 * 2];
+        kernel.process(&mut buffer, 2);
+        let allocations = alloc_counter::allocations_during(|| {
+            // Several steps, trigs, gate-offs and snapshots
+            for _ in 0..64 {
+                kernel.process(&mut buffer, 2);
+            }
+        });
+        assert_eq!(allocations, 0);
+        assert!(snapshot_cons.read().sequence > 4);
+    }
+
+    #[test]
+    fn test_snapshot_sequence_counts_steps() {
+        let (mut producer, consumer) = RingBuffer::new(16);
+        let (snapshot_prod, mut snapshot_cons) = triple_buffer::TripleBuffer::new(&AudioSnapshot::default()).split();
+        let mut kernel = FluxKernel::new(44100.0, consumer, snapshot_prod);
+        let mut pattern = pattern_with_bpm(120.0);
+        pattern.tracks[0].subtracks[0].steps[2].trig_type = TrigType::Note;
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        // Three steps in one callback: the reader sees the sequence jump from 0 to 3
+        let mut buffer = vec![0.0; 12000 * 2];
+        kernel.process(&mut buffer, 2);
+        let snapshot = *snapshot_cons.read();
+        assert_eq!(snapshot.sequence, 3);
+        assert_eq!(snapshot.current_step, 2);
+        assert!(snapshot.triggered_tracks[0]);
+        assert!(!snapshot.triggered_tracks[1]);
+    }
+
+    #[test]
+    fn test_snapshot_reports_metrics_and_levels() {
+        let (mut producer, consumer) = RingBuffer::new(16);
+        let (snapshot_prod, mut snapshot_cons) = triple_buffer::TripleBuffer::new(&AudioSnapshot::default()).split();
+        let mut kernel = FluxKernel::new(44100.0, consumer, snapshot_prod);
+        let mut pattern = pattern_with_bpm(120.0);
+        pattern.tracks[0].subtracks[0].steps[0].trig_type = TrigType::Note;
+        producer.push(AudioCommand::SwapPattern(0, Box::new(pattern))).unwrap();
+        producer.push(AudioCommand::Play).unwrap();
+
+        let mut buffer = vec![0.0; 256 * 2];
+        kernel.process(&mut buffer, 2);
+        let metrics = snapshot_cons.read().metrics;
+        assert_eq!(metrics.queue_depth, 2);
+        assert_eq!(metrics.active_voices, 1);
+        assert!(metrics.dsp_load > 0.0);
+
+        let levels = snapshot_cons.read().levels;
+        assert!(levels.tracks[0].peak > 0.0);
+        assert!(levels.master.rms > 0.0);
+        assert_eq!(levels.tracks[1].peak, 0.0);
     }
 }
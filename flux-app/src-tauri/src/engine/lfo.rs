@@ -0,0 +1,128 @@
+use crate::engine::domain::{
+    PARAM_DECAY, PARAM_DELAY_SEND, PARAM_FILTER_CUTOFF, PARAM_PAN, PARAM_RESONANCE,
+    PARAM_REVERB_SEND, PARAM_VOLUME,
+};
+use crate::shared::models::{LFOShape, LFO};
+use std::f32::consts::PI;
+
+const RANDOM_STEPS_PER_CYCLE: f64 = 16.0; // Sample & hold rate of the Random shape
+
+/// Map an LFO destination (a MIDI CC number) to the internal parameter it modulates.
+/// Destinations without an internal equivalent (e.g. CC 1, Mod Wheel) only reach external gear.
+pub fn destination_param(destination: u8) -> Option<usize> {
+    match destination {
+        7 => Some(PARAM_VOLUME),          // Channel Volume
+        10 => Some(PARAM_PAN),            // Pan
+        71 => Some(PARAM_RESONANCE),      // Sound Controller 2 (Timbre)
+        74 => Some(PARAM_FILTER_CUTOFF),  // Sound Controller 5 (Brightness)
+        75 => Some(PARAM_DECAY),          // Sound Controller 6 (Decay Time)
+        91 => Some(PARAM_REVERB_SEND),    // Effects 1 Depth (Reverb)
+        94 => Some(PARAM_DELAY_SEND),     // Effects 4 Depth, no GM meaning
+        _ => None,
+    }
+}
+
+/// Evaluate an LFO at `bar_position` (bars since the top of the pattern).
+/// Returns a bipolar value scaled by `lfo.amount`.
+pub fn lfo_value(lfo: &LFO, bar_position: f64) -> f32 {
+    lfo_waveform(lfo, bar_position) * lfo.amount
+}
+
+/// The LFO's bipolar waveform (-1.0 to 1.0) at `bar_position`, ignoring `amount`.
+pub fn lfo_waveform(lfo: &LFO, bar_position: f64) -> f32 {
+    // Apply Speed and Phase Offset
+    let cycles = bar_position * lfo.speed as f64 + lfo.phase as f64;
+    let phase = cycles.rem_euclid(1.0) as f32;
+
+    match &lfo.shape {
+        LFOShape::Sine => (phase * 2.0 * PI).sin(),
+        LFOShape::Triangle => {
+            // 0 -> 1 -> 0 -> -1 -> 0
+            if phase < 0.25 {
+                phase * 4.0
+            } else if phase < 0.75 {
+                1.0 - (phase - 0.25) * 4.0
+            } else {
+                -1.0 + (phase - 0.75) * 4.0
+            }
+        }
+        LFOShape::Square => {
+            if phase < 0.5 {
+                1.0
+            } else {
+                -1.0
+            }
+        }
+        LFOShape::Random => {
+            // Deterministic sample & hold, so renders and live playback match
+            let segment = (cycles * RANDOM_STEPS_PER_CYCLE).floor() as i64;
+            random_bipolar(segment as u64)
+        }
+        LFOShape::Designer(points) => {
+            // Linear Interpolation between 16 points
+            let len = points.len();
+            let idx_f = phase * len as f32;
+            let idx = idx_f.floor() as usize;
+            let next_idx = (idx + 1) % len;
+            let frac = idx_f - idx as f32;
+
+            let p1 = points[idx % len];
+            let p2 = points[next_idx];
+
+            p1 + (p2 - p1) * frac
+        }
+    }
+}
+
+/// SplitMix64 finalizer, mapped to -1.0..1.0. Stateless, so it is safe on the audio thread.
+pub fn random_bipolar(seed: u64) -> f32 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lfo(shape: LFOShape) -> LFO {
+        LFO { shape, destination: 74, amount: 1.0, speed: 1.0, phase: 0.0 }
+    }
+
+    #[test]
+    fn test_speed_and_phase_offset() {
+        let mut sine = lfo(LFOShape::Sine);
+        sine.speed = 2.0;
+        // Two cycles per bar: a quarter cycle is reached after 1/8 bar
+        assert!((lfo_value(&sine, 0.125) - 1.0).abs() < 1e-6);
+
+        sine.speed = 1.0;
+        sine.phase = 0.25;
+        assert!((lfo_value(&sine, 0.0) - 1.0).abs() < 1e-6);
+        // Phase wraps across bars
+        assert!((lfo_value(&sine, 3.0) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_random_is_sample_and_hold() {
+        let random = lfo(LFOShape::Random);
+        let first = lfo_value(&random, 0.0);
+        // Held within one segment
+        assert_eq!(first, lfo_value(&random, 0.5 / 16.0));
+        assert!((-1.0..=1.0).contains(&first));
+
+        // Not a repeating cycle: later bars produce different values
+        let values: Vec<f32> = (0..64).map(|i| lfo_value(&random, i as f64 / 16.0)).collect();
+        assert!(values.iter().all(|v| (-1.0..=1.0).contains(v)));
+        assert!(values[0..16] != values[16..32]);
+    }
+
+    #[test]
+    fn test_destination_mapping() {
+        assert_eq!(destination_param(74), Some(PARAM_FILTER_CUTOFF));
+        assert_eq!(destination_param(10), Some(PARAM_PAN));
+        assert_eq!(destination_param(1), None);
+    }
+}
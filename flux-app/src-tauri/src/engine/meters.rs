@@ -0,0 +1,151 @@
+use crate::engine::domain::MAX_TRACKS;
+use crate::engine::kernel::FrameTaps;
+use serde::Serialize;
+
+/// Emitted with the latest `MeterLevels` every `LEVELS_INTERVAL`.
+pub const AUDIO_LEVELS_EVENT: &str = "audio-levels";
+pub const LEVELS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(33); // ~30 FPS
+
+const PEAK_RELEASE_SECONDS: f32 = 1.5; // Time to fall 60 dB
+const RMS_WINDOW_SECONDS: f32 = 0.3;
+const HOLD_SECONDS: f32 = 1.0;
+
+/// One meter reading, linear amplitude (1.0 = 0 dBFS). Left and right are combined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct MeterLevel {
+    pub peak: f32, // Instant attack, exponential release
+    pub rms: f32,
+    pub hold: f32, // Highest peak, held for HOLD_SECONDS
+}
+
+/// Per-track (post-FX) and master levels, published with every `AudioSnapshot`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct MeterLevels {
+    pub tracks: [MeterLevel; MAX_TRACKS],
+    pub master: MeterLevel,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct LevelMeter {
+    peak: f32,
+    mean_square: f32,
+    hold: f32,
+    hold_remaining: usize, // Samples until the hold starts following the peak
+}
+
+impl LevelMeter {
+    fn process(&mut self, frame: [f32; 2], coefficients: &Coefficients) {
+        let amplitude = frame[0].abs().max(frame[1].abs());
+        self.peak = amplitude.max(self.peak * coefficients.peak_release);
+
+        let square = (frame[0] * frame[0] + frame[1] * frame[1]) * 0.5;
+        self.mean_square += (square - self.mean_square) * coefficients.rms;
+
+        if self.peak >= self.hold {
+            self.hold = self.peak;
+            self.hold_remaining = coefficients.hold_samples;
+        } else if self.hold_remaining > 0 {
+            self.hold_remaining -= 1;
+        } else {
+            self.hold = self.peak;
+        }
+    }
+
+    fn level(&self) -> MeterLevel {
+        MeterLevel { peak: self.peak, rms: self.mean_square.sqrt(), hold: self.hold }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Coefficients {
+    peak_release: f32, // Per-sample gain
+    rms: f32,          // One-pole smoothing of the mean square
+    hold_samples: usize,
+}
+
+impl Coefficients {
+    fn new(sample_rate: f32) -> Self {
+        let sample_rate = sample_rate.max(1.0);
+        Self {
+            peak_release: 0.001_f32.powf(1.0 / (PEAK_RELEASE_SECONDS * sample_rate)),
+            rms: 1.0 - (-1.0 / (RMS_WINDOW_SECONDS * sample_rate)).exp(),
+            hold_samples: (HOLD_SECONDS * sample_rate) as usize,
+        }
+    }
+}
+
+/// Track and master meters, fed one frame at a time from the kernel's `FrameTaps`.
+/// Fixed size, never allocates.
+#[derive(Clone, Copy, Debug)]
+pub struct MeterBank {
+    tracks: [LevelMeter; MAX_TRACKS],
+    master: LevelMeter,
+    coefficients: Coefficients,
+}
+
+impl MeterBank {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            tracks: [LevelMeter::default(); MAX_TRACKS],
+            master: LevelMeter::default(),
+            coefficients: Coefficients::new(sample_rate),
+        }
+    }
+
+    pub fn process(&mut self, taps: &FrameTaps) {
+        for (meter, frame) in self.tracks.iter_mut().zip(taps.track_post_fx.iter()) {
+            meter.process(*frame, &self.coefficients);
+        }
+        self.master.process(taps.master, &self.coefficients);
+    }
+
+    pub fn levels(&self) -> MeterLevels {
+        let mut levels = MeterLevels { master: self.master.level(), ..MeterLevels::default() };
+        for (level, meter) in levels.tracks.iter_mut().zip(self.tracks.iter()) {
+            *level = meter.level();
+        }
+        levels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 1000.0;
+
+    fn feed(bank: &mut MeterBank, track: usize, value: f32, frames: usize) {
+        let mut taps = FrameTaps::default();
+        taps.track_post_fx[track] = [value, -value];
+        taps.master = [value, value];
+        for _ in 0..frames {
+            bank.process(&taps);
+        }
+    }
+
+    #[test]
+    fn test_constant_signal_levels() {
+        let mut bank = MeterBank::new(SAMPLE_RATE);
+        feed(&mut bank, 2, 0.5, 5000);
+        let levels = bank.levels();
+        assert!((levels.tracks[2].peak - 0.5).abs() < 1e-6);
+        assert!((levels.tracks[2].rms - 0.5).abs() < 1e-3);
+        assert_eq!(levels.tracks[0], MeterLevel::default());
+        assert!((levels.master.hold - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_peak_decays_and_hold_releases() {
+        let mut bank = MeterBank::new(SAMPLE_RATE);
+        feed(&mut bank, 0, 1.0, 1);
+        feed(&mut bank, 0, 0.0, 500); // Half a second
+        let level = bank.levels().tracks[0];
+        assert!(level.peak < 1.0 && level.peak > 0.01);
+        assert_eq!(level.hold, 1.0);
+
+        feed(&mut bank, 0, 0.0, 1000); // Past the hold time
+        let level = bank.levels().tracks[0];
+        assert_eq!(level.hold, level.peak);
+        assert!(level.peak < 0.01);
+    }
+}
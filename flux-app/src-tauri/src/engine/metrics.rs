@@ -0,0 +1,146 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Emitted with the latest `EngineMetrics` every `METRICS_INTERVAL`.
+pub const ENGINE_METRICS_EVENT: &str = "engine-metrics";
+pub const METRICS_INTERVAL: Duration = Duration::from_millis(100);
+pub const METRICS_HISTORY_LEN: usize = 300; // 30 seconds at METRICS_INTERVAL
+
+const LOAD_SMOOTHING: f32 = 0.1; // Weight of the newest callback in the average
+const PEAK_DECAY: f32 = 0.995; // Per callback
+
+/// Callback performance, published with every `AudioSnapshot`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct EngineMetrics {
+    pub dsp_load: f32,  // Percent of the buffer deadline, smoothed
+    pub peak_load: f32, // Percent, slowly decaying peak
+    pub xruns: u64,     // Callbacks that arrived too late (the device ran dry)
+    pub overruns: u64,  // Callbacks that took longer than their buffer
+    pub active_voices: usize,
+    pub queue_depth: usize, // Commands waiting at the start of the last callback
+}
+
+/// Measures callback time against the buffer deadline. Lives in the kernel.
+#[derive(Debug, Default)]
+pub struct LoadMeter {
+    last_start: Option<Instant>,
+    last_period: Duration,
+    metrics: EngineMetrics,
+}
+
+impl LoadMeter {
+    /// Mark the start of a callback. A gap of more than two buffers since the previous one is an xrun.
+    pub fn begin(&mut self, now: Instant) {
+        if let Some(last) = self.last_start {
+            if now.saturating_duration_since(last) > self.last_period * 2 {
+                self.metrics.xruns += 1;
+            }
+        }
+        self.last_start = Some(now);
+    }
+
+    /// Record a finished callback that took `elapsed` to render `period` worth of audio.
+    pub fn end(&mut self, elapsed: Duration, period: Duration) {
+        if period.is_zero() {
+            return;
+        }
+        self.last_period = period;
+        let load = elapsed.as_secs_f32() / period.as_secs_f32() * 100.0;
+        if elapsed > period {
+            self.metrics.overruns += 1;
+        }
+        self.metrics.dsp_load += (load - self.metrics.dsp_load) * LOAD_SMOOTHING;
+        self.metrics.peak_load = load.max(self.metrics.peak_load * PEAK_DECAY);
+    }
+
+    /// Forget the previous callback, so a new stream doesn't count as an xrun.
+    pub fn reset_clock(&mut self) {
+        self.last_start = None;
+    }
+
+    pub fn metrics(&self, active_voices: usize, queue_depth: usize) -> EngineMetrics {
+        EngineMetrics { active_voices, queue_depth, ..self.metrics }
+    }
+}
+
+/// Recent metrics, sampled by the sync thread for `get_engine_metrics`.
+#[derive(Debug)]
+pub struct MetricsHistory {
+    samples: VecDeque<EngineMetrics>,
+}
+
+impl Default for MetricsHistory {
+    fn default() -> Self {
+        Self { samples: VecDeque::with_capacity(METRICS_HISTORY_LEN) }
+    }
+}
+
+impl MetricsHistory {
+    pub fn push(&mut self, metrics: EngineMetrics) {
+        if self.samples.len() == METRICS_HISTORY_LEN {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(metrics);
+    }
+
+    /// Oldest first.
+    pub fn to_vec(&self) -> Vec<EngineMetrics> {
+        self.samples.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn test_load_and_overruns() {
+        let mut meter = LoadMeter::default();
+        for _ in 0..200 {
+            meter.end(Duration::from_millis(5), PERIOD);
+        }
+        let metrics = meter.metrics(3, 1);
+        assert!((metrics.dsp_load - 50.0).abs() < 0.1);
+        assert_eq!(metrics.overruns, 0);
+        assert_eq!((metrics.active_voices, metrics.queue_depth), (3, 1));
+
+        meter.end(Duration::from_millis(15), PERIOD);
+        let metrics = meter.metrics(0, 0);
+        assert_eq!(metrics.overruns, 1);
+        assert!((metrics.peak_load - 150.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_late_callback_is_an_xrun() {
+        let mut meter = LoadMeter::default();
+        let start = Instant::now();
+        meter.begin(start);
+        meter.end(Duration::from_millis(1), PERIOD);
+        meter.begin(start + PERIOD);
+        meter.end(Duration::from_millis(1), PERIOD);
+        assert_eq!(meter.metrics(0, 0).xruns, 0);
+
+        meter.begin(start + PERIOD * 5);
+        assert_eq!(meter.metrics(0, 0).xruns, 1);
+
+        // A new stream starts from scratch
+        meter.reset_clock();
+        meter.begin(start + PERIOD * 50);
+        assert_eq!(meter.metrics(0, 0).xruns, 1);
+    }
+
+    #[test]
+    fn test_history_keeps_the_latest() {
+        let mut history = MetricsHistory::default();
+        for i in 0..METRICS_HISTORY_LEN + 5 {
+            history.push(EngineMetrics { queue_depth: i, ..EngineMetrics::default() });
+        }
+        let samples = history.to_vec();
+        assert_eq!(samples.len(), METRICS_HISTORY_LEN);
+        assert_eq!(samples[0].queue_depth, 5);
+        assert_eq!(samples.last().unwrap().queue_depth, METRICS_HISTORY_LEN + 4);
+    }
+}
@@ -6,20 +6,53 @@ use std::thread;
 use midir::{MidiOutput, MidiOutputConnection};
 use midir::os::unix::VirtualOutput;
 use rtrb::Consumer;
-use crate::shared::models::{Pattern, TrigType, LFOShape};
+use crate::shared::models::{
+    AtomicStep, MidiRetuning, Pattern, PatternSwitch, Song, Track, TrigType, LFOShape, MAX_SUBTRACKS, PATTERN_SLOTS,
+};
+use crate::engine::arpeggiator::{ArpSettings, ArpState, TICKS_PER_STEP};
+use crate::engine::arranger::{Arranger, SongStep};
+use crate::engine::chord::{chord_notes, MAX_CHORD_NOTES};
+use crate::engine::diagnostics::{Diagnostic, DiagnosticSender, LogSource, MIDI_JITTER_WARN_MS};
+use crate::engine::domain::{PARAM_COARSE_TUNE, PARAM_TUNING};
+use crate::engine::tuning::{
+    coarse_tune_semitones, fine_tune_cents, midi_note_with_bend, mts_retune_messages, note_frequency, pitch_bend,
+    PITCH_BEND_CENTER,
+};
+use crate::engine::voice::resolve_param;
 
 pub enum EngineCommand {
-    UpdatePattern(Pattern),
+    UpdatePattern(usize, Pattern),          // Bank slot
+    UpdateTrack(usize, usize, Box<Track>),  // Bank slot, Track index
+    QueuePattern(usize, PatternSwitch),     // Bank slot to play next
+    UpdateSong(Song),
     SetLFOShape { track_id: usize, lfo_index: usize, shape: LFOShape },
     SetLFODesignerValue { track_id: usize, lfo_index: usize, step: usize, value: f32 },
+    NoteInput(usize, u8, u8), // Track, Note, Velocity (0 releases): held notes for the arpeggiator
+}
+
+/// A track's arpeggiator on the MIDI clock.
+#[derive(Clone, Copy, Default)]
+struct MidiArp {
+    state: ArpState,
+    next_tick: Option<u64>,
+    sounding: Option<(u8, u64)>, // MIDI note playing and the tick its note off is due
 }
 
 pub struct MidiEngine {
     midi_out: MidiOutputConnection,
     command_consumer: Consumer<EngineCommand>,
     pattern: Option<Pattern>,
+    pattern_slot: usize, // Bank slot of `pattern`
+    patterns: Vec<Option<Pattern>>, // The other bank slots, PATTERN_SLOTS long; the playing slot is kept empty
+    queued_pattern: Option<(usize, PatternSwitch)>,
+    song: Song,
+    step: usize, // Pattern step of the latest step tick
     ppqn: u32,
     bpm: f32,
+    pitch_bends: [u16; 16], // Last bend sent on each channel
+    arps: [MidiArp; 16], // Per track
+    arranger: Arranger, // Song position while song mode is on
+    pub diagnostics: DiagnosticSender, // Heartbeats and jitter warnings for the logger thread
 }
 
 impl MidiEngine {
@@ -48,8 +81,17 @@ impl MidiEngine {
             midi_out: conn,
             command_consumer,
             pattern: None,
+            pattern_slot: 0,
+            patterns: vec![None; PATTERN_SLOTS],
+            queued_pattern: None,
+            song: Song::default(),
+            step: 15, // The first step tick plays step 0
             ppqn: 24,
             bpm: 120.0,
+            pitch_bends: [PITCH_BEND_CENTER; 16],
+            arps: [MidiArp::default(); 16],
+            arranger: Arranger::default(),
+            diagnostics: DiagnosticSender::disconnected(),
         })
     }
 
@@ -66,19 +108,30 @@ impl MidiEngine {
         
         // Set Thread Priority to High/Realtime
         // We try to set it to highest possible
-        match set_current_thread_priority(ThreadPriority::Max) {
-            Ok(_) => println!("MIDI Engine thread priority set to Max"),
-            Err(e) => eprintln!("Failed to set MIDI Engine thread priority: {:?}", e),
-        }
+        let raised = set_current_thread_priority(ThreadPriority::Max).is_ok();
+        self.diagnostics.send(Diagnostic::ThreadPriority { source: LogSource::Midi, raised });
 
         loop {
             // 1. Process Commands
             while let Ok(cmd) = self.command_consumer.pop() {
                 match cmd {
-                    EngineCommand::UpdatePattern(p) => {
+                    EngineCommand::UpdatePattern(slot, p) if slot != self.pattern_slot => {
+                        if let Some(stored) = self.patterns.get_mut(slot) {
+                            *stored = Some(p);
+                        }
+                    },
+                    EngineCommand::UpdatePattern(_, p) => {
                         self.bpm = p.bpm;
+                        Self::retune(&mut self.midi_out, self.pattern.as_ref(), &p);
                         self.pattern = Some(p);
                     },
+                    EngineCommand::UpdateTrack(slot, track_id, track) => {
+                        Self::update_track(self.pattern.as_mut(), self.pattern_slot, &mut self.patterns, slot, track_id, *track);
+                    },
+                    EngineCommand::QueuePattern(slot, switch) => {
+                        self.queued_pattern = (slot < PATTERN_SLOTS).then_some((slot, switch));
+                    },
+                    EngineCommand::UpdateSong(song) => self.song = song,
                     EngineCommand::SetLFOShape { track_id, lfo_index, shape } => {
                         if let Some(p) = &mut self.pattern {
                             if let Some(track) = p.tracks.get_mut(track_id) {
@@ -101,6 +154,16 @@ impl MidiEngine {
                             }
                         }
                     },
+                    EngineCommand::NoteInput(track_id, note, velocity) => {
+                        let enabled = self.pattern.as_ref().and_then(|p| p.tracks.get(track_id)).is_some_and(|t| t.arp_enabled);
+                        if let Some(arp) = self.arps.get_mut(track_id).filter(|_| enabled) {
+                            if velocity == 0 {
+                                arp.state.note_off(note);
+                            } else if arp.state.note_on(note, velocity) {
+                                arp.next_tick.get_or_insert(tick_count);
+                            }
+                        }
+                    },
                 }
             }
 
@@ -133,8 +196,10 @@ impl MidiEngine {
             }
 
             // 4. Sequencer Logic
+            // Step index and muted tracks on step ticks
+            let step = tick_count.is_multiple_of(6).then(|| self.next_step(((tick_count / 6) % 16) as usize));
             if let Some(pattern) = &self.pattern {
-                Self::process_tick(&mut self.midi_out, tick_count, pattern);
+                Self::process_tick(&mut self.midi_out, tick_count, step, pattern, &mut self.pitch_bends, &mut self.arps);
             }
             
             tick_count += 1;
@@ -143,18 +208,117 @@ impl MidiEngine {
             if tick_count % 1000 == 0 {
                 let jitter = Instant::now().duration_since(next_tick_time);
                 // We use micros for precision logging
-                let drift_ms = jitter.as_micros() as f64 / 1000.0;
+                let drift_ms = jitter.as_micros() as f32 / 1000.0;
                 
-                println!("Heartbeat: Tick {}, Drift: {:.3} ms", tick_count, drift_ms);
+                self.diagnostics.send(Diagnostic::MidiHeartbeat { tick: tick_count, drift_ms });
                 
-                if drift_ms > 0.5 {
-                    println!("WARNING: High Jitter Detected (>0.5ms)");
+                if drift_ms > MIDI_JITTER_WARN_MS {
+                    self.diagnostics.send(Diagnostic::MidiJitter { tick: tick_count, drift_ms });
+                }
+            }
+        }
+    }
+
+    // Move on to the next step, following the song's rows in song mode. Otherwise the pattern
+    // loops, and a queued pattern takes over when its switch is due (`bar_step` 0 starts a bar).
+    fn next_step(&mut self, bar_step: usize) -> (usize, [bool; 16]) {
+        let song_step = Self::song_step(&mut self.arranger, &self.song);
+        let mut mutes = [false; 16];
+        match song_step {
+            Some(song_step) => {
+                self.queued_pattern = None;
+                self.switch_pattern(song_step.pattern);
+                self.step = song_step.step;
+                mutes = song_step.mutes;
+            }
+            None => {
+                self.step = (self.step + 1) % 16;
+                if let Some((slot, switch)) = self.queued_pattern {
+                    let due = match switch {
+                        PatternSwitch::EndOfPattern => self.step == 0,
+                        PatternSwitch::Immediate => true,
+                        PatternSwitch::NextBar => bar_step == 0,
+                    };
+                    if due {
+                        self.queued_pattern = None;
+                        if self.switch_pattern(slot) {
+                            self.step = 0;
+                        }
+                    }
                 }
             }
         }
+        let pattern_bpm = self.pattern.as_ref().map_or(self.bpm, |p| p.bpm);
+        self.bpm = song_step.and_then(|s| s.tempo).filter(|bpm| *bpm > 0.0).unwrap_or(pattern_bpm);
+        (self.step, mutes)
     }
 
-    fn process_tick(midi_out: &mut MidiOutputConnection, tick_count: u64, pattern: &Pattern) {
+    // Make a bank slot's pattern the playing one, putting the playing pattern back in its slot.
+    // Returns false for slots never written.
+    fn switch_pattern(&mut self, slot: usize) -> bool {
+        if slot == self.pattern_slot {
+            return true;
+        }
+        let Some(next) = self.patterns.get_mut(slot).and_then(Option::take) else {
+            return false;
+        };
+        Self::retune(&mut self.midi_out, self.pattern.as_ref(), &next);
+        self.patterns[self.pattern_slot] = self.pattern.replace(next);
+        self.pattern_slot = slot;
+        true
+    }
+
+    // Replace a track of the playing pattern or a stored one. Edits to slots never written or
+    // out of range are dropped, like the kernel does.
+    fn update_track(
+        playing: Option<&mut Pattern>,
+        playing_slot: usize,
+        patterns: &mut [Option<Pattern>],
+        slot: usize,
+        track_id: usize,
+        track: Track,
+    ) {
+        let pattern = if slot == playing_slot {
+            playing
+        } else {
+            patterns.get_mut(slot).and_then(Option::as_mut)
+        };
+        if let Some(current) = pattern.and_then(|p| p.tracks.get_mut(track_id)) {
+            *current = track;
+        }
+    }
+
+    // Retune the receiver's keys whenever the project tuning changes
+    fn retune(midi_out: &mut MidiOutputConnection, old: Option<&Pattern>, new: &Pattern) {
+        let retune = new.tuning.midi_retuning == MidiRetuning::Mts && old.is_none_or(|old| old.tuning != new.tuning);
+        if retune {
+            for message in mts_retune_messages(&new.tuning) {
+                let _ = midi_out.send(&message);
+            }
+        }
+    }
+
+    // Next step of the song, None outside song mode.
+    // There is no transport to stop, so the song starts over after its last row.
+    fn song_step(arranger: &mut Arranger, song: &Song) -> Option<SongStep> {
+        if !song.enabled || song.rows.is_empty() {
+            arranger.reset();
+            return None;
+        }
+        arranger.advance(song).or_else(|| {
+            arranger.reset();
+            arranger.advance(song)
+        })
+    }
+
+    fn process_tick(
+        midi_out: &mut MidiOutputConnection,
+        tick_count: u64,
+        step: Option<(usize, [bool; 16])>, // Step index and muted tracks, on ticks that start a step
+        pattern: &Pattern,
+        pitch_bends: &mut [u16; 16],
+        arps: &mut [MidiArp; 16],
+    ) {
         // Simple logic for now: Advance tracks
         // Assuming 16 step pattern for simplicity for now, but should use pattern length
         
@@ -194,11 +358,13 @@ impl MidiEngine {
             }
         }
         
-        if tick_count % 6 == 0 {
-            let step_index = (tick_count / 6) % 16;
+        if let Some((step_index, mutes)) = step {
             // println!("Step {}", step_index);
             
-            for track in &pattern.tracks {
+            for ((track, arp), muted) in pattern.tracks.iter().zip(arps.iter_mut()).zip(mutes) {
+                if muted {
+                    continue;
+                }
                 // Check if track has a trig at this step
                 // Currently Track has subtracks with steps. 
                 // We need to map steps to the grid. 
@@ -207,69 +373,146 @@ impl MidiEngine {
                 // But usually step sequencer uses index-based access.
                 // Let's assume `steps` is 16 elements long for now or check bounds.
                 
-                for subtrack in &track.subtracks {
-                    if let Some(step) = subtrack.steps.get(step_index as usize) {
+                let mut arp_started = false; // Trigs of later subtracks on this step join the arp's input
+                for subtrack in track.subtracks.iter().take(MAX_SUBTRACKS) {
+                    if let Some(step) = subtrack.steps.get(step_index) {
+                        // The step's chord, a single note unless it has one
+                        let mut chord = [0.0; MAX_CHORD_NOTES];
+                        let mut keys = [0; MAX_CHORD_NOTES];
+                        let count = chord_notes(step.note as f32, &step.chord, &mut chord);
+                        for (key, note) in keys.iter_mut().zip(&chord[..count]) {
+                            *key = *note as u8;
+                        }
+                        let keys = &keys[..count];
+                        if track.arp_enabled && matches!(step.trig_type, TrigType::Note | TrigType::Lock) {
+                             // The arp plays Note trigs for their length; Locks only change its settings
+                             let settings = ArpSettings::from_params(|id| resolve_param(track, step, id));
+                             if step.trig_type == TrigType::Lock {
+                                 arp.state.settings = settings;
+                                 continue;
+                             }
+                             let length = (step.length.max(0.0) * TICKS_PER_STEP as f32) as u64;
+                             if arp_started {
+                                 arp.state.add_trig_notes(keys, tick_count + length);
+                             } else {
+                                 arp.state.trigger(settings, keys, step.velocity, tick_count + length, tick_count);
+                             }
+                             arp_started = true;
+                             arp.next_tick = Some(tick_count);
+                             continue;
+                         }
                          if step.trig_type == TrigType::Note {
-                             Self::send_note_on(midi_out, track.id as u8, step.note, step.velocity);
+                             // Chord notes are stacked on the track's channel and share its
+                             // pitch bend, which follows the lowest note
+                             let channel = track.id & 0x0F;
+                             let mut sent = [0; MAX_CHORD_NOTES];
+                             let mut sent_count = 0;
+                             for &key in keys {
+                                 // Keys the tuning leaves unmapped are silent
+                                 let Some((note, bend)) = Self::tuned_note(pattern, track, step, key) else {
+                                     continue;
+                                 };
+                                 if sent_count == 0 {
+                                     if let Some(bend) = bend.filter(|&bend| bend != pitch_bends[channel]) {
+                                         Self::send_pitch_bend(midi_out, channel as u8, bend);
+                                         pitch_bends[channel] = bend;
+                                     }
+                                 }
+                                 Self::send_note_on(midi_out, track.id as u8, note, step.velocity);
+                                 sent[sent_count] = note;
+                                 sent_count += 1;
+                             }
                              
                              // Note Off scheduled? 
                              // For this MVP, we might skip note off or schedule it.
                              // MIDI usually needs Note Off. 
                              // We'll send a very short Note Off for now or implement a note stack later.
-                             Self::send_note_off(midi_out, track.id as u8, step.note);
+                             for &note in &sent[..sent_count] {
+                                 Self::send_note_off(midi_out, track.id as u8, note);
+                             }
                          }
                     }
                 }
             }
         }
-    }
-    
-    fn calculate_lfo(lfo: &crate::shared::models::LFO, global_phase: f32) -> f32 {
-        use crate::shared::models::LFOShape;
-        // use std::f32::consts::PI; // Already imported or available? It was valid before.
-        use std::f32::consts::PI;
 
-        // Apply Speed and Phase Offset
-        let mut phase = (global_phase * lfo.speed + lfo.phase) % 1.0;
-        if phase < 0.0 { phase += 1.0; }
+        for (track, arp) in pattern.tracks.iter().zip(arps.iter_mut()) {
+            Self::run_arp(midi_out, tick_count, pattern, track, arp, pitch_bends);
+        }
+    }
 
-        let raw = match &lfo.shape {
-            LFOShape::Sine => (phase * 2.0 * PI).sin(),
-            LFOShape::Triangle => {
-                // 0 -> 1 -> 0 -> -1 -> 0
-                if phase < 0.25 {
-                    phase * 4.0
-                } else if phase < 0.75 {
-                    1.0 - (phase - 0.25) * 4.0
-                } else {
-                    -1.0 + (phase - 0.75) * 4.0
-                }
-            },
-            LFOShape::Square => if phase < 0.5 { 1.0 } else { -1.0 },
-            LFOShape::Random => {
-                // Return a hash based on phase step to adhere to deterministic "Random" if needed
-                // Or just use rand. For seeded random we'd need a seed. 
-                // Let's stick to pseudo-random based on tick for now or simple "Noise"
-                // For simplicity, let's use Sine for now or implement a proper Rand later.
-                (phase * 2.0 * PI).sin() // Placeholder
-            },
-            LFOShape::Designer(points) => {
-                // Linear Interpolation between 16 points
-                let len = 16;
-                let idx_f = phase * len as f32;
-                let idx = idx_f.floor() as usize;
-                let next_idx = (idx + 1) % len;
-                let frac = idx_f - idx as f32;
-                
-                let p1 = points[idx % len];
-                let p2 = points[next_idx];
-                
-                // Lerp
-                p1 + (p2 - p1) * frac
+    // Release the arp's note when its gate ends, then play the next one if it is due.
+    // The arp stops once its input is released or the track turns it off.
+    fn run_arp(
+        midi_out: &mut MidiOutputConnection,
+        tick_count: u64,
+        pattern: &Pattern,
+        track: &Track,
+        arp: &mut MidiArp,
+        pitch_bends: &mut [u16; 16],
+    ) {
+        let channel = track.id & 0x0F;
+        if let Some((note, off_tick)) = arp.sounding {
+            if tick_count >= off_tick {
+                Self::send_note_off(midi_out, channel as u8, note);
+                arp.sounding = None;
             }
+        }
+
+        let Some(due) = arp.next_tick.filter(|&due| due <= tick_count) else {
+            return;
+        };
+        arp.next_tick = None;
+        if !track.arp_enabled {
+            return;
+        }
+        let Some((note, velocity)) = arp.state.next_note(tick_count) else {
+            return;
+        };
+        let rate = arp.state.settings.rate_ticks as u64;
+        arp.next_tick = Some(due + rate);
+
+        // Legato gates cut the previous note
+        if let Some((previous, _)) = arp.sounding.take() {
+            Self::send_note_off(midi_out, channel as u8, previous);
+        }
+        // Arp notes use the track's tuning parameters
+        let Some((midi_note, bend)) = Self::tuned_note(pattern, track, &AtomicStep::default(), note) else {
+            return;
         };
+        if let Some(bend) = bend.filter(|&bend| bend != pitch_bends[channel]) {
+            Self::send_pitch_bend(midi_out, channel as u8, bend);
+            pitch_bends[channel] = bend;
+        }
+        Self::send_note_on(midi_out, channel as u8, midi_note, velocity);
+        let gate = ((arp.state.settings.gate * rate as f32).round() as u64).max(1);
+        arp.sounding = Some((midi_note, tick_count + gate));
+    }
+    
+    /// The MIDI note (and pitch bend, if any) that plays `note` on a step in the project tuning,
+    /// with the track's coarse and fine tune.
+    fn tuned_note(pattern: &Pattern, track: &Track, step: &AtomicStep, note: u8) -> Option<(u8, Option<u16>)> {
+        let tuning = &pattern.tuning;
+        let key = note as f32 + coarse_tune_semitones(resolve_param(track, step, PARAM_COARSE_TUNE));
+        let cents = fine_tune_cents(resolve_param(track, step, PARAM_TUNING));
+        match tuning.midi_retuning {
+            MidiRetuning::Off => Some((key.clamp(0.0, 127.0) as u8, None)),
+            MidiRetuning::PitchBend => {
+                let frequency = note_frequency(tuning, key)? * 2.0_f32.powf(cents / 1200.0);
+                let (note, bend) = midi_note_with_bend(frequency, tuning.midi_bend_range);
+                Some((note, Some(bend)))
+            }
+            // The receiver's keys are already retuned, only the fine tune is bent
+            MidiRetuning::Mts => {
+                note_frequency(tuning, key)?;
+                Some((key.clamp(0.0, 127.0) as u8, Some(pitch_bend(cents / 100.0, tuning.midi_bend_range))))
+            }
+        }
+    }
 
-        raw * lfo.amount
+    fn calculate_lfo(lfo: &crate::shared::models::LFO, global_phase: f32) -> f32 {
+        // Shared with the audio kernel, so external CCs and internal modulation agree
+        crate::engine::lfo::lfo_value(lfo, global_phase as f64)
     }
 
     fn send_note_on(midi_out: &mut MidiOutputConnection, channel: u8, note: u8, velocity: u8) {
@@ -283,6 +526,11 @@ impl MidiEngine {
         let _ = midi_out.send(&[status, note, 0]);
     }
 
+    fn send_pitch_bend(midi_out: &mut MidiOutputConnection, channel: u8, bend: u16) {
+        let status = 0xE0 | (channel & 0x0F);
+        let _ = midi_out.send(&[status, (bend & 0x7F) as u8, (bend >> 7) as u8]);
+    }
+
     fn send_cc(midi_out: &mut MidiOutputConnection, channel: u8, cc: u8, val: u8) {
         let status = 0xB0 | (channel & 0x0F);
         let _ = midi_out.send(&[status, cc, val]);
@@ -312,6 +560,35 @@ mod tests {
         assert!((MidiEngine::calculate_lfo(&lfo, 0.75) - -1.0).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_track_updates_only_reach_their_slot() {
+        let track = |length: u32| Track {
+            id: 0,
+            machine: crate::shared::models::MachineType::OneShot,
+            subtracks: Vec::new(),
+            length,
+            scale: 1.0,
+            default_params: crate::shared::models::default_track_params(),
+            lfos: Vec::new(),
+            mod_matrix: Vec::new(),
+            mod_envelopes: Default::default(),
+            sample: None,
+            arp_enabled: false,
+        };
+        let mut playing = Pattern { tracks: vec![track(16)], ..Pattern::default() };
+        let mut patterns = vec![None; PATTERN_SLOTS];
+        patterns[2] = Some(playing.clone());
+
+        // Slots never written and slots out of range leave the playing pattern alone
+        let edits = [(5, track(10)), (PATTERN_SLOTS, track(20)), (2, track(30)), (0, track(40))];
+        for (slot, edit) in edits {
+            MidiEngine::update_track(Some(&mut playing), 0, &mut patterns, slot, 0, edit);
+        }
+        assert_eq!(playing.tracks[0].length, 40);
+        assert_eq!(patterns[2].as_ref().map(|p| p.tracks[0].length), Some(30));
+        assert!(patterns[5].is_none());
+    }
+
     #[test]
     fn test_designer_interpolation() {
         let mut points = [0.0; 16];
@@ -0,0 +1,64 @@
+use midir::os::unix::VirtualInput;
+use midir::{MidiInput, MidiInputConnection};
+use std::sync::Arc;
+
+/// A note played on a MIDI input, for the track of its channel. Velocity 0 releases it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NoteInput {
+    pub track: usize,
+    pub note: u8,
+    pub velocity: u8,
+}
+
+/// Decode a note on/off message; other messages are ignored.
+pub fn parse_note(message: &[u8]) -> Option<NoteInput> {
+    let &[status, note, velocity] = message else {
+        return None;
+    };
+    let track = (status & 0x0F) as usize;
+    match status & 0xF0 {
+        0x90 => Some(NoteInput { track, note: note & 0x7F, velocity: velocity & 0x7F }),
+        0x80 => Some(NoteInput { track, note: note & 0x7F, velocity: 0 }),
+        _ => None,
+    }
+}
+
+/// Listen for notes on a virtual "Flux Sequencer In" port, or the first input port where
+/// virtual ports are unsupported. Notes arrive on midir's thread; drop the connection to stop.
+pub fn connect(
+    on_note: impl Fn(NoteInput) + Send + Sync + 'static,
+) -> Result<MidiInputConnection<()>, Box<dyn std::error::Error>> {
+    // Shared, so the fallback connection can still use it
+    let on_note = Arc::new(on_note);
+    let callback = move |_timestamp: u64, message: &[u8], _: &mut ()| {
+        if let Some(note) = parse_note(message) {
+            on_note(note);
+        }
+    };
+
+    let midi_in = MidiInput::new("Flux Sequencer")?;
+    match midi_in.create_virtual("Flux Sequencer In", callback.clone(), ()) {
+        Ok(conn) => Ok(conn),
+        Err(_) => {
+            let midi_in = MidiInput::new("Flux Sequencer")?;
+            let ports = midi_in.ports();
+            let port = ports.first().ok_or("No MIDI input ports available and could not create virtual port")?;
+            Ok(midi_in.connect(port, "Flux Sequencer In", callback, ())?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_note_messages() {
+        assert_eq!(parse_note(&[0x92, 60, 100]), Some(NoteInput { track: 2, note: 60, velocity: 100 }));
+        // Note on with velocity 0 and note off both release
+        assert_eq!(parse_note(&[0x90, 60, 0]), Some(NoteInput { track: 0, note: 60, velocity: 0 }));
+        assert_eq!(parse_note(&[0x8F, 64, 40]), Some(NoteInput { track: 15, note: 64, velocity: 0 }));
+        assert_eq!(parse_note(&[0xB0, 74, 10]), None);
+        assert_eq!(parse_note(&[0xF8]), None);
+    }
+}
@@ -1,6 +1,26 @@
 pub mod kernel;
 // pub mod sequencer;
-// pub mod voice;
+pub mod voice;
 pub mod domain;
-// pub mod sync;
+pub mod sync;
 pub mod midi_engine;
+pub mod fx;
+pub mod render;
+pub mod lfo;
+pub mod modulation;
+pub mod device;
+pub mod null_output;
+pub mod sample_pool;
+pub mod recorder;
+pub mod metrics;
+pub mod meters;
+pub mod analyzer;
+pub mod diagnostics;
+pub mod scheduler;
+pub mod render_pool;
+pub mod smoothing;
+pub mod tuning;
+pub mod arpeggiator;
+pub mod midi_input;
+pub mod chord;
+pub mod arranger;
@@ -0,0 +1,298 @@
+use crate::engine::domain::{
+    MAX_MOD_SLOTS, MOD_ENVELOPES_PER_TRACK, NUM_SYNTH_PARAMS, PARAM_MOD_DEPTH_BASE,
+};
+use crate::engine::lfo::{destination_param, lfo_value, lfo_waveform};
+use crate::shared::models::{AtomicStep, EnvelopeMode, ModEnvelope, ModSource, Track};
+
+// Map normalized envelope times (0.0 - 1.0) to seconds (1ms - 10s)
+fn envelope_seconds(time: f32) -> f32 {
+    0.001 * 10_000.0_f32.powf(time.clamp(0.0, 1.0))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EnvelopeStage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Running state of one modulation envelope. Linear segments, advanced at control rate.
+#[derive(Clone, Copy, Debug)]
+pub struct EnvelopeState {
+    stage: EnvelopeStage,
+    level: f32,
+    release_from: f32,
+    gate_samples: usize,
+}
+
+impl Default for EnvelopeState {
+    fn default() -> Self {
+        Self { stage: EnvelopeStage::Idle, level: 0.0, release_from: 0.0, gate_samples: 0 }
+    }
+}
+
+impl EnvelopeState {
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Restart from the current level (no click on retrigger).
+    pub fn trigger(&mut self, gate_samples: usize) {
+        self.stage = EnvelopeStage::Attack;
+        self.gate_samples = gate_samples;
+    }
+
+    /// Advance by `samples` and return the new level (0.0 to 1.0).
+    pub fn advance(&mut self, env: &ModEnvelope, samples: usize, sample_rate: f32) -> f32 {
+        let dt = samples as f32 / sample_rate;
+        let gate_open = self.gate_samples > 0;
+        self.gate_samples = self.gate_samples.saturating_sub(samples);
+
+        // ADSR releases as soon as the gate closes, whatever the stage
+        let finished = matches!(self.stage, EnvelopeStage::Idle | EnvelopeStage::Release);
+        if env.mode == EnvelopeMode::Adsr && !gate_open && !finished {
+            self.stage = EnvelopeStage::Release;
+            self.release_from = self.level;
+        }
+
+        let sustain = match env.mode {
+            EnvelopeMode::Ad => 0.0,
+            EnvelopeMode::Adsr => env.sustain.clamp(0.0, 1.0),
+        };
+
+        match self.stage {
+            EnvelopeStage::Idle => self.level = 0.0,
+            EnvelopeStage::Attack => {
+                self.level += dt / envelope_seconds(env.attack);
+                if self.level >= 1.0 {
+                    self.level = 1.0;
+                    self.stage = EnvelopeStage::Decay;
+                }
+            }
+            EnvelopeStage::Decay => {
+                self.level -= dt / envelope_seconds(env.decay) * (1.0 - sustain);
+                if self.level <= sustain {
+                    self.level = sustain;
+                    self.stage = match env.mode {
+                        EnvelopeMode::Ad => EnvelopeStage::Idle,
+                        EnvelopeMode::Adsr => EnvelopeStage::Sustain,
+                    };
+                }
+            }
+            EnvelopeStage::Sustain => self.level = sustain,
+            EnvelopeStage::Release => {
+                self.level -= dt / envelope_seconds(env.release) * self.release_from;
+                if self.level <= 0.0 {
+                    self.level = 0.0;
+                    self.stage = EnvelopeStage::Idle;
+                }
+            }
+        }
+        self.level
+    }
+}
+
+/// Per-track source values latched when a trig fires.
+#[derive(Clone, Copy, Debug)]
+pub struct TrigSources {
+    pub velocity: f32, // 0.0 to 1.0
+    pub note: f32,     // -1.0 to 1.0, 0.0 = C4
+    pub random: f32,   // -1.0 to 1.0
+    pub depth_locks: [Option<f32>; MAX_MOD_SLOTS],
+}
+
+impl Default for TrigSources {
+    fn default() -> Self {
+        Self {
+            velocity: 0.0,
+            note: 0.0,
+            random: 0.0,
+            depth_locks: [None; MAX_MOD_SLOTS],
+        }
+    }
+}
+
+impl TrigSources {
+    /// Latch the sources of a trig that plays (Note, OneShot, SynthTrigger).
+    /// `note` is None for Trigless Trigs, which keep the previous note.
+    pub fn latch_trig(&mut self, step: &AtomicStep, note: Option<f32>, random: f32) {
+        self.velocity = step.velocity as f32 / 127.0;
+        if let Some(note) = note {
+            self.note = ((note - 60.0) / 64.0).clamp(-1.0, 1.0);
+        }
+        self.random = random;
+        self.latch_locks(step);
+    }
+
+    /// Latch the step's depth P-Locks. Trigless Locks only do this.
+    pub fn latch_locks(&mut self, step: &AtomicStep) {
+        for (slot, lock) in self.depth_locks.iter_mut().enumerate() {
+            *lock = step.p_locks[PARAM_MOD_DEPTH_BASE + slot];
+        }
+    }
+}
+
+/// Sum a track's modulation into per-parameter offsets:
+/// LFOs and envelopes on their own destination, then every mod matrix slot.
+/// `step_position` is the playhead within the pattern (0.0 to 1.0).
+pub fn evaluate(
+    track: &Track,
+    sources: &TrigSources,
+    envelopes: &[f32; MOD_ENVELOPES_PER_TRACK],
+    bar_position: f64,
+    step_position: f32,
+    offsets: &mut [f32; NUM_SYNTH_PARAMS],
+) {
+    for lfo in &track.lfos {
+        if lfo.amount == 0.0 {
+            continue;
+        }
+        if let Some(param_id) = destination_param(lfo.destination) {
+            offsets[param_id] += lfo_value(lfo, bar_position);
+        }
+    }
+
+    for (env, level) in track.mod_envelopes.iter().zip(envelopes.iter()) {
+        let param_id = env.destination as usize;
+        if env.depth != 0.0 && param_id < NUM_SYNTH_PARAMS {
+            offsets[param_id] += level * env.depth;
+        }
+    }
+
+    for (slot_idx, slot) in track.mod_matrix.iter().take(MAX_MOD_SLOTS).enumerate() {
+        let param_id = slot.destination as usize;
+        let depth = sources.depth_locks[slot_idx].unwrap_or(slot.depth);
+        if param_id >= NUM_SYNTH_PARAMS || depth == 0.0 {
+            continue;
+        }
+
+        let value = match slot.source {
+            ModSource::Lfo(index) => track
+                .lfos
+                .get(index as usize)
+                .map(|lfo| lfo_waveform(lfo, bar_position))
+                .unwrap_or(0.0),
+            ModSource::Envelope(index) => envelopes.get(index as usize).copied().unwrap_or(0.0),
+            ModSource::Velocity => sources.velocity,
+            ModSource::Note => sources.note,
+            ModSource::Random => sources.random,
+            ModSource::StepPosition => step_position,
+        };
+        offsets[param_id] += value * depth;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::domain::{PARAM_DRIVE, PARAM_FILTER_CUTOFF, PARAM_PAN};
+    use crate::shared::models::{MachineType, ModSlot, Subtrack, LFO, LFOShape};
+
+    fn track_with(slots: Vec<ModSlot>) -> Track {
+        Track {
+            id: 0,
+            machine: MachineType::FmTone,
+            subtracks: vec![Subtrack { voice_id: 0, steps: vec![AtomicStep::default(); 16] }],
+            length: 16,
+            scale: 1.0,
+            default_params: crate::shared::models::default_track_params(),
+            lfos: vec![LFO { shape: LFOShape::Square, amount: 0.0, ..LFO::default() }],
+            mod_matrix: slots,
+            mod_envelopes: Default::default(),
+            sample: None,
+            arp_enabled: false,
+        }
+    }
+
+    #[test]
+    fn test_slots_sum_into_destinations() {
+        let track = track_with(vec![
+            ModSlot { source: ModSource::Lfo(0), destination: PARAM_FILTER_CUTOFF as u8, depth: 0.5 },
+            ModSlot { source: ModSource::Velocity, destination: PARAM_FILTER_CUTOFF as u8, depth: 0.25 },
+            ModSlot { source: ModSource::StepPosition, destination: PARAM_PAN as u8, depth: -1.0 },
+            // Out of range destinations and missing LFOs are ignored
+            ModSlot { source: ModSource::Lfo(3), destination: PARAM_DRIVE as u8, depth: 1.0 },
+            ModSlot { source: ModSource::Note, destination: 120, depth: 1.0 },
+        ]);
+        let mut sources = TrigSources::default();
+        let step = AtomicStep { velocity: 127, ..AtomicStep::default() };
+        sources.latch_trig(&step, Some(60.0), 0.0);
+
+        let mut offsets = [0.0; NUM_SYNTH_PARAMS];
+        // Square LFO is high in the first half of the bar
+        evaluate(&track, &sources, &[0.0; MOD_ENVELOPES_PER_TRACK], 0.1, 0.5, &mut offsets);
+
+        assert!((offsets[PARAM_FILTER_CUTOFF] - 0.75).abs() < 1e-6);
+        assert!((offsets[PARAM_PAN] + 0.5).abs() < 1e-6);
+        assert_eq!(offsets[PARAM_DRIVE], 0.0);
+    }
+
+    #[test]
+    fn test_depth_p_lock_overrides_slot_depth() {
+        let track = track_with(vec![
+            ModSlot { source: ModSource::Velocity, destination: PARAM_FILTER_CUTOFF as u8, depth: 0.0 },
+        ]);
+        let mut step = AtomicStep { velocity: 127, ..AtomicStep::default() };
+        step.p_locks[PARAM_MOD_DEPTH_BASE] = Some(-0.5);
+
+        let mut sources = TrigSources::default();
+        sources.latch_trig(&step, Some(60.0), 0.0);
+        let mut offsets = [0.0; NUM_SYNTH_PARAMS];
+        evaluate(&track, &sources, &[0.0; MOD_ENVELOPES_PER_TRACK], 0.0, 0.0, &mut offsets);
+        assert!((offsets[PARAM_FILTER_CUTOFF] + 0.5).abs() < 1e-6);
+
+        // The next trig without a lock falls back to the slot depth
+        sources.latch_locks(&AtomicStep::default());
+        let mut offsets = [0.0; NUM_SYNTH_PARAMS];
+        evaluate(&track, &sources, &[0.0; MOD_ENVELOPES_PER_TRACK], 0.0, 0.0, &mut offsets);
+        assert_eq!(offsets[PARAM_FILTER_CUTOFF], 0.0);
+    }
+
+    #[test]
+    fn test_ad_envelope_ignores_gate() {
+        let env = ModEnvelope { attack: 0.0, decay: 0.0, ..ModEnvelope::default() }; // 1 ms each
+        let mut state = EnvelopeState::default();
+        state.trigger(48_000);
+
+        assert_eq!(state.advance(&env, 48, 48_000.0), 1.0); // Attack done after 1 ms
+        assert_eq!(state.advance(&env, 48, 48_000.0), 0.0); // Decayed despite the open gate
+    }
+
+    #[test]
+    fn test_adsr_envelope_sustains_until_gate_closes() {
+        let env = ModEnvelope {
+            mode: EnvelopeMode::Adsr,
+            attack: 0.0,
+            decay: 0.0,
+            sustain: 0.5,
+            release: 0.0,
+            ..ModEnvelope::default()
+        };
+        let mut state = EnvelopeState::default();
+        state.trigger(480);
+
+        state.advance(&env, 48, 48_000.0);
+        state.advance(&env, 48, 48_000.0);
+        assert_eq!(state.advance(&env, 48, 48_000.0), 0.5);
+        for _ in 0..10 {
+            state.advance(&env, 48, 48_000.0);
+        }
+        assert_eq!(state.level(), 0.0, "Released after the gate");
+    }
+
+    #[test]
+    fn test_envelope_routing_and_matrix_source() {
+        let mut track = track_with(vec![
+            ModSlot { source: ModSource::Envelope(1), destination: PARAM_PAN as u8, depth: 0.5 },
+        ]);
+        track.mod_envelopes[0].destination = PARAM_FILTER_CUTOFF as u8;
+        track.mod_envelopes[0].depth = -1.0;
+
+        let mut offsets = [0.0; NUM_SYNTH_PARAMS];
+        evaluate(&track, &TrigSources::default(), &[0.25, 1.0], 0.0, 0.0, &mut offsets);
+        assert_eq!(offsets[PARAM_FILTER_CUTOFF], -0.25);
+        assert_eq!(offsets[PARAM_PAN], 0.5);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::engine::kernel::FluxKernel;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+pub const NULL_HOST: &str = "Null";
+pub const NULL_DEVICE: &str = "Null Output";
+pub const DEFAULT_SAMPLE_RATE: u32 = 48_000;
+pub const DEFAULT_BUFFER_SIZE: u32 = 512; // Frames
+pub const MIN_BUFFER_SIZE: u32 = 16;
+pub const MAX_BUFFER_SIZE: u32 = 8192;
+pub const CHANNELS: u16 = 2;
+
+/// Headless output: drives the kernel from a timer thread at the configured rate
+/// and discards the audio. Used when there is no sound card (CI, servers).
+pub struct NullStream {
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl NullStream {
+    pub fn start(kernel: Arc<Mutex<FluxKernel>>, sample_rate: u32, buffer_size: u32) -> Result<Self, String> {
+        if sample_rate == 0 {
+            return Err("Sample rate must be greater than zero".to_string());
+        }
+        let buffer_size = buffer_size.clamp(MIN_BUFFER_SIZE, MAX_BUFFER_SIZE);
+
+        kernel
+            .lock()
+            .map_err(|_| "Audio kernel lock poisoned")?
+            .set_sample_rate(sample_rate as f32);
+
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = Arc::clone(&running);
+        let thread = thread::Builder::new()
+            .name("flux-null-audio".to_string())
+            .spawn(move || run(kernel, thread_running, sample_rate, buffer_size))
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self { running, thread: Some(thread) })
+    }
+}
+
+impl Drop for NullStream {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run(kernel: Arc<Mutex<FluxKernel>>, running: Arc<AtomicBool>, sample_rate: u32, buffer_size: u32) {
+    let period = Duration::from_secs_f64(buffer_size as f64 / sample_rate as f64);
+    let mut buffer = vec![0.0; buffer_size as usize * CHANNELS as usize];
+    let mut next_block_time = Instant::now();
+
+    while running.load(Ordering::Acquire) {
+        match kernel.lock() {
+            Ok(mut kernel) => kernel.process(&mut buffer, CHANNELS as usize),
+            Err(_) => break,
+        }
+
+        next_block_time += period;
+        let now = Instant::now();
+        if now < next_block_time {
+            thread::sleep(next_block_time - now);
+        } else if now - next_block_time > period * 10 {
+            // Way behind (e.g. the machine was suspended): don't try to catch up
+            next_block_time = now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::kernel::AudioCommand;
+    use crate::engine::domain::AudioSnapshot;
+    use rtrb::RingBuffer;
+
+    #[test]
+    fn test_null_stream_drives_kernel() {
+        let (mut producer, consumer) = RingBuffer::new(16);
+        let (snapshot_prod, _) = triple_buffer::TripleBuffer::new(&AudioSnapshot::default()).split();
+        let kernel = Arc::new(Mutex::new(FluxKernel::new(44100.0, consumer, snapshot_prod)));
+        producer.push(AudioCommand::Play).unwrap();
+
+        let stream = NullStream::start(Arc::clone(&kernel), 8000, 64).unwrap();
+        assert_eq!(kernel.lock().unwrap().sample_rate, 8000.0);
+        thread::sleep(Duration::from_millis(100));
+        drop(stream);
+
+        let kernel = kernel.lock().unwrap();
+        assert!(kernel.is_playing);
+        assert!(kernel.playhead_sample > 0);
+        assert_eq!(kernel.playhead_sample % 64, 0); // Whole blocks only
+    }
+
+    #[test]
+    fn test_null_stream_rejects_zero_sample_rate() {
+        let (_, consumer) = RingBuffer::<AudioCommand>::new(16);
+        let (snapshot_prod, _) = triple_buffer::TripleBuffer::new(&AudioSnapshot::default()).split();
+        let kernel = Arc::new(Mutex::new(FluxKernel::new(44100.0, consumer, snapshot_prod)));
+        assert!(NullStream::start(kernel, 0, 512).is_err());
+    }
+}
@@ -0,0 +1,251 @@
+use crate::engine::sample_pool::SampleBuffer;
+use rtrb::{Consumer, Producer, RingBuffer};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+pub const MAX_RECORD_SECONDS: usize = 64; // Buffer allocated per recording
+pub const INPUT_QUEUE_SIZE: usize = 8192; // Frames
+pub const MAX_INPUT_LATENCY: usize = 2048; // Frames the kernel lets the input run ahead
+const RECORDING_QUEUE_SIZE: usize = 8;
+
+/// What the recorder captures.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecordSource {
+    Input,        // Audio input device
+    Master,       // Master output, after the master volume
+    Track(usize), // Post-FX signal of a track
+    Send(usize),  // Send bus return: 0 = Reverb, 1 = Delay
+}
+
+impl RecordSource {
+    /// Resampling sources follow the sequencer: they start on a bar and stop on a bar line.
+    pub fn is_resample(&self) -> bool {
+        !matches!(self, RecordSource::Input)
+    }
+
+    /// Name prefix for the recorded sample in the pool.
+    pub fn name_prefix(&self) -> &'static str {
+        match self {
+            RecordSource::Input => "REC",
+            RecordSource::Master | RecordSource::Track(_) | RecordSource::Send(_) => "RSMP",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecordStart {
+    Now,
+    NextBar, // Waits for step 1 of the pattern; starts with the transport when stopped
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordOptions {
+    pub source: RecordSource,
+    pub start: RecordStart,
+    pub bars: Option<u32>, // None records until stopped
+}
+
+/// A recording in progress. The buffer is allocated by the sender so the audio thread never allocates.
+pub struct Recording {
+    pub options: RecordOptions,
+    pub sample_rate: f32,
+    started: bool,
+    remaining: Option<usize>, // Frames left in bar mode, set when recording starts
+    bar_frames: usize,        // One bar at the tempo the recording started with
+    frames: Vec<[f32; 2]>,
+}
+
+impl Recording {
+    pub fn new(options: RecordOptions, sample_rate: f32) -> Self {
+        let capacity = MAX_RECORD_SECONDS * sample_rate.max(1.0) as usize;
+        Self {
+            options,
+            sample_rate,
+            started: false,
+            remaining: None,
+            bar_frames: 0,
+            frames: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Start capturing at the kernel's current rate. `bar_samples` converts the bar count to frames.
+    pub fn start(&mut self, sample_rate: f32, bar_samples: f32) {
+        self.started = true;
+        self.sample_rate = sample_rate;
+        self.bar_frames = bar_samples.round() as usize;
+        self.remaining = self.options.bars.map(|bars| (bars as f32 * bar_samples).round() as usize);
+    }
+
+    /// Capture one frame. Returns true once the recording is complete (bar count or buffer full).
+    pub fn push(&mut self, frame: [f32; 2]) -> bool {
+        if !self.started {
+            return false;
+        }
+        // Never grows past the pre-allocated capacity
+        if self.frames.len() < self.frames.capacity() {
+            self.frames.push(frame);
+        }
+        if let Some(remaining) = &mut self.remaining {
+            *remaining = remaining.saturating_sub(1);
+        }
+        self.remaining == Some(0) || self.frames.len() == self.frames.capacity()
+    }
+
+    /// Stop at the end of the current bar instead of immediately.
+    /// Returns true if the recording is already on a bar line (or never started) and can finish now.
+    pub fn stop_at_bar_end(&mut self) -> bool {
+        if !self.started || self.bar_frames == 0 {
+            return true;
+        }
+        let into_bar = self.frames.len() % self.bar_frames;
+        if into_bar == 0 {
+            return true;
+        }
+        self.remaining = Some(self.bar_frames - into_bar);
+        false
+    }
+
+    pub fn into_sample(self) -> SampleBuffer {
+        SampleBuffer { sample_rate: self.sample_rate, frames: self.frames }
+    }
+}
+
+/// Audio-thread end of the finished recordings queue.
+pub struct RecordingSender {
+    producer: Option<Producer<Box<Recording>>>,
+}
+
+impl RecordingSender {
+    /// A sender without a collector: finished recordings are dropped. Only for offline rendering and tests.
+    pub fn disconnected() -> Self {
+        Self { producer: None }
+    }
+
+    /// Hand a finished recording to the collector thread.
+    /// Returns it if the queue is full or disconnected, so the caller can retire it.
+    pub fn finish(&mut self, recording: Box<Recording>) -> Result<(), Box<Recording>> {
+        match &mut self.producer {
+            Some(producer) => producer.push(recording).map_err(|rtrb::PushError::Full(r)| r),
+            None => Err(recording),
+        }
+    }
+}
+
+pub fn recording_queue() -> (RecordingSender, Consumer<Box<Recording>>) {
+    let (producer, consumer) = RingBuffer::new(RECORDING_QUEUE_SIZE);
+    (RecordingSender { producer: Some(producer) }, consumer)
+}
+
+/// Pass finished recordings to `on_recording`. Exits when the kernel is gone.
+pub fn spawn_recording_collector<F>(mut consumer: Consumer<Box<Recording>>, mut on_recording: F) -> thread::JoinHandle<()>
+where
+    F: FnMut(Box<Recording>) + Send + 'static,
+{
+    thread::spawn(move || loop {
+        while let Ok(recording) = consumer.pop() {
+            on_recording(recording);
+        }
+        if consumer.is_abandoned() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    })
+}
+
+/// Input-stream end of the input queue. Shared so a rebuilt input stream can take it over.
+pub type InputProducer = Arc<Mutex<Producer<[f32; 2]>>>;
+
+pub fn input_queue() -> (InputProducer, Consumer<[f32; 2]>) {
+    let (producer, consumer) = RingBuffer::new(INPUT_QUEUE_SIZE);
+    (Arc::new(Mutex::new(producer)), consumer)
+}
+
+/// Queue interleaved input samples as stereo frames. Mono inputs are duplicated.
+/// Called from the input stream callback; frames that don't fit are dropped.
+pub fn push_input(producer: &mut Producer<[f32; 2]>, data: &[f32], channels: usize) {
+    for frame in data.chunks(channels.max(1)) {
+        let stereo = match frame {
+            [mono] => [*mono, *mono],
+            [left, right, ..] => [*left, *right],
+            [] => continue,
+        };
+        if producer.push(stereo).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(start: RecordStart, bars: Option<u32>) -> RecordOptions {
+        RecordOptions { source: RecordSource::Input, start, bars }
+    }
+
+    #[test]
+    fn test_recording_waits_for_start() {
+        let mut recording = Recording::new(options(RecordStart::NextBar, Some(1)), 100.0);
+        assert!(!recording.push([1.0, 1.0]));
+        assert!(recording.is_empty());
+
+        recording.start(100.0, 4.0);
+        assert!(!recording.push([1.0, 1.0]));
+        assert!(!recording.push([1.0, 1.0]));
+        assert!(!recording.push([1.0, 1.0]));
+        assert!(recording.push([1.0, 1.0])); // One bar = 4 frames
+        assert_eq!(recording.into_sample().frames.len(), 4);
+    }
+
+    #[test]
+    fn test_manual_recording_stops_when_full() {
+        let mut recording = Recording::new(options(RecordStart::Now, None), 1.0);
+        recording.start(1.0, 4.0);
+        for _ in 0..MAX_RECORD_SECONDS - 1 {
+            assert!(!recording.push([0.0, 0.0]));
+        }
+        assert!(recording.push([0.0, 0.0]));
+        assert_eq!(recording.len(), MAX_RECORD_SECONDS);
+    }
+
+    #[test]
+    fn test_resample_stop_waits_for_bar_end() {
+        let mut recording = Recording::new(options(RecordStart::NextBar, None), 100.0);
+        recording.start(100.0, 4.0);
+        for _ in 0..5 {
+            assert!(!recording.push([0.5, 0.5]));
+        }
+        assert!(!recording.stop_at_bar_end());
+        assert!(!recording.push([0.5, 0.5]));
+        assert!(!recording.push([0.5, 0.5]));
+        assert!(recording.push([0.5, 0.5])); // Two full bars
+        assert_eq!(recording.len(), 8);
+        assert!(recording.stop_at_bar_end());
+    }
+
+    #[test]
+    fn test_push_input_converts_to_stereo() {
+        let (producer, mut consumer) = input_queue();
+        let mut producer = producer.lock().unwrap();
+        push_input(&mut producer, &[0.5, 0.25], 1);
+        push_input(&mut producer, &[0.1, 0.2, 0.3, 0.4, 0.5, 0.6], 3);
+        assert_eq!(consumer.pop(), Ok([0.5, 0.5]));
+        assert_eq!(consumer.pop(), Ok([0.25, 0.25]));
+        assert_eq!(consumer.pop(), Ok([0.1, 0.2]));
+        assert_eq!(consumer.pop(), Ok([0.4, 0.5]));
+    }
+}
@@ -0,0 +1,330 @@
+use crate::engine::domain::{AudioSnapshot, MAX_TRACKS};
+use crate::engine::kernel::FluxKernel;
+use crate::engine::sample_pool::SampleTable;
+use crate::shared::models::{MachineType, Pattern, Track};
+use rtrb::RingBuffer;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use triple_buffer::TripleBuffer;
+
+/// Where a track stem is captured in the channel strip.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum StemTap {
+    PreFx,  // Raw voice, before drive/filter/volume/pan
+    PostFx, // What the track contributes to the master bus (dry)
+    Both,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct RenderOptions {
+    pub sample_rate: u32,
+    pub bars: u32,
+    pub tail_seconds: f32, // Extra time after the last bar for releases and FX tails
+    pub stems: bool,
+    pub stem_tap: StemTap,
+    pub include_sends: bool, // Write the reverb/delay returns as extra stems
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            sample_rate: 48000,
+            bars: 4,
+            tail_seconds: 2.0,
+            stems: true,
+            stem_tap: StemTap::PostFx,
+            include_sends: true,
+        }
+    }
+}
+
+/// An interleaved stereo buffer and the file name it is written to.
+pub struct RenderedStem {
+    pub file_name: String,
+    pub samples: Vec<f32>,
+}
+
+pub struct RenderedAudio {
+    pub sample_rate: u32,
+    pub master: Vec<f32>, // Interleaved stereo
+    pub stems: Vec<RenderedStem>,
+}
+
+pub const MASTER_FILE_NAME: &str = "master.wav";
+const SEND_FILE_NAMES: [&str; 2] = ["send_reverb.wav", "send_delay.wav"];
+
+fn machine_slug(machine: MachineType) -> &'static str {
+    match machine {
+        MachineType::OneShot => "oneshot",
+        MachineType::Werp => "werp",
+        MachineType::Slice => "slice",
+        MachineType::FmTone => "fmtone",
+        MachineType::Subtractive => "subtractive",
+        MachineType::TonverkBus => "tonverkbus",
+        MachineType::MidiCC => "midicc",
+    }
+}
+
+/// Stem file name, e.g. `track01_fmtone_post.wav`. Track numbers are 1-based like the UI.
+pub fn stem_file_name(track: &Track, tap: StemTap) -> String {
+    let suffix = match tap {
+        StemTap::PreFx => "pre",
+        StemTap::PostFx | StemTap::Both => "post",
+    };
+    format!("track{:02}_{}_{}.wav", track.id + 1, machine_slug(track.machine), suffix)
+}
+
+/// Render `options.bars` bars of `pattern` offline, without an audio device.
+/// `samples` are the sample pool slots the tracks may play.
+pub fn render_pattern(pattern: &Pattern, samples: &SampleTable, options: &RenderOptions) -> RenderedAudio {
+    let sample_rate = options.sample_rate as f32;
+    let (_command_producer, command_consumer) = RingBuffer::new(1);
+    let (snapshot_producer, _) = TripleBuffer::new(&AudioSnapshot::default()).split();
+    let mut kernel = FluxKernel::new(sample_rate, command_consumer, snapshot_producer);
+    kernel.pattern = pattern.clone();
+    kernel.set_tempo(pattern.bpm);
+    for (slot, sample) in kernel.samples.iter_mut().zip(samples) {
+        *slot = sample.clone();
+    }
+
+    let pattern_frames = (options.bars as f32 * 16.0 * kernel.samples_per_step).ceil() as usize;
+    let total_frames = pattern_frames + (options.tail_seconds.max(0.0) * sample_rate) as usize;
+    let track_count = pattern.tracks.len().min(MAX_TRACKS);
+
+    let taps: &[_] = match (options.stems, options.stem_tap) {
+        (false, _) => &[],
+        (true, StemTap::Both) => &[StemTap::PreFx, StemTap::PostFx],
+        (true, StemTap::PreFx) => &[StemTap::PreFx],
+        (true, StemTap::PostFx) => &[StemTap::PostFx],
+    };
+
+    let buffer = || Vec::with_capacity(total_frames * 2);
+    let mut master = buffer();
+    let mut track_stems: Vec<(usize, StemTap, Vec<f32>)> = (0..track_count)
+        .flat_map(|i| taps.iter().map(move |&tap| (i, tap)))
+        .map(|(i, tap)| (i, tap, buffer()))
+        .collect();
+    let mut send_stems: Vec<Vec<f32>> = if options.include_sends {
+        SEND_FILE_NAMES.iter().map(|_| buffer()).collect()
+    } else {
+        Vec::new()
+    };
+
+    kernel.play();
+    for frame in 0..total_frames {
+        if frame == pattern_frames {
+            // Stop sequencing, let voices and FX ring out
+            kernel.stop();
+        }
+
+        master.extend_from_slice(&kernel.render_frame());
+        for (track_idx, tap, samples) in track_stems.iter_mut() {
+            let signal = match tap {
+                StemTap::PreFx => kernel.taps.track_pre_fx[*track_idx],
+                _ => kernel.taps.track_post_fx[*track_idx],
+            };
+            samples.extend_from_slice(&signal);
+        }
+        for (bus, samples) in send_stems.iter_mut().enumerate() {
+            samples.extend_from_slice(&kernel.taps.sends[bus]);
+        }
+    }
+
+    let mut stems: Vec<RenderedStem> = track_stems
+        .into_iter()
+        .map(|(track_idx, tap, samples)| RenderedStem {
+            file_name: stem_file_name(&pattern.tracks[track_idx], tap),
+            samples,
+        })
+        .collect();
+    stems.extend(send_stems.into_iter().zip(SEND_FILE_NAMES).map(|(samples, name)| RenderedStem {
+        file_name: name.to_string(),
+        samples,
+    }));
+
+    RenderedAudio { sample_rate: options.sample_rate, master, stems }
+}
+
+/// Render `pattern` and write the master mixdown plus stems into `directory`.
+/// Returns the paths of all written files.
+pub fn export_pattern(
+    pattern: &Pattern,
+    samples: &SampleTable,
+    directory: &Path,
+    options: &RenderOptions,
+) -> Result<Vec<PathBuf>, String> {
+    std::fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+    let audio = render_pattern(pattern, samples, options);
+
+    let mut written = Vec::with_capacity(audio.stems.len() + 1);
+    let master_path = directory.join(MASTER_FILE_NAME);
+    write_wav(&master_path, audio.sample_rate, &audio.master).map_err(|e| e.to_string())?;
+    written.push(master_path);
+
+    for stem in &audio.stems {
+        let path = directory.join(&stem.file_name);
+        write_wav(&path, audio.sample_rate, &stem.samples).map_err(|e| e.to_string())?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Encode interleaved stereo samples as a 32-bit float WAV file.
+pub fn wav_bytes(sample_rate: u32, samples: &[f32]) -> Vec<u8> {
+    const CHANNELS: u16 = 2;
+    const BITS_PER_SAMPLE: u16 = 32;
+    const FORMAT_IEEE_FLOAT: u16 = 3;
+
+    let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+    let data_len = (samples.len() * 4) as u32;
+
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&FORMAT_IEEE_FLOAT.to_le_bytes());
+    bytes.extend_from_slice(&CHANNELS.to_le_bytes());
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    bytes.extend_from_slice(&block_align.to_le_bytes());
+    bytes.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
+
+pub fn write_wav(path: &Path, sample_rate: u32, samples: &[f32]) -> std::io::Result<()> {
+    std::fs::write(path, wav_bytes(sample_rate, samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::models::{default_track_params, AtomicStep, Subtrack, TrigType};
+
+    fn make_track(id: usize, machine: MachineType, active_steps: &[usize]) -> Track {
+        let mut steps = vec![AtomicStep::default(); 16];
+        for &i in active_steps {
+            steps[i].trig_type = TrigType::Note;
+        }
+        Track {
+            id,
+            machine,
+            subtracks: vec![Subtrack { voice_id: 0, steps }],
+            length: 16,
+            scale: 1.0,
+            default_params: default_track_params(),
+            lfos: Vec::new(),
+            mod_matrix: Vec::new(),
+            mod_envelopes: Default::default(),
+            sample: None,
+            arp_enabled: false,
+        }
+    }
+
+    fn short_options() -> RenderOptions {
+        RenderOptions {
+            sample_rate: 8000,
+            bars: 1,
+            tail_seconds: 0.5,
+            ..RenderOptions::default()
+        }
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0, |acc, s| acc.max(s.abs()))
+    }
+
+    #[test]
+    fn test_stem_file_name() {
+        let track = make_track(0, MachineType::FmTone, &[]);
+        assert_eq!(stem_file_name(&track, StemTap::PostFx), "track01_fmtone_post.wav");
+        assert_eq!(stem_file_name(&track, StemTap::PreFx), "track01_fmtone_pre.wav");
+    }
+
+    #[test]
+    fn test_renders_one_stem_per_track_plus_sends() {
+        let mut pattern = Pattern::default();
+        pattern.tracks.push(make_track(0, MachineType::OneShot, &[0, 8]));
+        pattern.tracks.push(make_track(1, MachineType::Subtractive, &[]));
+
+        let audio = render_pattern(&pattern, &[], &short_options());
+        let names: Vec<_> = audio.stems.iter().map(|s| s.file_name.as_str()).collect();
+        assert_eq!(names, [
+            "track01_oneshot_post.wav",
+            "track02_subtractive_post.wav",
+            "send_reverb.wav",
+            "send_delay.wav",
+        ]);
+
+        for stem in &audio.stems {
+            assert_eq!(stem.samples.len(), audio.master.len());
+        }
+        assert!(peak(&audio.stems[0].samples) > 0.01, "Active track should be audible");
+        assert_eq!(peak(&audio.stems[1].samples), 0.0, "Empty track should be silent");
+    }
+
+    #[test]
+    fn test_pre_and_post_fx_taps() {
+        let mut pattern = Pattern::default();
+        let mut track = make_track(0, MachineType::FmTone, &[0]);
+        track.default_params[crate::engine::domain::PARAM_VOLUME] = 0.0;
+        pattern.tracks.push(track);
+
+        let options = RenderOptions { stem_tap: StemTap::Both, include_sends: false, ..short_options() };
+        let audio = render_pattern(&pattern, &[], &options);
+        assert_eq!(audio.stems.len(), 2);
+        assert_eq!(audio.stems[0].file_name, "track01_fmtone_pre.wav");
+        // Pre-FX ignores the fader, post-FX does not
+        assert!(peak(&audio.stems[0].samples) > 0.01);
+        assert_eq!(peak(&audio.stems[1].samples), 0.0);
+    }
+
+    #[test]
+    fn test_master_is_sum_of_post_fx_stems_and_sends() {
+        let mut pattern = Pattern::default();
+        let mut track = make_track(0, MachineType::OneShot, &[0, 4]);
+        track.default_params[crate::engine::domain::PARAM_REVERB_SEND] = 0.5;
+        track.default_params[crate::engine::domain::PARAM_DELAY_SEND] = 0.5;
+        pattern.tracks.push(track);
+        pattern.tracks.push(make_track(1, MachineType::Werp, &[2, 6]));
+
+        let audio = render_pattern(&pattern, &[], &short_options());
+        for (i, master) in audio.master.iter().enumerate() {
+            let sum: f32 = audio.stems.iter().map(|s| s.samples[i]).sum();
+            assert!((master - sum).abs() < 1e-5, "Frame {}: master {} != stems {}", i, master, sum);
+        }
+    }
+
+    #[test]
+    fn test_wav_header() {
+        let bytes = wav_bytes(48000, &[0.0, 0.5, -0.5, 1.0]);
+        assert_eq!(bytes.len(), 44 + 16);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(&bytes[8..12], b"WAVE");
+        assert_eq!(u16::from_le_bytes([bytes[20], bytes[21]]), 3); // IEEE float
+        assert_eq!(u16::from_le_bytes([bytes[22], bytes[23]]), 2); // Stereo
+        assert_eq!(u32::from_le_bytes([bytes[24], bytes[25], bytes[26], bytes[27]]), 48000);
+        assert_eq!(u32::from_le_bytes([bytes[40], bytes[41], bytes[42], bytes[43]]), 16);
+    }
+
+    #[test]
+    fn test_export_writes_master_and_stems() {
+        let dir = std::env::temp_dir().join(format!("flux-export-test-{}", std::process::id()));
+        let mut pattern = Pattern::default();
+        pattern.tracks.push(make_track(0, MachineType::Slice, &[0]));
+
+        let files = export_pattern(&pattern, &[], &dir, &short_options()).unwrap();
+        let names: Vec<_> = files.iter().map(|p| p.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(names, [MASTER_FILE_NAME, "track01_slice_post.wav", "send_reverb.wav", "send_delay.wav"]);
+        assert!(files.iter().all(|p| p.exists()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
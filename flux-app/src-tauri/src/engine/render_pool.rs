@@ -0,0 +1,257 @@
+use crate::engine::sample_pool::{SampleBuffer, SampleTable};
+use crate::engine::voice::{ChannelFrame, TrackChannel};
+use std::hint::spin_loop;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle, Thread};
+use thread_priority::{set_current_thread_priority, ThreadPriority};
+
+pub const MIN_PARALLEL_FRAMES: usize = 32; // Shorter sub-blocks render on the audio thread
+pub const MAX_RENDER_WORKERS: usize = 7;
+const IDLE_SPINS: usize = 2000; // Before a worker parks
+
+/// Worker threads for the audio thread to spread on: one core per worker, minus the audio thread.
+pub fn default_worker_count() -> usize {
+    thread::available_parallelism()
+        .map(|cores| cores.get().saturating_sub(1))
+        .unwrap_or(0)
+        .min(MAX_RENDER_WORKERS)
+}
+
+// The block being rendered. Published before the claim word, so workers that load the claim
+// word first and see the new generation also see its job.
+#[derive(Default)]
+struct Job {
+    channels: AtomicPtr<TrackChannel>,
+    outputs: AtomicPtr<Vec<ChannelFrame>>,
+    samples: AtomicPtr<Option<Arc<SampleBuffer>>>,
+    samples_len: AtomicUsize,
+    tracks: AtomicUsize,
+    frames: AtomicUsize,
+}
+
+#[derive(Default)]
+struct Shared {
+    job: Job,
+    claim: AtomicU64, // Generation in the high 32 bits, next track in the low 32
+    done: AtomicUsize, // Tracks rendered in the current generation
+    shutdown: AtomicBool,
+}
+
+const TRACK_MASK: u64 = 0xFFFF_FFFF;
+
+impl Shared {
+    // Claim and render tracks of `generation` until none are left or a newer job is published
+    fn render_tracks(&self, generation: u64) {
+        // The claim word comes first: once it shows `generation`, the job fields are that job's.
+        // A newer job can only be published after every track of this one is claimed, so the
+        // generation-checked compare-exchange below fails if the fields were overwritten meanwhile.
+        let mut current = self.claim.load(Ordering::Acquire);
+        if current >> 32 != generation {
+            return;
+        }
+        let channels = self.job.channels.load(Ordering::Acquire);
+        let outputs = self.job.outputs.load(Ordering::Acquire);
+        let samples = self.job.samples.load(Ordering::Acquire);
+        let samples_len = self.job.samples_len.load(Ordering::Acquire);
+        let tracks = self.job.tracks.load(Ordering::Acquire);
+        let frames = self.job.frames.load(Ordering::Acquire);
+
+        loop {
+            let track = (current & TRACK_MASK) as usize;
+            if current >> 32 != generation || track >= tracks {
+                return;
+            }
+            match self.claim.compare_exchange_weak(current, current + 1, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => {
+                    // SAFETY: a successful claim in this generation means `render` is still waiting for
+                    // this track, so the pointers are live and nobody else touches this channel or output.
+                    unsafe {
+                        let samples = std::slice::from_raw_parts(samples, samples_len);
+                        let output = &mut *outputs.add(track);
+                        (*channels.add(track)).render_block(samples, &mut output[..frames]);
+                    }
+                    self.done.fetch_add(1, Ordering::Release);
+                    current = self.claim.load(Ordering::Acquire);
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+/// Pre-spawned real-time threads that render track channels in parallel with the audio thread.
+/// Tracks are claimed from a lock-free counter; each renders into its own buffer,
+/// so the mix order (and the output) does not depend on which thread rendered what.
+pub struct RenderPool {
+    shared: Arc<Shared>,
+    workers: Vec<(Thread, Option<JoinHandle<()>>)>,
+    generation: u64,
+}
+
+impl RenderPool {
+    /// Spawns `workers` threads. Allocates, so create it before the stream starts.
+    pub fn new(workers: usize) -> Self {
+        let shared = Arc::new(Shared::default());
+        let workers = (0..workers)
+            .map(|i| {
+                let shared = Arc::clone(&shared);
+                let handle = thread::Builder::new()
+                    .name(format!("flux-render-{}", i))
+                    .spawn(move || worker_loop(&shared))
+                    .expect("Failed to spawn render worker");
+                (handle.thread().clone(), Some(handle))
+            })
+            .collect();
+        Self { shared, workers, generation: 0 }
+    }
+
+    pub fn worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Render `frames` frames of every channel into the matching output, in parallel.
+    /// Returns once all tracks are done. The audio thread renders tracks too while it waits.
+    pub fn render(
+        &mut self,
+        channels: &mut [TrackChannel],
+        outputs: &mut [Vec<ChannelFrame>],
+        samples: &SampleTable,
+        frames: usize,
+    ) {
+        let tracks = channels.len().min(outputs.len());
+        debug_assert!(outputs[..tracks].iter().all(|o| o.len() >= frames));
+
+        let job = &self.shared.job;
+        job.channels.store(channels.as_mut_ptr(), Ordering::Release);
+        job.outputs.store(outputs.as_mut_ptr(), Ordering::Release);
+        job.samples.store(samples.as_ptr() as *mut _, Ordering::Release);
+        job.samples_len.store(samples.len(), Ordering::Release);
+        job.tracks.store(tracks, Ordering::Release);
+        job.frames.store(frames, Ordering::Release);
+        self.shared.done.store(0, Ordering::Release);
+
+        self.generation = (self.generation + 1) & TRACK_MASK;
+        self.shared.claim.store(self.generation << 32, Ordering::Release);
+        for (worker, _) in &self.workers {
+            worker.unpark();
+        }
+
+        self.shared.render_tracks(self.generation);
+        while self.shared.done.load(Ordering::Acquire) < tracks {
+            spin_loop();
+        }
+    }
+}
+
+impl Drop for RenderPool {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::Release);
+        for (worker, handle) in &mut self.workers {
+            worker.unpark();
+            if let Some(handle) = handle.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+fn worker_loop(shared: &Shared) {
+    // Best effort: without it the workers still help, just with more jitter
+    let _ = set_current_thread_priority(ThreadPriority::Max);
+
+    let mut seen = 0;
+    let mut idle = 0;
+    while !shared.shutdown.load(Ordering::Acquire) {
+        let generation = shared.claim.load(Ordering::Acquire) >> 32;
+        if generation != seen {
+            seen = generation;
+            idle = 0;
+            shared.render_tracks(generation);
+        } else if idle < IDLE_SPINS {
+            idle += 1;
+            spin_loop();
+        } else {
+            thread::park();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playing_channels() -> Vec<TrackChannel> {
+        (0..16)
+            .map(|i| {
+                let mut channel = TrackChannel::new(48000.0);
+                channel.layer_mut(0).voice.note_on(110.0 * (i + 1) as f32, 100);
+                channel
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parallel_render_matches_single_thread() {
+        let samples: Vec<Option<Arc<SampleBuffer>>> = vec![None; 4];
+
+        let mut serial = playing_channels();
+        let mut expected = vec![vec![ChannelFrame::default(); 256]; serial.len()];
+        for (channel, output) in serial.iter_mut().zip(expected.iter_mut()) {
+            channel.render_block(&samples, output);
+        }
+
+        // Several generations in a row
+        let mut pool = RenderPool::new(3);
+        let mut parallel = playing_channels();
+        let mut outputs = vec![vec![ChannelFrame::default(); 64]; parallel.len()];
+        for block in 0..4 {
+            pool.render(&mut parallel, &mut outputs, &samples, 64);
+            for (output, expected) in outputs.iter().zip(&expected) {
+                for (frame, expected) in output.iter().zip(&expected[block * 64..]) {
+                    assert_eq!(frame.post_fx, expected.post_fx);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parallel_render_follows_sub_block_lengths() {
+        let samples: Vec<Option<Arc<SampleBuffer>>> = vec![None; 4];
+        // Sub-block lengths as split by step, control and gate events
+        let lengths = [64, 37, 100, 33, 51, 64, 90, 41];
+
+        let mut serial = playing_channels();
+        let mut pool = RenderPool::new(3);
+        let mut parallel = playing_channels();
+        let mut expected = vec![vec![ChannelFrame::default(); 128]; serial.len()];
+        let mut outputs = vec![vec![ChannelFrame::default(); 128]; parallel.len()];
+        for _ in 0..50 {
+            // Retrigger so the voices don't decay to silence
+            for (i, channel) in serial.iter_mut().chain(parallel.iter_mut()).enumerate() {
+                channel.layer_mut(0).voice.note_on(110.0 * (i % 16 + 1) as f32, 100);
+            }
+            for frames in lengths {
+                for (channel, output) in serial.iter_mut().zip(expected.iter_mut()) {
+                    channel.render_block(&samples, &mut output[..frames]);
+                }
+                pool.render(&mut parallel, &mut outputs, &samples, frames);
+                for (output, expected) in outputs.iter().zip(&expected) {
+                    for (frame, expected) in output[..frames].iter().zip(&expected[..frames]) {
+                        assert_eq!(frame.post_fx, expected.post_fx);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pool_without_workers_renders_inline() {
+        let samples: Vec<Option<Arc<SampleBuffer>>> = Vec::new();
+        let mut pool = RenderPool::new(0);
+        let mut channels = playing_channels();
+        let mut outputs = vec![vec![ChannelFrame::default(); 64]; channels.len()];
+        pool.render(&mut channels, &mut outputs, &samples, 64);
+        assert!(outputs.iter().all(|o| o[63].pre_fx[0] != 0.0));
+    }
+}
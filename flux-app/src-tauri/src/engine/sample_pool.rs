@@ -0,0 +1,156 @@
+use crate::engine::domain::MAX_SAMPLES;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Stereo audio held in the sample pool. Shared with the audio thread, never mutated.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SampleBuffer {
+    pub sample_rate: f32,
+    pub frames: Vec<[f32; 2]>,
+}
+
+impl SampleBuffer {
+    /// Linearly interpolated frame at a fractional position. Silence past the end.
+    pub fn frame_at(&self, position: f64) -> [f32; 2] {
+        let index = position as usize;
+        let Some(a) = self.frames.get(index) else {
+            return [0.0; 2];
+        };
+        let b = self.frames.get(index + 1).unwrap_or(a);
+        let t = (position - index as f64) as f32;
+        [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+    }
+
+    pub fn duration_secs(&self) -> f32 {
+        self.frames.len() as f32 / self.sample_rate
+    }
+}
+
+/// Sample slots as seen by the audio thread, indexed by pool ID.
+pub type SampleTable = [Option<Arc<SampleBuffer>>];
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SampleInfo {
+    pub id: u16,
+    pub name: String,
+    pub frames: usize,
+    pub sample_rate: u32,
+    pub duration_secs: f32,
+}
+
+struct PoolEntry {
+    name: String,
+    buffer: Arc<SampleBuffer>,
+}
+
+/// The project's samples (Digitakt style pool). Tracks reference them by slot ID.
+/// Lives on the main side; loaded slots are mirrored to the kernel's `SampleTable`.
+pub struct SamplePool {
+    slots: Vec<Option<PoolEntry>>,
+}
+
+impl Default for SamplePool {
+    fn default() -> Self {
+        Self { slots: (0..MAX_SAMPLES).map(|_| None).collect() }
+    }
+}
+
+impl SamplePool {
+    /// Store a sample in the first free slot.
+    pub fn insert(&mut self, name: String, buffer: Arc<SampleBuffer>) -> Result<u16, String> {
+        let id = self
+            .slots
+            .iter()
+            .position(Option::is_none)
+            .ok_or_else(|| format!("Sample pool is full ({} slots)", MAX_SAMPLES))?;
+        self.slots[id] = Some(PoolEntry { name, buffer });
+        Ok(id as u16)
+    }
+
+    pub fn remove(&mut self, id: u16) -> Result<(), String> {
+        self.slots
+            .get_mut(id as usize)
+            .and_then(Option::take)
+            .map(|_| ())
+            .ok_or_else(|| format!("Sample slot {} is empty", id))
+    }
+
+    /// Name for the next recording, e.g. "REC 003": one past the highest number in use,
+    /// so names stay unique after deletions.
+    pub fn next_name(&self, prefix: &str) -> String {
+        let highest = self
+            .slots
+            .iter()
+            .flatten()
+            .filter_map(|e| e.name.strip_prefix(prefix)?.strip_prefix(' ')?.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0);
+        format!("{} {:03}", prefix, highest + 1)
+    }
+
+    pub fn info(&self, id: u16) -> Option<SampleInfo> {
+        let entry = self.slots.get(id as usize)?.as_ref()?;
+        Some(SampleInfo {
+            id,
+            name: entry.name.clone(),
+            frames: entry.buffer.frames.len(),
+            sample_rate: entry.buffer.sample_rate as u32,
+            duration_secs: entry.buffer.duration_secs(),
+        })
+    }
+
+    /// All slots, for the offline renderer.
+    pub fn table(&self) -> Vec<Option<Arc<SampleBuffer>>> {
+        self.slots.iter().map(|slot| slot.as_ref().map(|e| Arc::clone(&e.buffer))).collect()
+    }
+
+    pub fn list(&self) -> Vec<SampleInfo> {
+        (0..self.slots.len() as u16).filter_map(|id| self.info(id)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(frames: usize) -> Arc<SampleBuffer> {
+        Arc::new(SampleBuffer { sample_rate: 48000.0, frames: vec![[0.5, -0.5]; frames] })
+    }
+
+    #[test]
+    fn test_pool_reuses_free_slots() {
+        let mut pool = SamplePool::default();
+        assert_eq!(pool.insert("A".to_string(), buffer(10)), Ok(0));
+        assert_eq!(pool.insert("B".to_string(), buffer(10)), Ok(1));
+        pool.remove(0).unwrap();
+        assert!(pool.remove(0).is_err());
+        assert_eq!(pool.insert("C".to_string(), buffer(48000)), Ok(0));
+
+        let list = pool.list();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].name, "C");
+        assert!((list[0].duration_secs - 1.0).abs() < 1e-6);
+        assert_eq!(pool.next_name("REC"), "REC 001");
+    }
+
+    #[test]
+    fn test_recording_names_stay_unique_after_deletes() {
+        let mut pool = SamplePool::default();
+        for _ in 0..2 {
+            let name = pool.next_name("REC");
+            pool.insert(name, buffer(10)).unwrap();
+        }
+        pool.remove(0).unwrap(); // "REC 001"
+        assert_eq!(pool.next_name("REC"), "REC 003");
+        pool.insert("RECORDING".to_string(), buffer(10)).unwrap();
+        assert_eq!(pool.next_name("REC"), "REC 003");
+    }
+
+    #[test]
+    fn test_frame_interpolation() {
+        let buffer = SampleBuffer { sample_rate: 48000.0, frames: vec![[0.0, 1.0], [1.0, 0.0]] };
+        assert_eq!(buffer.frame_at(0.5), [0.5, 0.5]);
+        assert_eq!(buffer.frame_at(1.0), [1.0, 0.0]);
+        assert_eq!(buffer.frame_at(2.0), [0.0, 0.0]);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::engine::domain::MAX_TRACKS;
+use crate::shared::models::MAX_SUBTRACKS;
+
+/// Something the kernel does at an exact frame of a block.
+/// At the same frame, events run in declaration order: gates close before a new trig opens them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BlockEvent {
+    GateOff(usize, usize), // Track, subtrack voice layer
+    Step,           // Step boundary: pending swaps, then the step's trigs
+    Arp(usize),     // Track's next arpeggiator note
+    Control,        // Modulation update
+}
+
+// One pending event per source: step clock, control clock, each voice layer's gate and each track's arpeggiator
+pub const MAX_BLOCK_EVENTS: usize = (MAX_SUBTRACKS + 1) * MAX_TRACKS + 2;
+
+/// Events within the current block with their frame offsets.
+/// Fixed capacity: scheduling an event replaces the pending one of the same source.
+pub struct EventQueue {
+    events: [(usize, BlockEvent); MAX_BLOCK_EVENTS], // Sorted latest first, so the next event is last
+    len: usize,
+}
+
+impl Default for EventQueue {
+    fn default() -> Self {
+        Self { events: [(0, BlockEvent::Step); MAX_BLOCK_EVENTS], len: 0 }
+    }
+}
+
+impl EventQueue {
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn schedule(&mut self, offset: usize, event: BlockEvent) {
+        self.cancel(event);
+        if self.len == MAX_BLOCK_EVENTS {
+            return; // Unreachable: every source has at most one entry
+        }
+        let index = self.events[..self.len]
+            .iter()
+            .position(|&queued| queued < (offset, event))
+            .unwrap_or(self.len);
+        self.events.copy_within(index..self.len, index + 1);
+        self.events[index] = (offset, event);
+        self.len += 1;
+    }
+
+    pub fn cancel(&mut self, event: BlockEvent) {
+        if let Some(index) = self.events[..self.len].iter().position(|&(_, queued)| queued == event) {
+            self.events.copy_within(index + 1..self.len, index);
+            self.len -= 1;
+        }
+    }
+
+    /// Frame offset of the next event.
+    pub fn next_offset(&self) -> Option<usize> {
+        self.len.checked_sub(1).map(|last| self.events[last].0)
+    }
+
+    /// Remove and return the next event if it is due at `offset` (or overdue).
+    pub fn pop_due(&mut self, offset: usize) -> Option<BlockEvent> {
+        match self.next_offset() {
+            Some(next) if next <= offset => {
+                self.len -= 1;
+                Some(self.events[self.len].1)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_pop_in_frame_then_kind_order() {
+        let mut queue = EventQueue::default();
+        queue.schedule(64, BlockEvent::Control);
+        queue.schedule(10, BlockEvent::Step);
+        queue.schedule(10, BlockEvent::GateOff(3, 0));
+        queue.schedule(0, BlockEvent::GateOff(1, 0));
+
+        assert_eq!(queue.next_offset(), Some(0));
+        assert_eq!(queue.pop_due(0), Some(BlockEvent::GateOff(1, 0)));
+        assert_eq!(queue.pop_due(0), None);
+        assert_eq!(queue.pop_due(10), Some(BlockEvent::GateOff(3, 0)));
+        assert_eq!(queue.pop_due(10), Some(BlockEvent::Step));
+        assert_eq!(queue.next_offset(), Some(64));
+        assert_eq!(queue.pop_due(64), Some(BlockEvent::Control));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_rescheduling_replaces_pending_event() {
+        let mut queue = EventQueue::default();
+        queue.schedule(100, BlockEvent::GateOff(2, 0));
+        queue.schedule(20, BlockEvent::GateOff(2, 0)); // Retriggered with a shorter gate
+        assert_eq!(queue.pop_due(20), Some(BlockEvent::GateOff(2, 0)));
+        assert!(queue.is_empty());
+
+        // Fills up without overflowing
+        for track in 0..MAX_TRACKS {
+            for layer in 0..MAX_SUBTRACKS {
+                queue.schedule(track, BlockEvent::GateOff(track, layer));
+                queue.schedule(track, BlockEvent::GateOff(track, layer));
+            }
+        }
+        queue.schedule(5, BlockEvent::Step);
+        queue.schedule(7, BlockEvent::Control);
+        queue.cancel(BlockEvent::Step);
+        let expected = MAX_TRACKS * MAX_SUBTRACKS + 1;
+        let popped: usize = (0..=MAX_TRACKS).map(|offset| std::iter::from_fn(|| queue.pop_due(offset)).count()).sum();
+        assert_eq!(popped, expected);
+    }
+}
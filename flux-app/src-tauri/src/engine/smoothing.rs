@@ -0,0 +1,104 @@
+use crate::engine::domain::{
+    PARAM_COARSE_TUNE, PARAM_DECAY, PARAM_DELAY_SEND, PARAM_DRIVE, PARAM_FILTER_CUTOFF, PARAM_PAN, PARAM_PITCH,
+    PARAM_RESONANCE, PARAM_REVERB_SEND, PARAM_SUSTAIN, PARAM_TUNING, PARAM_VOLUME,
+};
+
+// Parameters without a ramp of their own (unassigned synth IDs): just long enough not to click
+const DEFAULT_RAMP_SECONDS: f32 = 0.01;
+
+/// Ramp time of a parameter when its base value (track default or P-Lock) changes.
+/// `None` for stepped parameters, which apply instantly.
+pub fn ramp_seconds(param_id: usize) -> Option<f32> {
+    match param_id {
+        PARAM_PITCH => None, // Only glides on slide trigs
+        PARAM_COARSE_TUNE => None, // Whole semitones, read at the trig
+        PARAM_FILTER_CUTOFF | PARAM_RESONANCE | PARAM_TUNING => Some(0.01),
+        PARAM_DECAY | PARAM_SUSTAIN => Some(0.005),
+        PARAM_VOLUME | PARAM_PAN | PARAM_DRIVE | PARAM_REVERB_SEND | PARAM_DELAY_SEND => Some(0.02),
+        _ => Some(DEFAULT_RAMP_SECONDS),
+    }
+}
+
+/// A value that moves to its target in a linear ramp, one increment per sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SmoothedValue {
+    current: f32,
+    target: f32,
+    increment: f32,
+    remaining: usize, // Samples left in the ramp
+}
+
+impl SmoothedValue {
+    pub fn new(value: f32) -> Self {
+        Self { current: value, target: value, increment: 0.0, remaining: 0 }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    pub fn is_ramping(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Jump to `value`, cancelling any ramp.
+    pub fn jump(&mut self, value: f32) {
+        *self = Self::new(value);
+    }
+
+    /// Ramp from the current value to `target` over `samples`; 0 jumps.
+    pub fn ramp_to(&mut self, target: f32, samples: usize) {
+        if samples == 0 || target == self.current {
+            self.jump(target);
+            return;
+        }
+        self.target = target;
+        self.increment = (target - self.current) / samples as f32;
+        self.remaining = samples;
+    }
+
+    /// Advance one sample and return the new value.
+    pub fn tick(&mut self) -> f32 {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            self.current = if self.remaining == 0 { self.target } else { self.current + self.increment };
+        }
+        self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ramp_reaches_target_exactly() {
+        let mut value = SmoothedValue::new(0.0);
+        value.ramp_to(1.0, 4);
+        assert!(value.is_ramping());
+        let ramp: Vec<f32> = (0..5).map(|_| value.tick()).collect();
+        assert_eq!(ramp, [0.25, 0.5, 0.75, 1.0, 1.0]);
+        assert!(!value.is_ramping());
+
+        // Retargeting mid-ramp starts from where it is
+        value.ramp_to(0.0, 2);
+        value.tick();
+        value.ramp_to(1.0, 1);
+        assert_eq!(value.tick(), 1.0);
+    }
+
+    #[test]
+    fn test_stepped_parameters_jump() {
+        assert_eq!(ramp_seconds(PARAM_PITCH), None);
+        assert!(ramp_seconds(PARAM_VOLUME).is_some());
+
+        let mut value = SmoothedValue::new(0.2);
+        value.ramp_to(0.9, 0);
+        assert_eq!(value.value(), 0.9);
+        assert!(!value.is_ramping());
+    }
+}
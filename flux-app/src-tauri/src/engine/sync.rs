@@ -0,0 +1,62 @@
+use crate::engine::recorder::Recording;
+use crate::engine::sample_pool::SampleBuffer;
+use crate::shared::models::{Pattern, Song, Track};
+use rtrb::{Consumer, Producer, PushError, RingBuffer};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+pub const GARBAGE_QUEUE_SIZE: usize = 256;
+
+/// Heap data the audio thread has swapped out.
+/// It is dropped on the garbage thread so the callback never frees memory.
+pub enum Garbage {
+    Pattern(Box<Pattern>),
+    Track(Box<Track>),
+    Song(Box<Song>),
+    Sample(Arc<SampleBuffer>), // Replaced or deleted pool slot
+    Recording(Box<Recording>), // Cancelled, or finished while the recordings queue was full
+}
+
+/// Audio-thread end of the garbage queue.
+pub struct GarbageSender {
+    producer: Option<Producer<Garbage>>,
+}
+
+impl GarbageSender {
+    /// A sender without a garbage thread: retired data is dropped in place.
+    /// Only for offline rendering and tests, where there is no real-time deadline.
+    pub fn disconnected() -> Self {
+        Self { producer: None }
+    }
+
+    pub fn retire(&mut self, garbage: Garbage) {
+        match &mut self.producer {
+            Some(producer) => {
+                if let Err(PushError::Full(garbage)) = producer.push(garbage) {
+                    // Leaking is preferable to freeing on the audio thread
+                    std::mem::forget(garbage);
+                }
+            }
+            None => drop(garbage),
+        }
+    }
+}
+
+pub fn garbage_queue() -> (GarbageSender, Consumer<Garbage>) {
+    let (producer, consumer) = RingBuffer::new(GARBAGE_QUEUE_SIZE);
+    (GarbageSender { producer: Some(producer) }, consumer)
+}
+
+/// Drop everything the audio thread retires. Exits when the kernel is gone.
+pub fn spawn_garbage_collector(mut consumer: Consumer<Garbage>) -> thread::JoinHandle<()> {
+    thread::spawn(move || loop {
+        while let Ok(garbage) = consumer.pop() {
+            drop(garbage);
+        }
+        if consumer.is_abandoned() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    })
+}
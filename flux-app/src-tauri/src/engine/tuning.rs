@@ -0,0 +1,274 @@
+use crate::engine::voice::midi_to_freq;
+use crate::shared::models::{KeyboardMapping, Tuning};
+
+pub const PITCH_BEND_CENTER: u16 = 8192;
+const MTS_NO_CHANGE: [u8; 3] = [0x7F, 0x7F, 0x7F];
+
+/// Fine tune in cents from the normalized Tuning parameter (±100 cents).
+pub fn fine_tune_cents(value: f32) -> f32 {
+    (value.clamp(0.0, 1.0) - 0.5) * 200.0
+}
+
+/// Coarse tune in whole semitones from the normalized Coarse Tune parameter (±24).
+pub fn coarse_tune_semitones(value: f32) -> f32 {
+    ((value.clamp(0.0, 1.0) - 0.5) * 48.0).round()
+}
+
+// First token of a line, Scala ignores anything after it
+fn first_token(line: &str) -> &str {
+    line.split_whitespace().next().unwrap_or("")
+}
+
+// A scale degree: cents if it has a '.', otherwise a ratio like "3/2" or "2"
+fn parse_degree(line: &str) -> Result<f64, String> {
+    let token = first_token(line);
+    if token.contains('.') {
+        return token.parse().map_err(|_| format!("Invalid cents value '{}'", token));
+    }
+    let (num, den) = token.split_once('/').unwrap_or((token, "1"));
+    let ratio = match (num.parse::<f64>(), den.parse::<f64>()) {
+        (Ok(num), Ok(den)) if num > 0.0 && den > 0.0 => num / den,
+        _ => return Err(format!("Invalid ratio '{}'", token)),
+    };
+    Ok(1200.0 * ratio.log2())
+}
+
+/// Parse a Scala scale file (.scl) into its description and degrees in cents.
+pub fn parse_scl(text: &str) -> Result<(String, Vec<f64>), String> {
+    let mut lines = text.lines().filter(|line| !line.starts_with('!'));
+    let name = lines.next().ok_or("Empty scale file")?.trim().to_string();
+    let count_line = lines.next().ok_or("Scale file has no degree count")?;
+    let count: usize = first_token(count_line)
+        .parse()
+        .map_err(|_| format!("Invalid degree count '{}'", count_line.trim()))?;
+    if count == 0 {
+        return Err("Scale has no degrees".to_string());
+    }
+
+    let cents = lines
+        .filter(|line| !line.trim().is_empty())
+        .take(count)
+        .map(parse_degree)
+        .collect::<Result<Vec<_>, _>>()?;
+    if cents.len() != count {
+        return Err(format!("Scale lists {} degrees but has {}", count, cents.len()));
+    }
+    Ok((name, cents))
+}
+
+/// Parse a Scala keyboard mapping (.kbm). Keys missing from the end of the map are unmapped.
+pub fn parse_kbm(text: &str) -> Result<KeyboardMapping, String> {
+    let mut values = text
+        .lines()
+        .filter(|line| !line.starts_with('!') && !line.trim().is_empty())
+        .map(first_token);
+
+    let mut header = [0.0; 7];
+    for (index, value) in header.iter_mut().enumerate() {
+        let token = values.next().ok_or("Keyboard mapping is missing header lines")?;
+        *value = token.parse().map_err(|_| format!("Invalid keyboard mapping value '{}' on line {}", token, index + 1))?;
+    }
+    let [size, first, last, middle, reference, frequency, octave] = header;
+    let note = |value: f64| -> Result<u8, String> {
+        if (0.0..=127.0).contains(&value) && value.fract() == 0.0 {
+            Ok(value as u8)
+        } else {
+            Err(format!("Invalid MIDI note {} in keyboard mapping", value))
+        }
+    };
+    if frequency <= 0.0 {
+        return Err("Reference frequency must be positive".to_string());
+    }
+
+    let keys = (0..size as usize)
+        .map(|_| match values.next() {
+            None | Some("x") => Ok(None),
+            Some(token) => token.parse().map(Some).map_err(|_| format!("Invalid scale degree '{}'", token)),
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mapping = KeyboardMapping {
+        first_note: note(first)?,
+        last_note: note(last)?,
+        middle_note: note(middle)?,
+        reference_note: note(reference)?,
+        reference_freq: frequency,
+        octave_degree: octave as usize,
+        keys,
+    };
+    if key_cents_with(&mapping, &[1200.0], mapping.reference_note as i64).is_none() {
+        return Err("The reference note is unmapped".to_string());
+    }
+    Ok(mapping)
+}
+
+/// A project tuning from the contents of a .scl file and an optional .kbm file.
+/// Without a mapping, degree 0 is on middle C and A4 is 440 Hz, as in Scala.
+pub fn load_scala(scl: &str, kbm: Option<&str>) -> Result<Tuning, String> {
+    let (name, cents) = parse_scl(scl)?;
+    let keyboard = match kbm {
+        Some(kbm) => parse_kbm(kbm)?,
+        None => KeyboardMapping { octave_degree: cents.len(), ..KeyboardMapping::default() },
+    };
+    Ok(Tuning { name, cents, keyboard, ..Tuning::default() })
+}
+
+// Cents of a degree above degree 0, repeating at the scale's period
+fn degree_cents(cents: &[f64], degree: i64) -> f64 {
+    let Some(&period) = cents.last() else {
+        return degree as f64 * 100.0;
+    };
+    let size = cents.len() as i64;
+    let octave = degree.div_euclid(size);
+    let index = degree.rem_euclid(size) as usize;
+    octave as f64 * period + if index == 0 { 0.0 } else { cents[index - 1] }
+}
+
+fn key_cents_with(mapping: &KeyboardMapping, cents: &[f64], key: i64) -> Option<f64> {
+    let offset = key - mapping.middle_note as i64;
+    if mapping.keys.is_empty() {
+        return Some(degree_cents(cents, offset));
+    }
+    let size = mapping.keys.len() as i64;
+    let degree = mapping.keys[offset.rem_euclid(size) as usize]?;
+    let octave = offset.div_euclid(size) as f64 * degree_cents(cents, mapping.octave_degree as i64);
+    Some(octave + degree_cents(cents, degree as i64))
+}
+
+/// Frequency of a (possibly fractional) MIDI note in `tuning`.
+/// `None` for keys the mapping leaves out; keys outside the mapped range stay 12-TET.
+pub fn note_frequency(tuning: &Tuning, note: f32) -> Option<f32> {
+    let mapping = &tuning.keyboard;
+    let key = note.floor() as i64;
+    if key < mapping.first_note as i64 || key > mapping.last_note as i64 {
+        return Some(midi_to_freq(note));
+    }
+
+    let low = key_cents_with(mapping, &tuning.cents, key);
+    let fraction = (note - note.floor()) as f64;
+    let cents = if fraction > 0.0 {
+        match (low, key_cents_with(mapping, &tuning.cents, key + 1)) {
+            (Some(low), Some(high)) => low + (high - low) * fraction,
+            (Some(only), None) | (None, Some(only)) => only,
+            (None, None) => return None,
+        }
+    } else {
+        low?
+    };
+    let reference = key_cents_with(mapping, &tuning.cents, mapping.reference_note as i64).unwrap_or(0.0);
+    Some((mapping.reference_freq * 2.0_f64.powf((cents - reference) / 1200.0)) as f32)
+}
+
+/// 14-bit pitch bend for an offset in semitones, on a receiver with a bend range of ±`range`.
+pub fn pitch_bend(semitones: f32, range: u8) -> u16 {
+    if range == 0 {
+        return PITCH_BEND_CENTER;
+    }
+    let amount = (semitones / range as f32).clamp(-1.0, 1.0);
+    (PITCH_BEND_CENTER as f32 + amount * 8191.0).round() as u16
+}
+
+/// The nearest MIDI note and the pitch bend that together play `frequency`.
+pub fn midi_note_with_bend(frequency: f32, range: u8) -> (u8, u16) {
+    let exact = 69.0 + 12.0 * (frequency / 440.0).log2();
+    let note = exact.round().clamp(0.0, 127.0);
+    (note as u8, pitch_bend(exact - note, range))
+}
+
+// MTS frequency data: semitone, then the fraction above it in 14 bits
+fn mts_frequency(frequency: f32) -> [u8; 3] {
+    let exact = (69.0 + 12.0 * (frequency as f64 / 440.0).log2()).clamp(0.0, 127.0);
+    // In 1/16384 semitone units, so a fraction that rounds up carries into the semitone
+    let units = ((exact * 16384.0).round() as u32).min(127 * 16384 + 16383);
+    let (semitone, fraction) = (units / 16384, units % 16384);
+    let data = [semitone as u8, (fraction >> 7) as u8, (fraction & 0x7F) as u8];
+    // 7F 7F 7F is reserved for "no change"
+    if data == MTS_NO_CHANGE { [0x7F, 0x7F, 0x7E] } else { data }
+}
+
+/// MIDI Tuning Standard real-time single note tuning changes that retune all 128 keys
+/// to `tuning`, split in two SysEx messages. Unmapped keys are left unchanged.
+pub fn mts_retune_messages(tuning: &Tuning) -> Vec<Vec<u8>> {
+    (0..128u8)
+        .collect::<Vec<_>>()
+        .chunks(64)
+        .map(|keys| {
+            // Universal real-time, all devices, tuning program 0
+            let mut message = vec![0xF0, 0x7F, 0x7F, 0x08, 0x02, 0x00, keys.len() as u8];
+            for &key in keys {
+                message.push(key);
+                match note_frequency(tuning, key as f32) {
+                    Some(frequency) => message.extend(mts_frequency(frequency)),
+                    None => message.extend(MTS_NO_CHANGE),
+                }
+            }
+            message.push(0xF7);
+            message
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PYTHAGOREAN: &str = "! pythagorean.scl\n\
+        !\n\
+        Pythagorean 5-note\n \
+        5\n\
+        !\n \
+        9/8\n \
+        81/64\n \
+        3/2\n \
+        27/16\n \
+        2/1\n";
+
+    #[test]
+    fn test_default_tuning_is_12_tet() {
+        let tuning = Tuning::default();
+        for note in [0.0, 21.0, 60.0, 69.0, 69.5, 127.0] {
+            let expected = midi_to_freq(note);
+            let actual = note_frequency(&tuning, note).unwrap();
+            assert!((actual - expected).abs() < expected * 1e-5, "{}: {} != {}", note, actual, expected);
+        }
+        let (note, bend) = midi_note_with_bend(midi_to_freq(64.0), 2);
+        assert_eq!((note, bend), (64, PITCH_BEND_CENTER));
+    }
+
+    #[test]
+    fn test_scala_scale_and_mapping() {
+        let (name, cents) = parse_scl(PYTHAGOREAN).unwrap();
+        assert_eq!(name, "Pythagorean 5-note");
+        assert!((cents[2] - 701.955).abs() < 1e-3);
+        assert_eq!(cents[4], 1200.0);
+        assert!(parse_scl("Broken\n3\n100.0\n").is_err());
+
+        // White keys only from C, black keys unmapped, A4 = 432 Hz
+        let kbm = "! white keys\n12\n0\n127\n60\n69\n432.0\n5\n0\nx\n1\nx\n2\nx\nx\n3\nx\n4\nx\nx\n";
+        let tuning = load_scala(PYTHAGOREAN, Some(kbm)).unwrap();
+        assert_eq!(note_frequency(&tuning, 69.0), Some(432.0));
+        assert_eq!(note_frequency(&tuning, 61.0), None);
+        // G4 is a pure fifth above C4
+        let c4 = note_frequency(&tuning, 60.0).unwrap();
+        let g4 = note_frequency(&tuning, 67.0).unwrap();
+        assert!((g4 / c4 - 1.5).abs() < 1e-5);
+        // Next octave of the mapping
+        assert!((note_frequency(&tuning, 72.0).unwrap() / c4 - 2.0).abs() < 1e-5);
+        assert!(parse_kbm("12\n0\n127\n60\n61\n440.0\n5\n0\nx\n").is_err()); // Unmapped reference
+    }
+
+    #[test]
+    fn test_midi_retuning_output() {
+        // A quarter tone up bends by 1/4 of a ±2 range
+        let (note, bend) = midi_note_with_bend(midi_to_freq(60.5), 2);
+        assert!(note == 60 || note == 61);
+        assert_eq!((bend as i32 - PITCH_BEND_CENTER as i32).abs(), 2048);
+
+        let messages = mts_retune_messages(&Tuning::default());
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].len(), 7 + 64 * 4 + 1);
+        // Key 69 plays exactly A4
+        let key = 69 - 64;
+        assert_eq!(&messages[1][7 + key * 4..7 + key * 4 + 4], &[69, 69, 0, 0]);
+    }
+}
@@ -0,0 +1,459 @@
+use crate::engine::chord::MAX_CHORD_NOTES;
+use crate::engine::domain::{
+    NUM_SYNTH_PARAMS, PARAM_DECAY, PARAM_DELAY_SEND, PARAM_DRIVE, PARAM_FILTER_CUTOFF, PARAM_PAN, PARAM_PITCH,
+    PARAM_RESONANCE, PARAM_REVERB_SEND, PARAM_SUSTAIN, PARAM_TUNING, PARAM_VOLUME,
+};
+use crate::engine::kernel::CONTROL_RATE_SAMPLES;
+use crate::engine::sample_pool::SampleTable;
+use crate::engine::smoothing::{ramp_seconds, SmoothedValue};
+use crate::engine::tuning::fine_tune_cents;
+use crate::shared::models::{default_track_params, AtomicStep, Track, MAX_SUBTRACKS};
+use std::f32::consts::PI;
+
+const VOICE_LEVEL: f32 = 0.3; // Headroom for 16 summed tracks
+
+// Helper to convert MIDI note to Hz (12-TET, A440). Trigs go through the project tuning instead.
+pub fn midi_to_freq(note: f32) -> f32 {
+    440.0 * 2.0_f32.powf((note - 69.0) / 12.0)
+}
+
+/// Resolve a parameter for a step: P-Lock first, then the track default.
+pub fn resolve_param(track: &Track, step: &AtomicStep, param_id: usize) -> f32 {
+    step.p_locks[param_id].unwrap_or(track.default_params[param_id])
+}
+
+// Map normalized decay (0.0 - 1.0) to seconds (10ms - 2s)
+fn decay_seconds(decay: f32) -> f32 {
+    0.01 * 200.0_f32.powf(decay.clamp(0.0, 1.0))
+}
+
+/// Sine oscillator or sample player with a decay/sustain envelope.
+/// The kernel closes the gate at the end of the step length with `release`.
+pub struct Voice {
+    sample_rate: f32,
+    frequency: SmoothedValue, // Glides on slide trigs
+    detune: f32,              // Pitch ratio from the track's fine tune
+    phase: f32,
+    sample: Option<u16>, // Sample pool slot; None plays the oscillator
+    sample_position: f64, // Frames into the sample
+    sample_speed: f64,    // Playback rate from pitch, 1.0 = original
+
+    velocity: f32,
+    envelope: f32,
+    sustain: f32,
+    decay_coeff: f32,
+    gate_open: bool, // Envelope holds at sustain until released
+}
+
+impl Voice {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut voice = Self {
+            sample_rate,
+            frequency: SmoothedValue::new(440.0),
+            detune: 1.0,
+            phase: 0.0,
+            sample: None,
+            sample_position: 0.0,
+            sample_speed: 1.0,
+            velocity: 1.0,
+            envelope: 0.0,
+            sustain: 0.0,
+            decay_coeff: 0.0,
+            gate_open: false,
+        };
+        voice.set_decay(0.5);
+        voice
+    }
+
+    /// Pitch of the current note (the slide target while gliding).
+    pub fn frequency(&self) -> f32 {
+        self.frequency.target()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.envelope > 1e-5
+    }
+
+    pub fn is_gate_open(&self) -> bool {
+        self.gate_open
+    }
+
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.frequency.jump(frequency);
+    }
+
+    /// Glide from the current pitch to `frequency` over `samples`.
+    pub fn slide_frequency(&mut self, frequency: f32, samples: usize) {
+        self.frequency.ramp_to(frequency, samples);
+    }
+
+    /// Fine tune on top of the note's pitch, applied to the oscillator and samples.
+    pub fn set_detune(&mut self, cents: f32) {
+        self.detune = 2.0_f32.powf(cents / 1200.0);
+    }
+
+    pub fn set_decay(&mut self, decay: f32) {
+        // -60 dB after the decay time
+        self.decay_coeff = (-6.9 / (decay_seconds(decay) * self.sample_rate)).exp();
+    }
+
+    pub fn set_sustain(&mut self, sustain: f32) {
+        self.sustain = sustain.clamp(0.0, 1.0);
+    }
+
+    // Same detune and envelope shape as `other`, without recomputing them
+    fn copy_settings(&mut self, other: &Voice) {
+        self.detune = other.detune;
+        self.decay_coeff = other.decay_coeff;
+        self.sustain = other.sustain;
+    }
+
+    /// Start a note. The envelope holds at sustain level until `release`.
+    pub fn note_on(&mut self, frequency: f32, velocity: u8) {
+        self.frequency.jump(frequency);
+        self.phase = 0.0;
+        self.sample = None;
+        self.retrigger(velocity);
+    }
+
+    /// Slide trig: glide to `frequency` over `samples` and restart the envelope, keeping the phase.
+    pub fn slide_on(&mut self, frequency: f32, velocity: u8, samples: usize) {
+        self.slide_frequency(frequency, samples);
+        self.sample = None;
+        self.retrigger(velocity);
+    }
+
+    /// Play a sample pool slot from the start. `speed` 2.0 is an octave up.
+    pub fn play_sample(&mut self, slot: u16, speed: f32, velocity: u8) {
+        self.sample = Some(slot);
+        self.sample_position = 0.0;
+        self.sample_speed = speed as f64;
+        self.retrigger(velocity);
+    }
+
+    /// Restart the envelope without touching pitch (Trigless Trig).
+    pub fn retrigger(&mut self, velocity: u8) {
+        self.velocity = velocity as f32 / 127.0;
+        self.envelope = 1.0;
+        self.gate_open = true;
+    }
+
+    /// Close the gate: the envelope decays to silence.
+    pub fn release(&mut self) {
+        self.gate_open = false;
+    }
+
+    pub fn render(&mut self, samples: &SampleTable) -> f32 {
+        if !self.is_active() {
+            return 0.0;
+        }
+
+        let source = match self.sample {
+            // Samples play at full level, mono-summed; a deleted slot is silent
+            Some(slot) => match samples.get(slot as usize).and_then(Option::as_ref) {
+                Some(buffer) => {
+                    let [left, right] = buffer.frame_at(self.sample_position);
+                    self.sample_position +=
+                        self.sample_speed * (self.detune * buffer.sample_rate / self.sample_rate) as f64;
+                    (left + right) * 0.5
+                }
+                None => 0.0,
+            },
+            None => {
+                let sine = (self.phase * 2.0 * PI).sin() * VOICE_LEVEL;
+                self.phase += self.frequency.tick() * self.detune / self.sample_rate;
+                if self.phase >= 1.0 {
+                    self.phase -= 1.0;
+                }
+                sine
+            }
+        };
+        let out = source * self.envelope * self.velocity;
+
+        // Decay towards sustain while the gate is open, towards silence after
+        let target = if self.gate_open { self.sustain } else { 0.0 };
+        self.envelope = target + (self.envelope - target) * self.decay_coeff;
+
+        out
+    }
+}
+
+/// Zero-delay-feedback state variable lowpass filter.
+struct LowpassFilter {
+    g: f32,
+    k: f32,
+    ic1eq: f32,
+    ic2eq: f32,
+}
+
+impl LowpassFilter {
+    fn new() -> Self {
+        Self { g: 1.0, k: 2.0, ic1eq: 0.0, ic2eq: 0.0 }
+    }
+
+    fn set(&mut self, sample_rate: f32, cutoff: f32, resonance: f32) {
+        // 20 Hz - 20 kHz, exponential
+        let freq = (20.0 * 1000.0_f32.powf(cutoff.clamp(0.0, 1.0))).min(sample_rate * 0.45);
+        self.g = (PI * freq / sample_rate).tan();
+        self.k = 2.0 - 1.9 * resonance.clamp(0.0, 1.0);
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let a1 = 1.0 / (1.0 + self.g * (self.g + self.k));
+        let a2 = self.g * a1;
+        let a3 = self.g * a2;
+        let v3 = input - self.ic2eq;
+        let v1 = a1 * self.ic1eq + a2 * v3;
+        let v2 = self.ic2eq + a2 * self.ic1eq + a3 * v3;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+        v2
+    }
+}
+
+/// The voices of one subtrack: a lead voice plus the rest of a chord.
+pub struct VoiceLayer {
+    pub voice: Voice, // Lead voice: single notes and chord roots, slides, locks and trigless trigs
+    pub chord_voices: [Voice; MAX_CHORD_NOTES - 1], // The rest of a chord, lowest first
+}
+
+impl VoiceLayer {
+    fn new(sample_rate: f32) -> Self {
+        Self {
+            voice: Voice::new(sample_rate),
+            chord_voices: std::array::from_fn(|_| Voice::new(sample_rate)),
+        }
+    }
+
+    /// Voice `index` of a chord, 0 being the lead voice.
+    pub fn voice_mut(&mut self, index: usize) -> Option<&mut Voice> {
+        match index {
+            0 => Some(&mut self.voice),
+            _ => self.chord_voices.get_mut(index - 1),
+        }
+    }
+
+    /// Close the gate of every voice.
+    pub fn release(&mut self) {
+        self.release_from(0);
+    }
+
+    /// Close the gates of the chord voices from `index` on (0 includes the lead voice).
+    pub fn release_from(&mut self, index: usize) {
+        if index == 0 {
+            self.voice.release();
+        }
+        for voice in self.chord_voices.iter_mut().skip(index.saturating_sub(1)) {
+            voice.release();
+        }
+    }
+
+    pub fn active_voices(&self) -> usize {
+        std::iter::once(&self.voice).chain(&self.chord_voices).filter(|v| v.is_active()).count()
+    }
+
+    fn render(&mut self, samples: &SampleTable) -> f32 {
+        let mut out = self.voice.render(samples);
+        for voice in self.chord_voices.iter_mut() {
+            out += voice.render(samples);
+        }
+        out
+    }
+}
+
+/// One rendered frame of a track channel.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChannelFrame {
+    pub pre_fx: [f32; 2],  // Raw voice output
+    pub post_fx: [f32; 2], // After drive, filter, volume and pan
+    pub reverb_send: [f32; 2],
+    pub delay_send: [f32; 2],
+}
+
+/// Per-track signal chain: subtrack voices -> drive -> filter -> volume/pan -> sends.
+/// Parameters are the step's base values plus modulation offsets, shared by the subtracks.
+/// Base value changes ramp in (see `ramp_seconds`) so they don't zipper.
+pub struct TrackChannel {
+    sample_rate: f32,
+    layers: [VoiceLayer; MAX_SUBTRACKS], // One per subtrack voice ID
+    layers_used: usize,                  // Layers ever played, the rest are not rendered
+    filter: LowpassFilter,
+    base: [SmoothedValue; NUM_SYNTH_PARAMS], // Track defaults + P-Locks of the last step
+    ramping: bool,                           // Some base value is still moving
+    voice_countdown: usize,                  // Ramp samples until the voices and filter follow
+    modulation: [f32; NUM_SYNTH_PARAMS], // Bipolar offsets, updated at control rate
+    drive: f32,
+    volume: f32,
+    pan: f32,
+    reverb_send: f32,
+    delay_send: f32,
+}
+
+impl TrackChannel {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut base = [SmoothedValue::default(); NUM_SYNTH_PARAMS];
+        for (value, default) in base.iter_mut().zip(default_track_params()) {
+            value.jump(default);
+        }
+        let mut channel = Self {
+            sample_rate,
+            layers: std::array::from_fn(|_| VoiceLayer::new(sample_rate)),
+            layers_used: 1,
+            filter: LowpassFilter::new(),
+            base,
+            ramping: false,
+            voice_countdown: CONTROL_RATE_SAMPLES,
+            modulation: [0.0; NUM_SYNTH_PARAMS],
+            drive: 0.0,
+            volume: 0.0,
+            pan: 0.5,
+            reverb_send: 0.0,
+            delay_send: 0.0,
+        };
+        channel.refresh();
+        channel
+    }
+
+    /// Voices of a subtrack voice ID (wrapped to `MAX_SUBTRACKS`).
+    pub fn layer(&self, voice_id: usize) -> &VoiceLayer {
+        &self.layers[voice_id % MAX_SUBTRACKS]
+    }
+
+    pub fn layer_mut(&mut self, voice_id: usize) -> &mut VoiceLayer {
+        let index = voice_id % MAX_SUBTRACKS;
+        self.layers_used = self.layers_used.max(index + 1);
+        &mut self.layers[index]
+    }
+
+    /// Close the gate of every voice.
+    pub fn release(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.release();
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active_voices() > 0
+    }
+
+    pub fn active_voices(&self) -> usize {
+        self.layers[..self.layers_used].iter().map(VoiceLayer::active_voices).sum()
+    }
+
+    /// Apply the track defaults and this step's P-Locks to the channel.
+    /// Each parameter ramps over its own time, or over `slide` samples on a slide trig.
+    /// Stepped parameters jump even on a slide, except pitch, which is what it glides.
+    /// A silent channel has nothing to zipper, so it takes the new values at once.
+    pub fn apply_params(&mut self, track: &Track, step: &AtomicStep, slide: Option<usize>) {
+        let silent = !self.is_active();
+        for (param_id, value) in self.base.iter_mut().enumerate() {
+            let seconds = ramp_seconds(param_id);
+            let samples = match (slide, seconds) {
+                _ if silent => 0,
+                (Some(samples), Some(_)) => samples,
+                (Some(samples), None) if param_id == PARAM_PITCH => samples,
+                _ => (seconds.unwrap_or(0.0) * self.sample_rate) as usize,
+            };
+            value.ramp_to(resolve_param(track, step, param_id), samples);
+        }
+        self.ramping = self.base.iter().any(SmoothedValue::is_ramping);
+        self.refresh();
+    }
+
+    /// Replace the modulation offsets (e.g. from the track's LFOs).
+    pub fn set_modulation(&mut self, offsets: [f32; NUM_SYNTH_PARAMS]) {
+        if offsets != self.modulation {
+            self.modulation = offsets;
+            self.refresh();
+        }
+    }
+
+    /// Base value plus modulation, clamped to the normalized range.
+    pub fn effective_param(&self, param_id: usize) -> f32 {
+        (self.base[param_id].value() + self.modulation[param_id]).clamp(0.0, 1.0)
+    }
+
+    // Recompute the DSP state from the effective parameters
+    fn refresh(&mut self) {
+        self.refresh_voices();
+        self.refresh_mix();
+        self.voice_countdown = CONTROL_RATE_SAMPLES;
+    }
+
+    // Tuning, envelope and filter settings, too costly to follow a ramp every sample
+    fn refresh_voices(&mut self) {
+        // Every voice shares the first one's settings
+        let detune = fine_tune_cents(self.effective_param(PARAM_TUNING));
+        let decay = self.effective_param(PARAM_DECAY);
+        let sustain = self.effective_param(PARAM_SUSTAIN);
+        let [first, rest @ ..] = &mut self.layers;
+        first.voice.set_detune(detune);
+        first.voice.set_decay(decay);
+        first.voice.set_sustain(sustain);
+        let lead = &first.voice;
+        let others = rest.iter_mut().flat_map(|layer| std::iter::once(&mut layer.voice).chain(&mut layer.chord_voices));
+        for voice in first.chord_voices.iter_mut().chain(others) {
+            voice.copy_settings(lead);
+        }
+        self.filter.set(
+            self.sample_rate,
+            self.effective_param(PARAM_FILTER_CUTOFF),
+            self.effective_param(PARAM_RESONANCE),
+        );
+    }
+
+    // Drive, level and sends
+    fn refresh_mix(&mut self) {
+        self.drive = self.effective_param(PARAM_DRIVE);
+        self.volume = self.effective_param(PARAM_VOLUME);
+        self.pan = self.effective_param(PARAM_PAN);
+        self.reverb_send = self.effective_param(PARAM_REVERB_SEND);
+        self.delay_send = self.effective_param(PARAM_DELAY_SEND);
+    }
+
+    /// Render `out.len()` frames with the current parameters.
+    pub fn render_block(&mut self, samples: &SampleTable, out: &mut [ChannelFrame]) {
+        for frame in out.iter_mut() {
+            *frame = self.render(samples);
+        }
+    }
+
+    pub fn render(&mut self, samples: &SampleTable) -> ChannelFrame {
+        if self.ramping {
+            for value in self.base.iter_mut() {
+                value.tick();
+            }
+            self.ramping = self.base.iter().any(SmoothedValue::is_ramping);
+            self.refresh_mix();
+            // The voices and filter follow at control rate, and once more when the ramp ends
+            self.voice_countdown = self.voice_countdown.saturating_sub(1);
+            if self.voice_countdown == 0 || !self.ramping {
+                self.refresh_voices();
+                self.voice_countdown = CONTROL_RATE_SAMPLES;
+            }
+        }
+
+        let mut dry = 0.0;
+        for layer in self.layers[..self.layers_used].iter_mut() {
+            dry += layer.render(samples);
+        }
+
+        let mut wet = dry;
+        if self.drive > 0.0 {
+            let gain = 1.0 + self.drive * 9.0;
+            wet = (wet * gain).tanh() / gain.tanh();
+        }
+        wet = self.filter.process(wet) * self.volume;
+
+        // Equal-power pan
+        let angle = self.pan * PI * 0.5;
+        let post = [wet * angle.cos(), wet * angle.sin()];
+
+        ChannelFrame {
+            pre_fx: [dry, dry],
+            post_fx: post,
+            reverb_send: [post[0] * self.reverb_send, post[1] * self.reverb_send],
+            delay_send: [post[0] * self.delay_send, post[1] * self.delay_send],
+        }
+    }
+}
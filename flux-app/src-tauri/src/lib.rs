@@ -2,31 +2,69 @@
 pub mod engine;
 pub mod shared;
 pub mod commands;
+pub mod preferences;
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use rtrb::RingBuffer;
-use tauri::{Emitter, State};
+use tauri::{Emitter, Manager, State};
 use triple_buffer::TripleBuffer;
 use crate::engine::domain::AudioSnapshot;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::engine::midi_engine::{MidiEngine, EngineCommand};
+use crate::engine::midi_input;
 use crate::engine::kernel::{AudioCommand, FluxKernel};
+use crate::engine::sync::{garbage_queue, spawn_garbage_collector};
+use crate::engine::device::{spawn_device_thread, AUDIO_STATUS_EVENT};
+use crate::engine::recorder::{input_queue, recording_queue, spawn_recording_collector};
+use crate::engine::sample_pool::SamplePool;
+use crate::engine::metrics::{MetricsHistory, ENGINE_METRICS_EVENT, METRICS_INTERVAL};
+use crate::engine::meters::{AUDIO_LEVELS_EVENT, LEVELS_INTERVAL};
+use crate::engine::analyzer::{analyzer_queue, spawn_analyzer, ANALYZER_EVENT};
+use crate::engine::render_pool::{default_worker_count, RenderPool};
+use crate::engine::diagnostics::{
+    diagnostics_queue, spawn_logger, LogSource, RecentLogs, RotatingFile, LOG_EVENT, LOG_FILES_KEPT, LOG_FILE_MAX_BYTES,
+    LOG_FILE_NAME,
+};
+use crate::preferences::Preferences;
+use crate::shared::models::{Pattern, Project, PATTERN_SLOTS};
 
 pub struct AppState {
     command_producer: Mutex<rtrb::Producer<AudioCommand>>,
 }
 
+/// Samples recorded or resampled this session.
+pub struct SamplePoolState {
+    pool: Mutex<SamplePool>,
+}
+
+/// Engine metrics sampled every `METRICS_INTERVAL` by the sync thread.
+pub struct MetricsState {
+    history: Arc<Mutex<MetricsHistory>>,
+}
+
+/// Log records from the real-time threads, kept for the debug panel.
+pub struct LogState {
+    recent: Arc<Mutex<RecentLogs>>,
+}
+
+/// Emitted with the new sample's `SampleInfo` when a recording lands in the pool.
+pub const SAMPLE_RECORDED_EVENT: &str = "sample-recorded";
+
 struct EngineState {
     command_producer: Mutex<rtrb::Producer<EngineCommand>>,
 }
 
+/// Open MIDI input; notes stop arriving when it is dropped.
+struct MidiInputState {
+    _connection: Mutex<midir::MidiInputConnection<()>>,
+}
+
 #[derive(serde::Deserialize)]
 pub struct MidiCommandArgs {
     pub command: String,
@@ -51,47 +89,51 @@ fn push_midi_command(_state: State<'_, EngineState>, args: MidiCommandArgs) -> R
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // 1. Setup Audio via CPAL
-    let host = cpal::default_host();
-    let device = host.default_output_device().expect("No output device available");
-    let config = device.default_output_config().expect("No default config");
-    let sample_rate = config.sample_rate().0 as f32;
-    let channels = config.channels() as usize;
-
-    // 2. Create Command Queue (RingBuffer) for Audio
+    // 1. Create Command Queue (RingBuffer) for Audio
     let (audio_producer, audio_consumer) = RingBuffer::new(1024);
 
-    // 3. Create State Snapshot (TripleBuffer)
+    // 2. Create State Snapshot (TripleBuffer)
     let (snapshot_producer, mut snapshot_consumer) = TripleBuffer::new(&AudioSnapshot::default()).split();
 
-    // 4. Initialize Kernel
-    // Move the consumer into the audio thread (Kernel)
-    let mut kernel = FluxKernel::new(sample_rate, audio_consumer, snapshot_producer);
-
-    // 4. Build Audio Stream
-    // We run the stream in a separate thread managed by CPAL
-    let stream = device.build_output_stream(
-        &config.into(),
-        move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
-            // CALL THE KERNEL PROCESS LOOP HERE
-            kernel.process(data, channels);
-        },
-        |err| eprintln!("Stream error: {}", err),
-        None // Timeout
-    ).expect("Failed to build audio stream");
-
-    // 6. Start Stream
-    stream.play().expect("Failed to play stream");
-
-    // LEAK THE STREAM so it stays alive for the duration of the app
-    // (In a production app, we would store this in the AppHandle)
-    Box::leak(Box::new(stream));
+    // 3. Initialize Kernel
+    // The sample rate is set by the device thread once the output stream opens
+    let mut kernel = FluxKernel::new(48000.0, audio_consumer, snapshot_producer);
+
+    // Swapped-out patterns are freed on the garbage thread, never in the callback
+    let (garbage_sender, garbage_consumer) = garbage_queue();
+    kernel.garbage = garbage_sender;
+    spawn_garbage_collector(garbage_consumer);
+
+    // Audio input and finished recordings (collected into the sample pool in setup)
+    let (input_producer, input_consumer) = input_queue();
+    kernel.input = Some(input_consumer);
+    let (recording_sender, recording_consumer) = recording_queue();
+    kernel.recordings = recording_sender;
+
+    // Master (and selected track) blocks for the scope and spectrum analyzer
+    let (analyzer_tap, analyzer_consumer) = analyzer_queue();
+    kernel.analyzer = analyzer_tap;
+
+    // Tracks render in parallel on the spare cores (inline on a single core)
+    let workers = default_worker_count();
+    if workers > 0 {
+        kernel.render_pool = Some(RenderPool::new(workers));
+    }
+
+    // The audio and MIDI threads never print; their events go to the logger thread
+    let (audio_diagnostics, audio_log) = diagnostics_queue(LogSource::Audio);
+    kernel.diagnostics = audio_diagnostics;
+    let (midi_diagnostics, midi_log) = diagnostics_queue(LogSource::Midi);
+
+    // Shared with the stream callback, so the device can be changed without losing state
+    let kernel = Arc::new(Mutex::new(kernel));
 
     // Existing MIDI Engine setup
     let (midi_producer, midi_consumer) = RingBuffer::new(1024);
 
     thread::spawn(move || {
         let mut engine = MidiEngine::new(midi_consumer).expect("Failed to initialize MIDI Engine");
+        engine.diagnostics = midi_diagnostics;
         engine.run();
     });
 
@@ -100,19 +142,96 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .setup(move |app| {
             let app_handle = app.handle().clone();
+
+            // 4. Write real-time diagnostics to a rotating log file and stream them to the debug panel
+            let log_file = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| e.to_string())
+                .and_then(|dir| {
+                    RotatingFile::open(&dir.join("logs").join(LOG_FILE_NAME), LOG_FILE_MAX_BYTES, LOG_FILES_KEPT)
+                        .map_err(|e| e.to_string())
+                })
+                .inspect_err(|e| eprintln!("Failed to open the log file: {}", e))
+                .ok();
+            let recent_logs = Arc::new(Mutex::new(RecentLogs::default()));
+            app.manage(LogState { recent: Arc::clone(&recent_logs) });
+            let log_handle = app_handle.clone();
+            spawn_logger(vec![audio_log, midi_log], log_file, move |records| {
+                if let Ok(mut recent) = recent_logs.lock() {
+                    recent.extend(records);
+                }
+                let _ = log_handle.emit(LOG_EVENT, records);
+            });
+
+            // 5. Open the saved output device (falls back to the default, then headless)
+            let preferences = Preferences::load(&app_handle);
+            let status_handle = app_handle.clone();
+            app.manage(spawn_device_thread(kernel, input_producer, preferences.audio, move |status| {
+                let _ = status_handle.emit(AUDIO_STATUS_EVENT, status);
+            }));
+
+            // 6. Store finished recordings in the pool and load them into the kernel
+            let recording_handle = app_handle.clone();
+            spawn_recording_collector(recording_consumer, move |recording| {
+                if let Err(e) = commands::add_recording(&recording_handle, *recording) {
+                    eprintln!("Failed to store recording: {}", e);
+                }
+            });
             
+            // 7. Scope and spectrum for the UI, computed off the audio thread
+            let analyzer_handle = app_handle.clone();
+            spawn_analyzer(analyzer_consumer, move |frame| {
+                let _ = analyzer_handle.emit(ANALYZER_EVENT, &frame);
+            });
+
+            // 8. Notes from the MIDI input feed the arpeggiators of the tracks on their channels
+            let input_handle = app_handle.clone();
+            match midi_input::connect(move |note| {
+                if let Err(e) = commands::route_note_input(&input_handle, note) {
+                    eprintln!("Failed to route MIDI input: {}", e);
+                }
+            }) {
+                Ok(connection) => {
+                    app.manage(MidiInputState { _connection: Mutex::new(connection) });
+                }
+                Err(e) => eprintln!("MIDI input unavailable: {}", e),
+            }
+
+            // 9. Keep a history of engine metrics for the CPU meter
+            let metrics_history = Arc::new(Mutex::new(MetricsHistory::default()));
+            app.manage(MetricsState { history: Arc::clone(&metrics_history) });
+
             // Spawn Sync Thread
             thread::spawn(move || {
-                let mut last_step = 999;
+                let mut last_step = (u64::MAX, 999, 0, None); // (sequence, current_step, pattern_slot, queued_pattern)
+                let mut last_metrics = Instant::now();
+                let mut last_levels = Instant::now();
                 loop {
-                    // Read latest state
-                    let snapshot = snapshot_consumer.read();
+                    // Read latest state (a plain copy, the snapshot is fixed-size)
+                    let snapshot = *snapshot_consumer.read();
                     
-                    // Only emit if step changed
-                    if snapshot.current_step != last_step {
+                    // Only emit if step or pattern changed
+                    let step = (snapshot.sequence, snapshot.current_step, snapshot.pattern_slot, snapshot.queued_pattern);
+                    if step != last_step {
                          // Emit to Frontend
                          let _ = app_handle.emit("playback-status", snapshot);
-                         last_step = snapshot.current_step;
+                         last_step = step;
+                    }
+
+                    // Levels at UI rate, also while stopped so the meters fall back
+                    if last_levels.elapsed() >= LEVELS_INTERVAL {
+                        last_levels = Instant::now();
+                        let _ = app_handle.emit(AUDIO_LEVELS_EVENT, snapshot.levels);
+                    }
+
+                    // Metrics are sampled at a fixed rate, playing or not
+                    if last_metrics.elapsed() >= METRICS_INTERVAL {
+                        last_metrics = Instant::now();
+                        if let Ok(mut history) = metrics_history.lock() {
+                            history.push(snapshot.metrics);
+                        }
+                        let _ = app_handle.emit(ENGINE_METRICS_EVENT, snapshot.metrics);
                     }
                     
                     thread::sleep(Duration::from_millis(16)); // ~60 FPS polling
@@ -123,36 +242,65 @@ pub fn run() {
         .manage(AppState {
             command_producer: Mutex::new(audio_producer),
         })
+        .manage(SamplePoolState {
+            pool: Mutex::new(SamplePool::default()),
+        })
         .manage(EngineState {
             command_producer: Mutex::new(midi_producer),
         })
         .invoke_handler(tauri::generate_handler![
             greet, 
             push_midi_command, 
-            save_pattern, 
-            load_pattern, 
+            save_project, 
+            load_project, 
             set_lfo_shape, 
             set_lfo_designer_value, 
             commands::set_playback_state, 
             commands::toggle_step,
-            commands::set_param_lock
+            commands::set_param_lock,
+            commands::update_pattern,
+            commands::update_track,
+            commands::queue_pattern,
+            commands::update_song,
+            commands::load_tuning,
+            commands::export_audio,
+            commands::list_audio_devices,
+            commands::get_audio_device,
+            commands::get_audio_status,
+            commands::start_recording,
+            commands::stop_recording,
+            commands::list_samples,
+            commands::delete_sample,
+            commands::get_engine_metrics,
+            commands::set_analyzer_track,
+            commands::get_recent_logs,
+            commands::set_audio_device
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
 #[tauri::command]
-fn save_pattern(pattern: crate::shared::models::Pattern, path: String) -> Result<(), String> {
-    let json = serde_json::to_string_pretty(&pattern).map_err(|e| e.to_string())?;
+fn save_project(project: Project, path: String) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(&project).map_err(|e| e.to_string())?;
     std::fs::write(path, json).map_err(|e| e.to_string())?;
     Ok(())
 }
 
+/// Load a project. Files saved before pattern banks hold a single pattern, which lands in slot A01.
 #[tauri::command]
-fn load_pattern(path: String) -> Result<crate::shared::models::Pattern, String> {
+fn load_project(path: String) -> Result<Project, String> {
     let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let pattern = serde_json::from_str(&json).map_err(|e| e.to_string())?;
-    Ok(pattern)
+    let mut project = match serde_json::from_str::<Project>(&json) {
+        Ok(project) => project,
+        Err(e) => {
+            let pattern = serde_json::from_str::<Pattern>(&json).map_err(|_| e.to_string())?;
+            Project { patterns: vec![Some(pattern)], ..Project::default() }
+        }
+    };
+    project.patterns.resize(PATTERN_SLOTS, None);
+    project.current = project.current.min(PATTERN_SLOTS - 1);
+    Ok(project)
 }
 
 #[tauri::command]
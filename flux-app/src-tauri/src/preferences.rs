@@ -0,0 +1,43 @@
+use crate::engine::device::AudioSettings;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+const PREFERENCES_FILE: &str = "preferences.json";
+
+/// App-wide settings, stored as JSON in the app config directory.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub audio: AudioSettings,
+}
+
+fn preferences_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(PREFERENCES_FILE))
+}
+
+impl Preferences {
+    /// Missing or unreadable preferences fall back to the defaults.
+    pub fn load(app: &AppHandle) -> Self {
+        let Ok(path) = preferences_path(app) else {
+            return Self::default();
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Ignoring invalid preferences {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+        let path = preferences_path(app)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| e.to_string())
+    }
+}
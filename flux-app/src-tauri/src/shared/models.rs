@@ -11,16 +11,18 @@ pub enum TrigType {
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum LogicOp {
-    And,    // A AND B
-    Or,     // A OR B
-    Xor,    // A XOR B
-    Not,    // NOT A
-    // Add more logic ops as needed
+    // Must match the frontend's variants, patterns are exchanged as JSON
+    Match,
+    Not,
+    Pre,
+    Nei,
+    Fill,
+    // Add more as needed
 }
 
 impl Default for LogicOp {
     fn default() -> Self {
-        Self::And
+        Self::Match
     }
 }
 
@@ -56,6 +58,8 @@ pub struct AtomicStep {
     pub p_locks: ParameterLocks,// Parameter Modulations
     pub is_slide: bool,         // Analog Four Parameter Slide
     pub retrig_rate: u8,        // 0 = Off
+    #[serde(default)]
+    pub chord: Chord,           // Notes played with `note` as the root
 }
 
 impl Default for AtomicStep {
@@ -72,10 +76,54 @@ impl Default for AtomicStep {
             p_locks: [None; 128], // Compiler optimizes this
             is_slide: false,
             retrig_rate: 0,
+            chord: Chord::default(),
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ChordKind {
+    #[default]
+    Single, // Just the root
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    Sus2,
+    Sus4,
+    Power,
+    Major7,
+    Minor7,
+    Dominant7,
+    HalfDiminished7,
+    Diminished7,
+    Add9,
+    Major9,
+    Minor9,
+    Custom, // `Chord::intervals`
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Voicing {
+    #[default]
+    Close,
+    Drop2,  // Second-highest note an octave down
+    Drop3,  // Third-highest note an octave down
+    Spread, // Every other note an octave up
+}
+
+/// Chord of a step: a chord type (or custom intervals) on the step's note, with inversion and voicing.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Chord {
+    pub kind: ChordKind,
+    #[serde(default)]
+    pub intervals: Vec<u8>, // Semitones above the root for Custom chords, the root is implied
+    #[serde(default)]
+    pub inversion: u8, // Lowest notes moved up an octave
+    #[serde(default)]
+    pub voicing: Voicing,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum MachineType {
     OneShot,    // Digitakt II
@@ -87,9 +135,11 @@ pub enum MachineType {
     MidiCC,     // External
 }
 
+pub const MAX_SUBTRACKS: usize = 8; // Layers per track, sequenced in parallel (Tonverk)
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Subtrack {
-    pub voice_id: usize, // Internal Audio Engine Voice ID
+    pub voice_id: usize, // Internal Audio Engine Voice ID, one voice layer per ID (below MAX_SUBTRACKS)
     pub steps: Vec<AtomicStep>, // 16-64 steps
 }
 
@@ -100,7 +150,34 @@ pub struct Track {
     pub subtracks: Vec<Subtrack>, // Vector to support Tonverk layering
     pub length: u32,
     pub scale: f32, // 1x, 2x, 1/2x, etc.
+    #[serde(with = "serde_big_array::BigArray", default = "default_track_params")]
+    pub default_params: [f32; 128], // Track-level default parameters
     pub lfos: Vec<LFO>,
+    #[serde(default)]
+    pub mod_matrix: Vec<ModSlot>, // Dynamic: slots are added and removed by the user
+    #[serde(default)]
+    pub mod_envelopes: [ModEnvelope; 2], // Triggered by Note/SynthTrigger trigs
+    #[serde(default)]
+    pub sample: Option<u16>, // Sample pool slot played by Note trigs instead of the oscillator
+    #[serde(default)]
+    pub arp_enabled: bool, // Note trigs and held MIDI input notes play through the arpeggiator
+}
+
+/// Track-level parameter defaults, indexed like `ParameterLocks`.
+/// See `engine::domain` for the parameter IDs.
+pub fn default_track_params() -> [f32; 128] {
+    let mut params = [0.5; 128]; // Mid-range unless stated otherwise
+    params[2] = 1.0; // Filter Cutoff: fully open
+    params[3] = 0.0; // Resonance
+    params[4] = 0.0; // Drive
+    params[5] = 0.0; // Sustain
+    params[6] = 0.0; // Reverb Send
+    params[7] = 0.0; // Delay Send
+    params[8] = 0.8; // Volume
+    params[16] = 0.0; // Arp Mode: Up
+    params[17] = 0.4; // Arp Rate: 1/16
+    params[18] = 0.0; // Arp Octaves: 1
+    params
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -133,11 +210,76 @@ impl Default for LFO {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EnvelopeMode {
+    Ad,   // Attack, then decay to zero. Ignores the gate
+    Adsr, // Holds at sustain level while the trig's gate is open
+}
+
+/// Modulation envelope with its own routing, like the LFOs.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModEnvelope {
+    pub mode: EnvelopeMode,
+    pub attack: f32,     // 0.0 to 1.0 (1 ms - 10 s)
+    pub decay: f32,      // 0.0 to 1.0 (1 ms - 10 s)
+    pub sustain: f32,    // Level, 0.0 to 1.0 (ADSR only)
+    pub release: f32,    // 0.0 to 1.0 (1 ms - 10 s, ADSR only)
+    pub destination: u8, // Internal parameter ID (not a MIDI CC)
+    pub depth: f32,      // -1.0 to 1.0
+}
+
+impl Default for ModEnvelope {
+    fn default() -> Self {
+        Self {
+            mode: EnvelopeMode::Ad,
+            attack: 0.0,
+            decay: 0.4,
+            sustain: 0.5,
+            release: 0.4,
+            destination: 2, // Filter Cutoff
+            depth: 0.0,
+        }
+    }
+}
+
+/// Modulation source of a mod matrix slot.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ModSource {
+    Lfo(u8),      // Track LFO index, bipolar (ignores the LFO's own amount)
+    Envelope(u8), // Modulation envelope index, unipolar (ignores its own depth)
+    Velocity,     // Velocity of the last trig, unipolar
+    Note,         // Note of the last trig, bipolar around C4
+    Random,       // New value on every trig, bipolar
+    StepPosition, // Playhead position within the pattern, unipolar
+}
+
+/// One mod matrix routing: source -> internal parameter ID, scaled by depth.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModSlot {
+    pub source: ModSource,
+    pub destination: u8, // Internal parameter ID (not a MIDI CC)
+    pub depth: f32,      // -1.0 to 1.0, P-Lockable per step
+}
+
+impl Default for ModSlot {
+    fn default() -> Self {
+        Self {
+            source: ModSource::Lfo(0),
+            destination: 2, // Filter Cutoff
+            depth: 0.0,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Pattern {
     pub tracks: Vec<Track>, // 16 Tracks per pattern (Tonverk standard)
     pub bpm: f32,
     pub master_length: u32,
+    #[serde(default)]
+    pub tuning: Tuning, // Project-wide, used by every track's pitch
+    #[serde(default)]
+    pub key: Key, // Note entry and transposition snap to it
 }
 
 impl Default for Pattern {
@@ -146,6 +288,154 @@ impl Default for Pattern {
             tracks: Vec::new(),
             bpm: 120.0,
             master_length: 16,
+            tuning: Tuning::default(),
+            key: Key::default(),
+        }
+    }
+}
+
+/// How MIDI output plays the project tuning.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum MidiRetuning {
+    Off,       // Plain notes, the receiver's own tuning
+    #[default]
+    PitchBend, // Nearest note plus a per-channel pitch bend
+    Mts,       // MIDI Tuning Standard key retuning, sent when the tuning changes
+}
+
+/// Scala keyboard mapping (.kbm): which scale degree each MIDI key plays.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyboardMapping {
+    pub first_note: u8,           // Keys outside first..=last stay 12-TET
+    pub last_note: u8,
+    pub middle_note: u8,          // Key that plays scale degree 0
+    pub reference_note: u8,       // Key tuned to `reference_freq`
+    pub reference_freq: f64,      // Hz
+    pub octave_degree: usize,     // Degree the mapping repeats at
+    pub keys: Vec<Option<usize>>, // Degrees from the middle note on, None = unmapped; empty maps keys to consecutive degrees
+}
+
+impl Default for KeyboardMapping {
+    fn default() -> Self {
+        Self {
+            first_note: 0,
+            last_note: 127,
+            middle_note: 60,
+            reference_note: 69,
+            reference_freq: 440.0,
+            octave_degree: 12,
+            keys: Vec::new(),
+        }
+    }
+}
+
+/// Project tuning from a Scala scale (.scl) and keyboard mapping. Defaults to 12-TET at A440.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tuning {
+    pub name: String,      // Scale description
+    pub cents: Vec<f64>,   // Degrees 1..=n above the root; the last is the period (1200.0 = octave)
+    pub keyboard: KeyboardMapping,
+    #[serde(default)]
+    pub midi_retuning: MidiRetuning,
+    #[serde(default = "default_bend_range")]
+    pub midi_bend_range: u8, // Semitones, must match the receiver
+}
+
+fn default_bend_range() -> u8 {
+    2
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            name: "12-TET".to_string(),
+            cents: (1..=12).map(|degree| degree as f64 * 100.0).collect(),
+            keyboard: KeyboardMapping::default(),
+            midi_retuning: MidiRetuning::default(),
+            midi_bend_range: default_bend_range(),
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ScaleMode {
+    #[default]
+    Chromatic,
+    Major,
+    Minor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+    HarmonicMinor,
+    MelodicMinor,
+    MajorPentatonic,
+    MinorPentatonic,
+    Custom, // Uses `Key::custom`
+}
+
+/// Musical key of a pattern: a root and a scale.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Key {
+    pub root: u8, // Pitch class, 0 = C
+    pub mode: ScaleMode,
+    pub custom: [bool; 12], // Pitch classes above the root in a Custom scale; the root is always in
+}
+
+impl Default for Key {
+    fn default() -> Self {
+        Self { root: 0, mode: ScaleMode::default(), custom: [true; 12] }
+    }
+}
+
+pub const PATTERN_BANKS: usize = 8; // A–H
+pub const PATTERNS_PER_BANK: usize = 16;
+pub const PATTERN_SLOTS: usize = PATTERN_BANKS * PATTERNS_PER_BANK;
+
+/// A saved project: banks of patterns and the song arranging them.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Project {
+    pub patterns: Vec<Option<Pattern>>, // Slot A01 first, bank after bank; None until written
+    pub current: usize,                 // Slot being played and edited
+    #[serde(default)]
+    pub song: Song,
+}
+
+/// When a pattern selected during playback takes over from the playing one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PatternSwitch {
+    #[default]
+    EndOfPattern, // After the playing pattern's last step
+    Immediate,    // On the next step, from the new pattern's first step
+    NextBar,      // On the transport's next bar line
+}
+
+/// Song mode arrangement: rows of pattern slots played top to bottom instead of looping one pattern.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Song {
+    pub enabled: bool, // Playback follows the rows
+    pub rows: Vec<SongRow>,
+    pub loop_enabled: bool, // Jump back to `loop_start` after `loop_end`; otherwise playback stops after the last row
+    pub loop_start: usize,
+    pub loop_end: usize, // Row index, inclusive
+}
+
+/// One row of a song: a pattern played a number of times.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SongRow {
+    pub pattern: usize, // Pattern slot
+    pub repeats: u32,   // Passes through the pattern, at least 1
+    #[serde(default)]
+    pub length: Option<u32>, // Steps per pass instead of the pattern's length
+    #[serde(default)]
+    pub tempo: Option<f32>, // BPM while the row plays instead of the pattern's
+    #[serde(default)]
+    pub mutes: [bool; 16], // Tracks kept silent while the row plays
+}
+
+impl Default for SongRow {
+    fn default() -> Self {
+        Self { pattern: 0, repeats: 1, length: None, tempo: None, mutes: [false; 16] }
+    }
+}
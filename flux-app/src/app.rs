@@ -5,23 +5,186 @@ use leptos::ev::KeyboardEvent;
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::JsCast;
 
+use crate::ui::components::analyzer_view::AnalyzerView;
 use crate::ui::components::grid::Grid;
+use crate::ui::components::mixer::Mixer;
+use crate::shared::models::{Pattern, PatternSwitch, Project, Song, PATTERN_SLOTS};
 use crate::ui::components::toolbar::Toolbar;
 use crate::ui::state::PlaybackState;
 use crate::ui::tauri_detect::{detect_tauri, TauriCapabilities};
 
 #[derive(Serialize, Deserialize, Clone, Default, Debug)]
 struct AudioSnapshot {
+    sequence: u64, // Steps advanced by the engine, increases by 1 per step
     current_step: usize,
     is_playing: bool,
     triggered_tracks: Vec<bool>,
+    #[serde(default)]
+    pattern_slot: usize, // Bank slot playing
+    #[serde(default)]
+    queued_pattern: Option<usize>, // Bank slot waiting to take over
+    #[serde(default)]
+    song_row: Option<usize>, // Song row playing, None outside song mode
+}
+
+/// Indices of tracks that differ between two patterns.
+/// `None` when the change is structural (track count, tempo, length) and needs a full swap.
+fn changed_tracks(
+    previous: &crate::shared::models::Pattern,
+    next: &crate::shared::models::Pattern,
+) -> Option<Vec<usize>> {
+    if previous.tracks.len() != next.tracks.len()
+        || previous.bpm != next.bpm
+        || previous.master_length != next.master_length
+        || previous.tuning != next.tuning
+    {
+        return None;
+    }
+    Some(
+        (0..next.tracks.len())
+            .filter(|&i| previous.tracks[i] != next.tracks[i])
+            .collect(),
+    )
 }
 
 // Create a context for the step
-#[derive(Clone)]
+#[derive(Clone, Copy)]
 pub struct SequencerState {
     pub current_step: ReadSignal<usize>,
     pub selected_step: RwSignal<Option<(usize, usize)>>, // (track_id, step_idx)
+    pub subtracks: RwSignal<Vec<usize>>, // Subtrack shown and edited, per track
+}
+
+impl SequencerState {
+    /// Subtrack shown and edited on a track, 0 until switched.
+    pub fn subtrack(&self, track_id: usize) -> usize {
+        self.subtracks.with(|s| s.get(track_id).copied().unwrap_or(0))
+    }
+
+    pub fn set_subtrack(&self, track_id: usize, subtrack: usize) {
+        self.subtracks.update(|s| {
+            if s.len() <= track_id {
+                s.resize(track_id + 1, 0);
+            }
+            s[track_id] = subtrack;
+        });
+    }
+}
+
+/// Pattern banks and song of the project. The pattern signal holds the slot being played and
+/// edited; `patterns` keeps the others.
+#[derive(Clone, Copy)]
+pub struct ProjectState {
+    pub patterns: RwSignal<Vec<Option<Pattern>>>, // PATTERN_SLOTS long; the current slot's entry is refreshed when leaving it
+    pub current: RwSignal<usize>,
+    pub queued: RwSignal<Option<usize>>, // Slot waiting for its switch while playing
+    pub switch_mode: RwSignal<PatternSwitch>,
+    pub song: RwSignal<Song>,
+    pattern: ReadSignal<Pattern>,
+    set_pattern: WriteSignal<Pattern>,
+}
+
+impl ProjectState {
+    /// Whether a slot holds a pattern.
+    pub fn has_pattern(&self, slot: usize) -> bool {
+        slot == self.current.get() || self.patterns.with(|p| p.get(slot).is_some_and(Option::is_some))
+    }
+
+    /// Pick a pattern. It is sent to the engines and queued while playing, shown right away otherwise.
+    pub fn select(&self, slot: usize, playing: bool) {
+        if slot == self.current.get_untracked() || slot >= PATTERN_SLOTS {
+            return;
+        }
+        let pattern = self.stored_or_new(slot);
+        self.patterns.update(|p| p[slot] = Some(pattern.clone()));
+        let switch = if playing { self.switch_mode.get_untracked() } else { PatternSwitch::Immediate };
+        spawn_local(async move {
+            crate::ui::tauri::update_pattern(slot, pattern).await;
+            crate::ui::tauri::queue_pattern(slot, switch).await;
+        });
+        if playing {
+            self.queued.set(Some(slot));
+        } else {
+            self.show(slot);
+        }
+    }
+
+    /// Show and edit `slot`, keeping the current pattern in its slot.
+    pub fn show(&self, slot: usize) {
+        let current = self.current.get_untracked();
+        if slot == current || slot >= PATTERN_SLOTS {
+            return;
+        }
+        let next = self.stored_or_new(slot);
+        let previous = self.pattern.get_untracked();
+        self.patterns.update(|p| {
+            p[current] = Some(previous);
+            p[slot] = Some(next.clone());
+        });
+        self.current.set(slot);
+        self.queued.set(None);
+        self.set_pattern.set(next);
+    }
+
+    /// The project as saved, with the pattern being edited in its slot.
+    pub fn project(&self) -> Project {
+        let current = self.current.get_untracked();
+        let mut patterns = self.patterns.get_untracked();
+        patterns[current] = Some(self.pattern.get_untracked());
+        Project { patterns, current, song: self.song.get_untracked() }
+    }
+
+    /// Replace the project. The engines get every stored pattern and switch to the current one.
+    pub fn load(&self, project: Project) {
+        let Project { mut patterns, current, song } = project;
+        patterns.resize(PATTERN_SLOTS, None);
+        let current = current.min(PATTERN_SLOTS - 1);
+        let pattern = patterns[current].clone().unwrap_or_default();
+        let stored: Vec<_> = patterns
+            .iter()
+            .enumerate()
+            .filter(|(slot, _)| *slot != current)
+            .filter_map(|(slot, p)| p.clone().map(|p| (slot, p)))
+            .collect();
+        let current_pattern = pattern.clone();
+        spawn_local(async move {
+            for (slot, pattern) in stored {
+                crate::ui::tauri::update_pattern(slot, pattern).await;
+            }
+            // The engines' copy of the slot may be empty or from the old project
+            crate::ui::tauri::update_pattern(current, current_pattern).await;
+            crate::ui::tauri::queue_pattern(current, PatternSwitch::Immediate).await;
+        });
+        self.patterns.set(patterns);
+        self.current.set(current);
+        self.queued.set(None);
+        self.song.set(song);
+        self.set_pattern.set(pattern);
+    }
+
+    // New slots start empty, with the project's tempo, tuning and key
+    fn stored_or_new(&self, slot: usize) -> Pattern {
+        self.patterns.with_untracked(|p| p.get(slot).cloned().flatten()).unwrap_or_else(|| {
+            self.pattern.with_untracked(|p| Pattern {
+                bpm: p.bpm,
+                tuning: p.tuning.clone(),
+                key: p.key,
+                ..Pattern::default()
+            })
+        })
+    }
+}
+
+/// Latest track and master meter readings from the "audio-levels" event.
+#[derive(Clone, Copy)]
+pub struct AudioLevels {
+    pub levels: RwSignal<crate::services::audio::MeterLevels>,
+}
+
+/// Samples in the backend's sample pool, sorted by slot ID.
+#[derive(Clone, Copy)]
+pub struct SamplePool {
+    pub samples: RwSignal<Vec<crate::services::audio::SampleInfo>>,
 }
 
 #[component]
@@ -36,13 +199,28 @@ pub fn App() -> impl IntoView {
 
     // Create Pattern signal
     let (pattern_signal, set_pattern_signal) = signal(crate::shared::models::Pattern::default());
+    let project = ProjectState {
+        patterns: RwSignal::new(vec![None; PATTERN_SLOTS]),
+        current: RwSignal::new(0),
+        queued: RwSignal::new(None),
+        switch_mode: RwSignal::new(PatternSwitch::default()),
+        song: RwSignal::new(Song::default()),
+        pattern: pattern_signal,
+        set_pattern: set_pattern_signal,
+    };
+    provide_context(project);
 
     // Provide state to all children
-    provide_context(SequencerState { current_step, selected_step });
+    let subtracks = RwSignal::new(Vec::new());
+    provide_context(SequencerState { current_step, selected_step, subtracks });
     provide_context(pattern_signal);
     provide_context(set_pattern_signal);
     provide_context(playback_state);
     provide_context(set_playback_state);
+    let samples = RwSignal::new(Vec::new());
+    provide_context(SamplePool { samples });
+    let levels = RwSignal::new(Default::default());
+    provide_context(AudioLevels { levels });
 
     // ESC key handler to deselect step
     let handle_escape = move |ev: KeyboardEvent| {
@@ -54,18 +232,93 @@ pub fn App() -> impl IntoView {
     // Attach to window
     window_event_listener(ev::keydown, handle_escape);
 
+    // Ship every pattern edit to the audio kernel (only if Tauri available)
+    // Edited tracks are sent on their own, structural changes as a whole pattern
+    if tauri_capabilities.audio_enabled {
+        Effect::new(move |previous: Option<crate::shared::models::Pattern>| {
+            let pattern = pattern_signal.get();
+            let slot = project.current.get_untracked();
+            match previous.as_ref().and_then(|prev| changed_tracks(prev, &pattern)) {
+                Some(track_ids) => {
+                    for track_id in track_ids {
+                        let track = pattern.tracks[track_id].clone();
+                        spawn_local(async move {
+                            crate::ui::tauri::update_track(slot, track_id, track).await;
+                        });
+                    }
+                }
+                None => {
+                    let full_pattern = pattern.clone();
+                    spawn_local(async move {
+                        crate::ui::tauri::update_pattern(slot, full_pattern).await;
+                    });
+                }
+            }
+            pattern
+        });
+
+        // The song goes to the engines as a whole
+        Effect::new(move |_| {
+            let song = project.song.get();
+            spawn_local(async move {
+                crate::ui::tauri::update_song(song).await;
+            });
+        });
+    }
+
     // Setup Tauri Event Listener (only if Tauri available)
     if tauri_capabilities.events_enabled {
         Effect::new(move |_| {
             spawn_local(async move {
                 use crate::ui::tauri::safe_listen_event;
+                let last_sequence = StoredValue::new(None::<u64>);
                 safe_listen_event("playback-status", move |event: AudioSnapshot| {
                     let normalized_position = event.current_step % 16;
+                    // Steps the engine passed between two events (the sync thread polls at ~60 FPS)
+                    let skipped_steps = last_sequence
+                        .get_value()
+                        .map(|last| event.sequence.saturating_sub(last).saturating_sub(1) as usize)
+                        .unwrap_or(0);
+                    last_sequence.set_value(Some(event.sequence));
                     set_current_step.set(normalized_position);
                     set_playback_state.update(|state| {
                         state.is_playing = event.is_playing;
                         state.current_position = normalized_position;
+                        state.skipped_steps = skipped_steps;
                         state.triggered_tracks = event.triggered_tracks;
+                        state.song_row = event.song_row;
+                    });
+                    // The editor follows the engine's pattern switches while playing
+                    if event.is_playing && event.pattern_slot != project.current.get_untracked() {
+                        project.show(event.pattern_slot);
+                    }
+                    project.queued.set(event.queued_pattern);
+                }).await;
+            });
+        });
+
+        // Meters at UI rate
+        Effect::new(move |_| {
+            spawn_local(async move {
+                use crate::services::audio::MeterLevels;
+                use crate::ui::tauri::safe_listen_event;
+                safe_listen_event("audio-levels", move |event: MeterLevels| {
+                    levels.set(event);
+                }).await;
+            });
+        });
+
+        // Sample pool: initial list, then every new recording
+        Effect::new(move |_| {
+            spawn_local(async move {
+                use crate::services::audio::{list_samples, SampleInfo};
+                use crate::ui::tauri::safe_listen_event;
+                samples.set(list_samples().await);
+                safe_listen_event("sample-recorded", move |info: SampleInfo| {
+                    samples.update(|samples| {
+                        samples.retain(|s| s.id != info.id);
+                        samples.push(info);
+                        samples.sort_by_key(|s| s.id);
                     });
                 }).await;
             });
@@ -130,6 +383,16 @@ pub fn App() -> impl IntoView {
                     </div>
                     <Grid />
                 </section>
+
+                <section class="bg-zinc-900/50 rounded-lg p-4">
+                    <h2 class="text-sm font-medium text-zinc-400 uppercase tracking-wide mb-4">"Mixer"</h2>
+                    <Mixer />
+                </section>
+
+                <section class="bg-zinc-900/50 rounded-lg p-4">
+                    <h2 class="text-sm font-medium text-zinc-400 uppercase tracking-wide mb-4">"Analyzer"</h2>
+                    <AnalyzerView />
+                </section>
             </div>
         </main>
     }
@@ -1,3 +1,4 @@
+use crate::shared::models::Tuning;
 use crate::ui::tauri::{safe_invoke, TauriError};
 
 #[derive(serde::Serialize)]
@@ -24,3 +25,345 @@ pub async fn set_playback_state(playing: bool) {
         }
     }
 }
+
+/// Output device choice, mirrors the backend's `engine::device::AudioSettings`.
+/// `None` means the host/device default.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AudioSettings {
+    pub host: Option<String>,
+    pub device: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub buffer_size: Option<u32>,
+    pub input_device: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct ActiveDevice {
+    pub host: String,
+    pub device: String,
+    pub sample_rate: u32,
+    pub buffer_size: Option<u32>,
+    pub channels: u16,
+    pub input_device: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct DeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+    pub channels: u16,
+    pub sample_rates: Vec<u32>,
+    pub default_sample_rate: Option<u32>,
+    pub min_buffer_size: Option<u32>,
+    pub max_buffer_size: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct HostInfo {
+    pub name: String,
+    pub is_default: bool,
+    pub devices: Vec<DeviceInfo>,
+    pub input_devices: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamState {
+    Running,
+    Fallback,
+    Recovering,
+    Stopped,
+}
+
+/// Payload of the "audio-status" event.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct AudioStatus {
+    pub state: StreamState,
+    pub device: Option<ActiveDevice>,
+    pub message: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct SetAudioDeviceArgs {
+    settings: AudioSettings,
+}
+
+pub async fn list_audio_devices() -> Vec<HostInfo> {
+    match safe_invoke("list_audio_devices", js_sys::Object::new().into()).await {
+        Ok(result) => serde_wasm_bindgen::from_value(result).unwrap_or_else(|e| {
+            web_sys::console::error_1(&format!("Failed to deserialize audio devices: {:?}", e).into());
+            Vec::new()
+        }),
+        Err(TauriError::NotAvailable) => Vec::new(),
+        Err(TauriError::InvokeFailed(msg)) => {
+            web_sys::console::error_1(&format!("list_audio_devices failed: {}", msg).into());
+            Vec::new()
+        }
+    }
+}
+
+pub async fn get_audio_device() -> Option<ActiveDevice> {
+    match safe_invoke("get_audio_device", js_sys::Object::new().into()).await {
+        Ok(result) => serde_wasm_bindgen::from_value(result).unwrap_or_else(|e| {
+            web_sys::console::error_1(&format!("Failed to deserialize audio device: {:?}", e).into());
+            None
+        }),
+        Err(TauriError::NotAvailable) => None,
+        Err(TauriError::InvokeFailed(msg)) => {
+            web_sys::console::error_1(&format!("get_audio_device failed: {}", msg).into());
+            None
+        }
+    }
+}
+
+pub async fn get_audio_status() -> Option<AudioStatus> {
+    match safe_invoke("get_audio_status", js_sys::Object::new().into()).await {
+        Ok(result) => serde_wasm_bindgen::from_value(result)
+            .map_err(|e| {
+                web_sys::console::error_1(&format!("Failed to deserialize audio status: {:?}", e).into());
+            })
+            .ok(),
+        Err(TauriError::NotAvailable) => None,
+        Err(TauriError::InvokeFailed(msg)) => {
+            web_sys::console::error_1(&format!("get_audio_status failed: {}", msg).into());
+            None
+        }
+    }
+}
+
+/// Switch the output device. The error is returned for display in the settings panel.
+pub async fn set_audio_device(settings: AudioSettings) -> Result<ActiveDevice, String> {
+    let args = serde_wasm_bindgen::to_value(&SetAudioDeviceArgs { settings })
+        .map_err(|e| format!("Failed to serialize set_audio_device args: {:?}", e))?;
+
+    match safe_invoke("set_audio_device", args).await {
+        Ok(result) => serde_wasm_bindgen::from_value(result)
+            .map_err(|e| format!("Failed to deserialize audio device: {:?}", e)),
+        Err(TauriError::NotAvailable) => Err("Audio settings require the desktop app".to_string()),
+        Err(TauriError::InvokeFailed(msg)) => Err(msg),
+    }
+}
+
+/// Mirrors the backend's `engine::recorder::RecordSource`.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum RecordSource {
+    Input,
+    Master,
+    Track(usize),
+    Send(usize), // 0 = Reverb, 1 = Delay
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum RecordStart {
+    Now,
+    NextBar,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecordOptions {
+    pub source: RecordSource,
+    pub start: RecordStart,
+    pub bars: Option<u32>, // None records until stopped
+}
+
+/// A sample in the backend's pool, also the payload of the "sample-recorded" event.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct SampleInfo {
+    pub id: u16,
+    pub name: String,
+    pub frames: usize,
+    pub sample_rate: u32,
+    pub duration_secs: f32,
+}
+
+#[derive(serde::Serialize)]
+struct StartRecordingArgs {
+    options: RecordOptions,
+}
+
+#[derive(serde::Serialize)]
+struct DeleteSampleArgs {
+    id: u16,
+}
+
+pub async fn start_recording(options: RecordOptions) -> Result<(), String> {
+    let args = serde_wasm_bindgen::to_value(&StartRecordingArgs { options })
+        .map_err(|e| format!("Failed to serialize start_recording args: {:?}", e))?;
+
+    match safe_invoke("start_recording", args).await {
+        Ok(_) => Ok(()),
+        Err(TauriError::NotAvailable) => Err("Recording requires the desktop app".to_string()),
+        Err(TauriError::InvokeFailed(msg)) => Err(msg),
+    }
+}
+
+pub async fn stop_recording() {
+    match safe_invoke("stop_recording", js_sys::Object::new().into()).await {
+        Ok(_) | Err(TauriError::NotAvailable) => {}
+        Err(TauriError::InvokeFailed(msg)) => {
+            web_sys::console::error_1(&format!("stop_recording failed: {}", msg).into());
+        }
+    }
+}
+
+pub async fn list_samples() -> Vec<SampleInfo> {
+    match safe_invoke("list_samples", js_sys::Object::new().into()).await {
+        Ok(result) => serde_wasm_bindgen::from_value(result).unwrap_or_else(|e| {
+            web_sys::console::error_1(&format!("Failed to deserialize samples: {:?}", e).into());
+            Vec::new()
+        }),
+        Err(TauriError::NotAvailable) => Vec::new(),
+        Err(TauriError::InvokeFailed(msg)) => {
+            web_sys::console::error_1(&format!("list_samples failed: {}", msg).into());
+            Vec::new()
+        }
+    }
+}
+
+pub async fn delete_sample(id: u16) {
+    let args = match serde_wasm_bindgen::to_value(&DeleteSampleArgs { id }) {
+        Ok(v) => v,
+        Err(e) => {
+            web_sys::console::error_1(&format!("Failed to serialize delete_sample args: {:?}", e).into());
+            return;
+        }
+    };
+
+    match safe_invoke("delete_sample", args).await {
+        Ok(_) | Err(TauriError::NotAvailable) => {}
+        Err(TauriError::InvokeFailed(msg)) => {
+            web_sys::console::error_1(&format!("delete_sample failed: {}", msg).into());
+        }
+    }
+}
+
+/// Mirrors the backend's `engine::metrics::EngineMetrics`, also the "engine-metrics" event payload.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct EngineMetrics {
+    pub dsp_load: f32,
+    pub peak_load: f32,
+    pub xruns: u64,
+    pub overruns: u64,
+    pub active_voices: usize,
+    pub queue_depth: usize,
+}
+
+/// Metrics of the last 30 seconds, oldest first.
+pub async fn get_engine_metrics() -> Vec<EngineMetrics> {
+    match safe_invoke("get_engine_metrics", js_sys::Object::new().into()).await {
+        Ok(result) => serde_wasm_bindgen::from_value(result).unwrap_or_else(|e| {
+            web_sys::console::error_1(&format!("Failed to deserialize engine metrics: {:?}", e).into());
+            Vec::new()
+        }),
+        Err(TauriError::NotAvailable) => Vec::new(),
+        Err(TauriError::InvokeFailed(msg)) => {
+            web_sys::console::error_1(&format!("get_engine_metrics failed: {}", msg).into());
+            Vec::new()
+        }
+    }
+}
+
+/// Mirrors the backend's `engine::meters::MeterLevel`. Linear amplitude, 1.0 = 0 dBFS.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct MeterLevel {
+    pub peak: f32,
+    pub rms: f32,
+    pub hold: f32,
+}
+
+/// Payload of the "audio-levels" event.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct MeterLevels {
+    pub tracks: Vec<MeterLevel>,
+    pub master: MeterLevel,
+}
+
+/// Mirrors the backend's `engine::analyzer::SignalAnalysis`.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct SignalAnalysis {
+    pub scope: Vec<f32>,
+    pub spectrum: Vec<f32>, // dB per FFT bin, 0 Hz to Nyquist
+}
+
+/// Payload of the "analyzer-frame" event.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct AnalyzerFrame {
+    pub sample_rate: f32,
+    pub master: SignalAnalysis,
+    pub track: Option<usize>,
+    pub track_analysis: Option<SignalAnalysis>,
+}
+
+#[derive(serde::Serialize)]
+struct AnalyzerTrackArgs {
+    track: Option<usize>,
+}
+
+pub async fn set_analyzer_track(track: Option<usize>) {
+    let args = match serde_wasm_bindgen::to_value(&AnalyzerTrackArgs { track }) {
+        Ok(v) => v,
+        Err(e) => {
+            web_sys::console::error_1(&format!("Failed to serialize set_analyzer_track args: {:?}", e).into());
+            return;
+        }
+    };
+
+    match safe_invoke("set_analyzer_track", args).await {
+        Ok(_) | Err(TauriError::NotAvailable) => {}
+        Err(TauriError::InvokeFailed(msg)) => {
+            web_sys::console::error_1(&format!("set_analyzer_track failed: {}", msg).into());
+        }
+    }
+}
+
+/// Mirrors the backend's `engine::diagnostics::LogLevel`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Deserialize)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// Mirrors the backend's `engine::diagnostics::LogRecord`; the "log-records" event carries a batch.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct LogRecord {
+    pub timestamp_ms: u64, // Unix time
+    pub level: LogLevel,
+    pub source: String, // "Audio" or "Midi"
+    pub message: String,
+}
+
+/// Recent log records kept by the backend, oldest first.
+pub async fn get_recent_logs() -> Vec<LogRecord> {
+    match safe_invoke("get_recent_logs", js_sys::Object::new().into()).await {
+        Ok(result) => serde_wasm_bindgen::from_value(result).unwrap_or_else(|e| {
+            web_sys::console::error_1(&format!("Failed to deserialize log records: {:?}", e).into());
+            Vec::new()
+        }),
+        Err(TauriError::NotAvailable) => Vec::new(),
+        Err(TauriError::InvokeFailed(msg)) => {
+            web_sys::console::error_1(&format!("get_recent_logs failed: {}", msg).into());
+            Vec::new()
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")] // Tauri expects camelCase argument names
+struct LoadTuningArgs {
+    scl_path: String,
+    kbm_path: Option<String>,
+}
+
+/// Parse a Scala scale and optional keyboard mapping into a project tuning.
+pub async fn load_tuning(scl_path: String, kbm_path: Option<String>) -> Result<Tuning, String> {
+    let args = serde_wasm_bindgen::to_value(&LoadTuningArgs { scl_path, kbm_path })
+        .map_err(|e| format!("Failed to serialize load_tuning args: {:?}", e))?;
+
+    match safe_invoke("load_tuning", args).await {
+        Ok(result) => serde_wasm_bindgen::from_value(result).map_err(|e| format!("Failed to deserialize tuning: {:?}", e)),
+        Err(TauriError::NotAvailable) => Err("Loading tunings requires the desktop app".to_string()),
+        Err(TauriError::InvokeFailed(msg)) => Err(msg),
+    }
+}
@@ -0,0 +1,155 @@
+use crate::shared::models::{ChordKind, Key, ScaleMode, Voicing};
+
+pub const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+/// Scale choices in menu order, with their display names.
+pub const SCALE_MODES: [(ScaleMode, &str); 13] = [
+    (ScaleMode::Chromatic, "Chromatic"),
+    (ScaleMode::Major, "Major"),
+    (ScaleMode::Minor, "Minor"),
+    (ScaleMode::Dorian, "Dorian"),
+    (ScaleMode::Phrygian, "Phrygian"),
+    (ScaleMode::Lydian, "Lydian"),
+    (ScaleMode::Mixolydian, "Mixolydian"),
+    (ScaleMode::Locrian, "Locrian"),
+    (ScaleMode::HarmonicMinor, "Harmonic Minor"),
+    (ScaleMode::MelodicMinor, "Melodic Minor"),
+    (ScaleMode::MajorPentatonic, "Major Pentatonic"),
+    (ScaleMode::MinorPentatonic, "Minor Pentatonic"),
+    (ScaleMode::Custom, "Custom"),
+];
+
+pub fn mode_name(mode: ScaleMode) -> &'static str {
+    SCALE_MODES.iter().find(|(m, _)| *m == mode).map(|(_, name)| *name).unwrap_or("Chromatic")
+}
+
+pub fn mode_from_name(name: &str) -> ScaleMode {
+    SCALE_MODES.iter().find(|(_, n)| *n == name).map(|(mode, _)| *mode).unwrap_or_default()
+}
+
+/// Semitones above the root of each scale degree, ascending.
+pub fn intervals(key: &Key) -> Vec<u8> {
+    let preset: &[u8] = match key.mode {
+        ScaleMode::Chromatic => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+        ScaleMode::Major => &[0, 2, 4, 5, 7, 9, 11],
+        ScaleMode::Minor => &[0, 2, 3, 5, 7, 8, 10],
+        ScaleMode::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+        ScaleMode::Phrygian => &[0, 1, 3, 5, 7, 8, 10],
+        ScaleMode::Lydian => &[0, 2, 4, 6, 7, 9, 11],
+        ScaleMode::Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+        ScaleMode::Locrian => &[0, 1, 3, 5, 6, 8, 10],
+        ScaleMode::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+        ScaleMode::MelodicMinor => &[0, 2, 3, 5, 7, 9, 11],
+        ScaleMode::MajorPentatonic => &[0, 2, 4, 7, 9],
+        ScaleMode::MinorPentatonic => &[0, 3, 5, 7, 10],
+        ScaleMode::Custom => return (0..12).filter(|&i| i == 0 || key.custom[i as usize]).collect(),
+    };
+    preset.to_vec()
+}
+
+// Semitones from the root's pitch class up to the note's
+fn offset_from_root(key: &Key, note: i32) -> u8 {
+    (note - key.root as i32).rem_euclid(12) as u8
+}
+
+/// The closest note of the key, searching up for a positive `direction`, down for a negative one
+/// and both ways for 0 (ties go down). Stays within 0-127.
+pub fn snap(key: &Key, note: i32, direction: i32) -> u8 {
+    let note = note.clamp(0, 127);
+    let scale = intervals(key);
+    let fits = |n: &i32| scale.contains(&offset_from_root(key, *n));
+    let up = (note..=127).find(fits);
+    let down = (0..=note).rev().find(fits);
+    let snapped = match direction.signum() {
+        1 => up.or(down),
+        -1 => down.or(up),
+        _ => match (down, up) {
+            (Some(down), Some(up)) => Some(if up - note < note - down { up } else { down }),
+            (down, up) => down.or(up),
+        },
+    };
+    snapped.unwrap_or(note) as u8
+}
+
+/// Move a note by `steps` scale degrees, snapping it into the key first.
+pub fn transpose(key: &Key, note: u8, steps: i32) -> u8 {
+    let mut current = snap(key, note as i32, 0);
+    for _ in 0..steps.unsigned_abs() {
+        let next = snap(key, current as i32 + steps.signum(), steps.signum());
+        if next == current {
+            break; // Edge of the MIDI range
+        }
+        current = next;
+    }
+    current
+}
+
+/// Scale degree of a note, "1" for the root. Notes outside the key show as the degree below, sharpened.
+pub fn degree_label(key: &Key, note: u8) -> String {
+    let scale = intervals(key);
+    let offset = offset_from_root(key, note as i32);
+    match scale.iter().position(|&interval| interval == offset) {
+        Some(degree) => (degree + 1).to_string(),
+        None => {
+            let below = scale.iter().rposition(|&interval| interval < offset).unwrap_or(0);
+            format!("♯{}", below + 1)
+        }
+    }
+}
+
+/// Note name with octave, MIDI 60 = "C4".
+pub fn note_name(note: u8) -> String {
+    format!("{}{}", NOTE_NAMES[note as usize % 12], note as i32 / 12 - 1)
+}
+
+/// Chord choices in menu order, with their display names.
+pub const CHORD_KINDS: [(ChordKind, &str); 17] = [
+    (ChordKind::Single, "Single Note"),
+    (ChordKind::Major, "Major"),
+    (ChordKind::Minor, "Minor"),
+    (ChordKind::Diminished, "Dim"),
+    (ChordKind::Augmented, "Aug"),
+    (ChordKind::Sus2, "Sus2"),
+    (ChordKind::Sus4, "Sus4"),
+    (ChordKind::Power, "Power (5)"),
+    (ChordKind::Major7, "Maj7"),
+    (ChordKind::Minor7, "Min7"),
+    (ChordKind::Dominant7, "7"),
+    (ChordKind::HalfDiminished7, "Min7b5"),
+    (ChordKind::Diminished7, "Dim7"),
+    (ChordKind::Add9, "Add9"),
+    (ChordKind::Major9, "Maj9"),
+    (ChordKind::Minor9, "Min9"),
+    (ChordKind::Custom, "Custom"),
+];
+
+pub const VOICINGS: [(Voicing, &str); 4] = [
+    (Voicing::Close, "Close"),
+    (Voicing::Drop2, "Drop 2"),
+    (Voicing::Drop3, "Drop 3"),
+    (Voicing::Spread, "Spread"),
+];
+
+pub fn chord_kind_name(kind: ChordKind) -> &'static str {
+    CHORD_KINDS.iter().find(|(k, _)| *k == kind).map(|(_, name)| *name).unwrap_or("Single Note")
+}
+
+pub fn chord_kind_from_name(name: &str) -> ChordKind {
+    CHORD_KINDS.iter().find(|(_, n)| *n == name).map(|(kind, _)| *kind).unwrap_or_default()
+}
+
+pub fn voicing_name(voicing: Voicing) -> &'static str {
+    VOICINGS.iter().find(|(v, _)| *v == voicing).map(|(_, name)| *name).unwrap_or("Close")
+}
+
+pub fn voicing_from_name(name: &str) -> Voicing {
+    VOICINGS.iter().find(|(_, n)| *n == name).map(|(voicing, _)| *voicing).unwrap_or_default()
+}
+
+/// Custom chord intervals typed as semitones above the root, e.g. "3 7 10". Invalid entries are skipped.
+pub fn parse_intervals(text: &str) -> Vec<u8> {
+    text.split([' ', ','])
+        .filter_map(|part| part.trim().parse::<u8>().ok())
+        .filter(|&interval| interval <= 36)
+        .collect()
+}
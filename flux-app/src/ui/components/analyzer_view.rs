@@ -0,0 +1,188 @@
+use crate::services::audio::{set_analyzer_track, AnalyzerFrame, SignalAnalysis};
+use crate::shared::models::Pattern;
+use crate::ui::tauri::safe_listen_event;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+const MASTER_COLOR: &str = "rgb(34, 197, 94)"; // green-500
+const TRACK_COLOR: &str = "rgb(245, 158, 11)"; // amber-500
+const SPECTRUM_MIN_HZ: f64 = 20.0;
+const SPECTRUM_RANGE_DB: f64 = 90.0; // Bottom of the spectrum view, below 0 dBFS
+
+fn context_2d(canvas: &HtmlCanvasElement) -> Option<CanvasRenderingContext2d> {
+    canvas.get_context("2d").ok()??.dyn_into::<CanvasRenderingContext2d>().ok()
+}
+
+fn clear(ctx: &CanvasRenderingContext2d, w: f64, h: f64) {
+    ctx.set_fill_style_str("rgb(24, 24, 27)"); // zinc-900
+    ctx.fill_rect(0.0, 0.0, w, h);
+}
+
+fn draw_scope(canvas: &HtmlCanvasElement, frame: &AnalyzerFrame) {
+    let Some(ctx) = context_2d(canvas) else {
+        return;
+    };
+    let w = canvas.width() as f64;
+    let h = canvas.height() as f64;
+    clear(&ctx, w, h);
+
+    // Center line
+    ctx.set_stroke_style_str("rgba(63, 63, 70, 0.5)"); // zinc-700
+    ctx.set_line_width(1.0);
+    ctx.begin_path();
+    ctx.move_to(0.0, h / 2.0);
+    ctx.line_to(w, h / 2.0);
+    ctx.stroke();
+
+    let trace = |analysis: &SignalAnalysis, color: &str| {
+        if analysis.scope.is_empty() {
+            return;
+        }
+        let step = w / analysis.scope.len() as f64;
+        ctx.set_stroke_style_str(color);
+        ctx.set_line_width(1.5);
+        ctx.begin_path();
+        for (i, &sample) in analysis.scope.iter().enumerate() {
+            let x = i as f64 * step;
+            let y = h / 2.0 - (sample.clamp(-1.0, 1.0) as f64 * h / 2.0);
+            if i == 0 {
+                ctx.move_to(x, y);
+            } else {
+                ctx.line_to(x, y);
+            }
+        }
+        ctx.stroke();
+    };
+    trace(&frame.master, MASTER_COLOR);
+    if let Some(track) = &frame.track_analysis {
+        trace(track, TRACK_COLOR);
+    }
+}
+
+fn draw_spectrum(canvas: &HtmlCanvasElement, frame: &AnalyzerFrame) {
+    let Some(ctx) = context_2d(canvas) else {
+        return;
+    };
+    let w = canvas.width() as f64;
+    let h = canvas.height() as f64;
+    clear(&ctx, w, h);
+
+    let nyquist = frame.sample_rate as f64 / 2.0;
+    if nyquist <= SPECTRUM_MIN_HZ {
+        return;
+    }
+    // Log frequency axis from 20 Hz to Nyquist
+    let x_for = |hz: f64| (hz / SPECTRUM_MIN_HZ).ln() / (nyquist / SPECTRUM_MIN_HZ).ln() * w;
+
+    // Decade grid lines
+    ctx.set_stroke_style_str("rgba(63, 63, 70, 0.5)");
+    ctx.set_line_width(1.0);
+    ctx.begin_path();
+    for hz in [100.0, 1_000.0, 10_000.0] {
+        if hz < nyquist {
+            ctx.move_to(x_for(hz), 0.0);
+            ctx.line_to(x_for(hz), h);
+        }
+    }
+    ctx.stroke();
+
+    let curve = |analysis: &SignalAnalysis, color: &str| {
+        let bins = analysis.spectrum.len();
+        if bins == 0 {
+            return;
+        }
+        let bin_hz = nyquist / bins as f64;
+        ctx.set_stroke_style_str(color);
+        ctx.set_line_width(1.5);
+        ctx.begin_path();
+        let mut started = false;
+        for (bin, &db) in analysis.spectrum.iter().enumerate() {
+            let hz = bin as f64 * bin_hz;
+            if hz < SPECTRUM_MIN_HZ {
+                continue;
+            }
+            let x = x_for(hz);
+            let y = (-(db as f64) / SPECTRUM_RANGE_DB).clamp(0.0, 1.0) * h;
+            if started {
+                ctx.line_to(x, y);
+            } else {
+                ctx.move_to(x, y);
+                started = true;
+            }
+        }
+        ctx.stroke();
+    };
+    curve(&frame.master, MASTER_COLOR);
+    if let Some(track) = &frame.track_analysis {
+        curve(track, TRACK_COLOR);
+    }
+}
+
+/// Oscilloscope and spectrum of the master, optionally overlaid with one track.
+#[component]
+pub fn AnalyzerView() -> impl IntoView {
+    let pattern_signal = use_context::<ReadSignal<Pattern>>().expect("Pattern context not found");
+    let scope_ref = NodeRef::<leptos::html::Canvas>::new();
+    let spectrum_ref = NodeRef::<leptos::html::Canvas>::new();
+    let frame = RwSignal::new(AnalyzerFrame::default());
+    let track = RwSignal::new(None::<usize>);
+
+    Effect::new(move |_| {
+        spawn_local(async move {
+            safe_listen_event("analyzer-frame", move |event: AnalyzerFrame| {
+                frame.set(event);
+            }).await;
+        });
+    });
+
+    Effect::new(move |_| {
+        frame.with(|frame| {
+            if let Some(canvas) = scope_ref.get() {
+                draw_scope(&canvas, frame);
+            }
+            if let Some(canvas) = spectrum_ref.get() {
+                draw_spectrum(&canvas, frame);
+            }
+        });
+    });
+
+    let on_track_change = move |ev| {
+        let selected = event_target_value(&ev).parse().ok();
+        track.set(selected);
+        spawn_local(async move { set_analyzer_track(selected).await });
+    };
+
+    view! {
+        <div class="flex flex-col gap-2">
+            <div class="flex items-center gap-2">
+                <span class="text-[10px] font-medium uppercase tracking-tight text-zinc-400">"Overlay"</span>
+                <select
+                    prop:value=move || track.get().map(|t| t.to_string()).unwrap_or_default()
+                    on:change=on_track_change
+                    class="bg-zinc-800 text-zinc-50 text-xs rounded px-2 py-1 border border-zinc-700 focus:outline-none focus:ring-1 focus:ring-blue-500"
+                >
+                    <option value="">"Master only"</option>
+                    {move || pattern_signal.with(|p| (0..p.tracks.len()).map(|i| {
+                        view! { <option value=i.to_string()>{format!("Track {}", i + 1)}</option> }
+                    }).collect::<Vec<_>>())}
+                </select>
+            </div>
+            <div class="grid grid-cols-2 gap-2">
+                <canvas
+                    node_ref=scope_ref
+                    width="400"
+                    height="150"
+                    class="w-full h-32 rounded bg-zinc-900 border border-zinc-700"
+                />
+                <canvas
+                    node_ref=spectrum_ref
+                    width="400"
+                    height="150"
+                    class="w-full h-32 rounded bg-zinc-900 border border-zinc-700"
+                />
+            </div>
+        </div>
+    }
+}
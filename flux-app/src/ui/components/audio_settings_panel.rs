@@ -0,0 +1,227 @@
+use crate::services::audio::{
+    get_audio_device, list_audio_devices, set_audio_device, ActiveDevice, AudioSettings, HostInfo,
+};
+use leptos::ev;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+const BUFFER_SIZES: [u32; 6] = [64, 128, 256, 512, 1024, 2048];
+
+/// Labeled select for the settings panel. An empty value means "Default".
+#[component]
+fn SettingsSelect(
+    label: &'static str,
+    /// (value, display text) pairs
+    #[prop(into)]
+    options: Signal<Vec<(String, String)>>,
+    selected: RwSignal<String>,
+    #[prop(optional)]
+    on_change: Option<Callback<String>>,
+) -> impl IntoView {
+    view! {
+        <label class="flex items-center justify-between gap-4">
+            <span class="text-[10px] font-medium uppercase tracking-tight text-zinc-400">{label}</span>
+            <select
+                prop:value=move || selected.get()
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    selected.set(value.clone());
+                    if let Some(on_change) = on_change {
+                        on_change.run(value);
+                    }
+                }
+                class="w-56 bg-zinc-800 text-zinc-50 text-xs rounded px-2 py-1 border border-zinc-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-zinc-900"
+            >
+                <option value="">"Default"</option>
+                {move || options.get().into_iter().map(|(value, text)| {
+                    view! { <option value=value>{text}</option> }
+                }).collect::<Vec<_>>()}
+            </select>
+        </label>
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() { None } else { Some(value) }
+}
+
+/// Audio settings: host, output and input device, sample rate and buffer size.
+/// Applying rebuilds the stream without restarting; the choice is saved in the preferences.
+#[component]
+pub fn AudioSettingsPanel(visible: RwSignal<bool>) -> impl IntoView {
+    let hosts = RwSignal::new(Vec::<HostInfo>::new());
+    let active = RwSignal::new(None::<ActiveDevice>);
+    let host = RwSignal::new(String::new());
+    let device = RwSignal::new(String::new());
+    let sample_rate = RwSignal::new(String::new());
+    let buffer_size = RwSignal::new(String::new());
+    let input_device = RwSignal::new(String::new());
+    let error = RwSignal::new(None::<String>);
+    let applying = RwSignal::new(false);
+
+    // Refresh the device list every time the panel opens
+    Effect::new(move |_| {
+        if !visible.get() {
+            return;
+        }
+        spawn_local(async move {
+            let available = list_audio_devices().await;
+            let current = get_audio_device().await;
+            if let Some(current) = &current {
+                host.set(current.host.clone());
+                device.set(current.device.clone());
+                sample_rate.set(current.sample_rate.to_string());
+                buffer_size.set(current.buffer_size.map(|b| b.to_string()).unwrap_or_default());
+                input_device.set(current.input_device.clone().unwrap_or_default());
+            }
+            hosts.set(available);
+            active.set(current);
+            error.set(None);
+        });
+    });
+
+    let selected_host = move || {
+        hosts.with(|hosts| {
+            hosts
+                .iter()
+                .find(|h| h.name == host.get() || (host.get().is_empty() && h.is_default))
+                .cloned()
+        })
+    };
+
+    let selected_device = move || {
+        selected_host().and_then(|h| {
+            h.devices
+                .into_iter()
+                .find(|d| d.name == device.get() || (device.get().is_empty() && d.is_default))
+        })
+    };
+
+    let host_options = Signal::derive(move || {
+        hosts.with(|hosts| hosts.iter().map(|h| (h.name.clone(), h.name.clone())).collect())
+    });
+
+    let device_options = Signal::derive(move || {
+        selected_host()
+            .map(|h| h.devices.iter().map(|d| (d.name.clone(), d.name.clone())).collect())
+            .unwrap_or_default()
+    });
+
+    let input_device_options = Signal::derive(move || {
+        selected_host()
+            .map(|h| h.input_devices.iter().map(|name| (name.clone(), name.clone())).collect())
+            .unwrap_or_default()
+    });
+
+    let sample_rate_options = Signal::derive(move || {
+        selected_device()
+            .map(|d| d.sample_rates.iter().map(|r| (r.to_string(), format!("{} Hz", r))).collect())
+            .unwrap_or_default()
+    });
+
+    let buffer_size_options = Signal::derive(move || {
+        let device = selected_device();
+        let min = device.as_ref().and_then(|d| d.min_buffer_size).unwrap_or(0);
+        let max = device.as_ref().and_then(|d| d.max_buffer_size).unwrap_or(u32::MAX);
+        BUFFER_SIZES
+            .iter()
+            .filter(|&&size| min <= size && size <= max)
+            .map(|size| (size.to_string(), format!("{} frames", size)))
+            .collect()
+    });
+
+    // Device names are per host, sample rates per device
+    let on_host_change = Callback::new(move |_: String| {
+        device.set(String::new());
+        input_device.set(String::new());
+        sample_rate.set(String::new());
+    });
+    let on_device_change = Callback::new(move |_: String| sample_rate.set(String::new()));
+
+    let apply = move |_| {
+        let settings = AudioSettings {
+            host: non_empty(host.get_untracked()),
+            device: non_empty(device.get_untracked()),
+            sample_rate: sample_rate.get_untracked().parse().ok(),
+            buffer_size: buffer_size.get_untracked().parse().ok(),
+            input_device: non_empty(input_device.get_untracked()),
+        };
+        applying.set(true);
+        spawn_local(async move {
+            match set_audio_device(settings).await {
+                Ok(device) => {
+                    active.set(Some(device));
+                    error.set(None);
+                }
+                Err(msg) => {
+                    web_sys::console::error_1(&format!("set_audio_device failed: {}", msg).into());
+                    error.set(Some(msg));
+                }
+            }
+            applying.set(false);
+        });
+    };
+
+    let handle_escape = move |ev: ev::KeyboardEvent| {
+        if ev.key() == "Escape" && visible.get() {
+            visible.set(false);
+        }
+    };
+    window_event_listener(ev::keydown, handle_escape);
+
+    view! {
+        <Show when=move || visible.get()>
+            <div
+                class="fixed inset-0 bg-black/50 flex items-center justify-center z-50"
+                on:click=move |_| visible.set(false)
+            >
+                <div
+                    class="bg-zinc-900 border border-zinc-700 rounded-lg p-6 w-[28rem] flex flex-col gap-3"
+                    on:click=|e| e.stop_propagation()
+                >
+                    <h3 class="text-lg font-medium text-zinc-50">"Audio Settings"</h3>
+
+                    <SettingsSelect label="Host" options=host_options selected=host on_change=on_host_change />
+                    <SettingsSelect label="Output Device" options=device_options selected=device on_change=on_device_change />
+                    <SettingsSelect label="Input Device" options=input_device_options selected=input_device />
+                    <SettingsSelect label="Sample Rate" options=sample_rate_options selected=sample_rate />
+                    <SettingsSelect label="Buffer Size" options=buffer_size_options selected=buffer_size />
+
+                    <div class="text-[10px] font-mono text-zinc-500">
+                        {move || match active.get() {
+                            Some(d) => format!(
+                                "ACTIVE: {} / {} - {} Hz, {} ch, {}",
+                                d.host,
+                                d.device,
+                                d.sample_rate,
+                                d.channels,
+                                d.buffer_size.map(|b| format!("{} frames", b)).unwrap_or_else(|| "default buffer".to_string()),
+                            ),
+                            None => "NO OUTPUT DEVICE".to_string(),
+                        }}
+                    </div>
+
+                    {move || error.get().map(|msg| view! {
+                        <div class="text-xs text-red-400">{msg}</div>
+                    })}
+
+                    <div class="flex gap-2 justify-end">
+                        <button
+                            class="px-4 py-2 bg-zinc-800 hover:bg-zinc-700 rounded text-sm text-zinc-300 transition-colors"
+                            on:click=move |_| visible.set(false)
+                        >
+                            "Close"
+                        </button>
+                        <button
+                            class="px-4 py-2 bg-amber-600 hover:bg-amber-500 disabled:opacity-50 rounded text-sm text-white transition-colors"
+                            disabled=move || applying.get()
+                            on:click=apply
+                        >
+                            {move || if applying.get() { "Applying..." } else { "Apply" }}
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
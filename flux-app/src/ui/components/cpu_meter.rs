@@ -0,0 +1,88 @@
+use crate::services::audio::{get_engine_metrics, EngineMetrics};
+use crate::ui::tauri::safe_listen_event;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+const HISTORY_LEN: usize = 300; // Matches the backend history (30 s)
+const SPARKLINE_WIDTH: f32 = 60.0;
+const SPARKLINE_HEIGHT: f32 = 20.0;
+
+/// DSP load of the audio callback with a 30 second history.
+/// Turns amber from 70 % and red on an overrun or xrun.
+#[component]
+pub fn CpuMeter() -> impl IntoView {
+    let history = RwSignal::new(Vec::<EngineMetrics>::new());
+
+    // Backend history first, then follow the live metrics
+    Effect::new(move |_| {
+        spawn_local(async move {
+            history.set(get_engine_metrics().await);
+            safe_listen_event("engine-metrics", move |metrics: EngineMetrics| {
+                history.update(|history| {
+                    if history.len() == HISTORY_LEN {
+                        history.remove(0);
+                    }
+                    history.push(metrics);
+                });
+            }).await;
+        });
+    });
+
+    let latest = move || history.with(|h| h.last().copied().unwrap_or_default());
+
+    // Dropouts within the visible history
+    let has_dropouts = move || {
+        history.with(|h| match (h.first(), h.last()) {
+            (Some(first), Some(last)) => last.xruns + last.overruns > first.xruns + first.overruns,
+            _ => false,
+        })
+    };
+
+    let load_class = move || {
+        if has_dropouts() {
+            "text-red-400"
+        } else if latest().dsp_load >= 70.0 {
+            "text-amber-400"
+        } else {
+            "text-zinc-400"
+        }
+    };
+
+    let points = move || {
+        history.with(|h| {
+            h.iter()
+                .enumerate()
+                .map(|(i, m)| {
+                    let x = i as f32 / (HISTORY_LEN - 1) as f32 * SPARKLINE_WIDTH;
+                    let y = SPARKLINE_HEIGHT - m.dsp_load.clamp(0.0, 100.0) / 100.0 * SPARKLINE_HEIGHT;
+                    format!("{:.1},{:.1}", x, y)
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+    };
+
+    let title = move || {
+        let m = latest();
+        format!(
+            "DSP load {:.0} % (peak {:.0} %)\nXruns: {}  Overruns: {}\nVoices: {}  Queued commands: {}",
+            m.dsp_load, m.peak_load, m.xruns, m.overruns, m.active_voices, m.queue_depth
+        )
+    };
+
+    view! {
+        <div class="h-10 px-2 flex items-center gap-2 bg-zinc-900 rounded-md" title=title>
+            <span class="text-[10px] font-medium text-zinc-500">"CPU"</span>
+            <svg
+                class="w-[60px] h-5"
+                viewBox=format!("0 0 {} {}", SPARKLINE_WIDTH, SPARKLINE_HEIGHT)
+                preserveAspectRatio="none"
+            >
+                <polyline points=points fill="none" stroke="#22C55E" stroke-width="1" />
+            </svg>
+            <span class=move || format!("w-9 text-right text-xs font-mono {}", load_class())>
+                {move || format!("{:.0}%", latest().dsp_load)}
+            </span>
+        </div>
+    }
+}
@@ -0,0 +1,137 @@
+use crate::services::audio::{get_recent_logs, LogLevel, LogRecord};
+use crate::ui::tauri::safe_listen_event;
+use leptos::ev;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+const LOG_LEN: usize = 500; // Matches the backend's recent log buffer
+
+fn level_class(level: LogLevel) -> &'static str {
+    match level {
+        LogLevel::Debug => "text-zinc-500",
+        LogLevel::Info => "text-zinc-300",
+        LogLevel::Warn => "text-amber-400",
+        LogLevel::Error => "text-red-400",
+    }
+}
+
+/// Local wall-clock time with milliseconds, e.g. "14:03:07.215".
+fn format_time(timestamp_ms: u64) -> String {
+    let date = js_sys::Date::new(&(timestamp_ms as f64).into());
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        date.get_hours(),
+        date.get_minutes(),
+        date.get_seconds(),
+        date.get_milliseconds()
+    )
+}
+
+fn parse_level(value: &str) -> LogLevel {
+    match value {
+        "Info" => LogLevel::Info,
+        "Warn" => LogLevel::Warn,
+        "Error" => LogLevel::Error,
+        _ => LogLevel::Debug,
+    }
+}
+
+/// Live log of the audio and MIDI threads, newest last.
+/// Records stream in while the panel is closed, so it opens with recent history.
+#[component]
+pub fn DebugPanel(visible: RwSignal<bool>) -> impl IntoView {
+    let records = RwSignal::new(Vec::<LogRecord>::new());
+    let min_level = RwSignal::new(LogLevel::Info);
+    let paused = RwSignal::new(false);
+
+    // Backend history first, then follow the live log
+    Effect::new(move |_| {
+        spawn_local(async move {
+            records.set(get_recent_logs().await);
+            safe_listen_event("log-records", move |batch: Vec<LogRecord>| {
+                if paused.get_untracked() {
+                    return;
+                }
+                records.update(|records| {
+                    records.extend(batch);
+                    let excess = records.len().saturating_sub(LOG_LEN);
+                    records.drain(..excess);
+                });
+            }).await;
+        });
+    });
+
+    let handle_escape = move |ev: ev::KeyboardEvent| {
+        if ev.key() == "Escape" && visible.get() {
+            visible.set(false);
+        }
+    };
+    window_event_listener(ev::keydown, handle_escape);
+
+    view! {
+        <Show when=move || visible.get()>
+            <div
+                class="fixed inset-0 bg-black/50 flex items-center justify-center z-50"
+                on:click=move |_| visible.set(false)
+            >
+                <div
+                    class="bg-zinc-900 border border-zinc-700 rounded-lg p-6 w-[48rem] flex flex-col gap-3"
+                    on:click=|e| e.stop_propagation()
+                >
+                    <div class="flex items-center justify-between">
+                        <h3 class="text-lg font-medium text-zinc-50">"Debug Log"</h3>
+                        <div class="flex items-center gap-2">
+                            <select
+                                prop:value=move || format!("{:?}", min_level.get())
+                                on:change=move |ev| min_level.set(parse_level(&event_target_value(&ev)))
+                                class="bg-zinc-800 text-zinc-50 text-xs rounded px-2 py-1 border border-zinc-700 focus:outline-none focus:ring-1 focus:ring-blue-500"
+                            >
+                                <option value="Debug">"DEBUG"</option>
+                                <option value="Info">"INFO"</option>
+                                <option value="Warn">"WARN"</option>
+                                <option value="Error">"ERROR"</option>
+                            </select>
+                            <button
+                                on:click=move |_| paused.update(|p| *p = !*p)
+                                class="px-3 py-1 bg-zinc-800 hover:bg-zinc-700 rounded text-xs font-medium text-zinc-300 transition-colors"
+                            >
+                                {move || if paused.get() { "RESUME" } else { "PAUSE" }}
+                            </button>
+                            <button
+                                on:click=move |_| records.set(Vec::new())
+                                class="px-3 py-1 bg-zinc-800 hover:bg-zinc-700 rounded text-xs font-medium text-zinc-300 transition-colors"
+                            >
+                                "CLEAR"
+                            </button>
+                        </div>
+                    </div>
+
+                    <div class="h-96 overflow-y-auto rounded bg-zinc-950 border border-zinc-800 p-2 font-mono text-[11px] leading-4">
+                        {move || {
+                            let min = min_level.get();
+                            records.with(|records| {
+                                records
+                                    .iter()
+                                    .filter(|r| r.level >= min)
+                                    .map(|r| {
+                                        view! {
+                                            <div class=level_class(r.level)>
+                                                {format!(
+                                                    "{} {:<5} {:<5} {}",
+                                                    format_time(r.timestamp_ms),
+                                                    format!("{:?}", r.level).to_uppercase(),
+                                                    r.source.to_uppercase(),
+                                                    r.message
+                                                )}
+                                            </div>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>()
+                            })
+                        }}
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
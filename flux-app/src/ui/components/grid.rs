@@ -1,13 +1,16 @@
 use super::confirm_dialog::ConfirmDialog;
+use super::level_meter::LevelMeter;
 use super::machine_selector::MachineSelector;
+use super::pattern_bank::PatternBank;
 use super::playhead_indicator::PlayheadIndicator;
 use super::remove_track_button::RemoveTrackButton;
 use super::step_badge::StepBadge;
 use super::step_editor_sidebar::StepEditorSidebar;
+use super::subtrack_switcher::SubtrackSwitcher;
 use super::track_controls::TrackControls;
 use super::velocity_lanes::VelocityLanes;
 use crate::ui::components::grid_step::GridStep;
-use crate::ui::state::GridUIState;
+use crate::ui::state::{GridUIState, NoteLabelMode};
 use leptos::prelude::*;
 
 #[component]
@@ -20,11 +23,15 @@ pub fn Grid() -> impl IntoView {
         .expect("Pattern context not found");
     let set_pattern_signal = use_context::<WriteSignal<crate::shared::models::Pattern>>()
         .expect("Pattern write signal not found");
+    let levels = use_context::<crate::app::AudioLevels>()
+        .expect("AudioLevels context not found")
+        .levels;
 
     // Create GridUIState signal and provide context
     let grid_ui_state = signal(GridUIState::default());
     provide_context(grid_ui_state.0); // Provide read signal
     provide_context(grid_ui_state.1); // Provide write signal
+    provide_context(RwSignal::new(NoteLabelMode::default()));
 
     // State for confirmation dialog
     let (show_confirm_dialog, set_show_confirm_dialog) = signal::<Option<usize>>(None);
@@ -67,17 +74,24 @@ pub fn Grid() -> impl IntoView {
         let is_playing = playback.is_playing;
 
         if is_playing {
-            // Check each track for active steps at current position
+            // Check each track for active steps at current position, and at the steps
+            // skipped since the last update so their trigs still flash
+            let skipped = playback.skipped_steps.min(15);
             pattern_signal.with(|pattern| {
-                for (track_idx, track) in pattern.tracks.iter().enumerate() {
-                    if let Some(subtrack) = track.subtracks.get(0) {
-                        if let Some(step) = subtrack.steps.get(pos) {
-                            if step.trig_type != crate::shared::models::TrigType::None {
-                                // Step triggered! Add to GridUIState
-                                grid_ui_state.1.update(|state| {
-                                    state.add_trigger(track_idx, pos, current_time);
-                                });
-                            }
+                for back in 0..=skipped {
+                    let pos = (pos + 16 - back) % 16;
+                    for (track_idx, track) in pattern.tracks.iter().enumerate() {
+                        // Any subtrack's trig flashes the step
+                        let triggered = track
+                            .subtracks
+                            .iter()
+                            .filter_map(|subtrack| subtrack.steps.get(pos))
+                            .any(|step| step.trig_type != crate::shared::models::TrigType::None);
+                        if triggered {
+                            // Step triggered! Add to GridUIState
+                            grid_ui_state.1.update(|state| {
+                                state.add_trigger(track_idx, pos, current_time);
+                            });
                         }
                     }
                 }
@@ -114,6 +128,8 @@ pub fn Grid() -> impl IntoView {
 
     view! {
         <div class="sequencer-grid">
+            <PatternBank />
+
             // NEW: 2-column layout with sidebar
             <div class="flex gap-4">
                 // Left: Step Editor Sidebar
@@ -123,8 +139,8 @@ pub fn Grid() -> impl IntoView {
                 <div class="flex-1">
                     // CSS Grid container for step grid + velocity lanes alignment
                     // Grid columns: [track-labels] [step-1] [step-2] ... [step-16]
-                    // Track label column: 100px (fits RemoveButton + T1 + MachineSelector)
-                    <div style="display: grid; grid-template-columns: 100px repeat(16, 40px); gap: 2px; position: relative;">
+                    // Track label column: 112px (fits RemoveButton + T1 + MachineSelector + LevelMeter)
+                    <div style="display: grid; grid-template-columns: 112px repeat(16, 40px); gap: 2px; position: relative;">
                         // Step Grid Rows
                         <For
                             each=move || {
@@ -132,6 +148,9 @@ pub fn Grid() -> impl IntoView {
                             }
                             key=|track_idx| *track_idx
                             children=move |track_idx| {
+                                let level = Signal::derive(move || {
+                                    levels.with(|l| l.tracks.get(track_idx).copied().unwrap_or_default())
+                                });
                                 view! {
                                     // Track label cell
                                     <div class="h-10 flex items-center justify-start gap-1 px-1" style=format!("grid-column: 1; grid-row: {};", track_idx + 1)>
@@ -143,6 +162,7 @@ pub fn Grid() -> impl IntoView {
                                             {format!("T{}", track_idx + 1)}
                                         </div>
                                         <MachineSelector track_idx=track_idx />
+                                        <LevelMeter level=level />
                                     </div>
 
                                     // 16 step cells
@@ -176,6 +196,9 @@ pub fn Grid() -> impl IntoView {
                         />
                     </div>
 
+                    // Subtrack tabs of the selected track
+                    <SubtrackSwitcher />
+
                     // Velocity lanes (uses same grid template)
                     <VelocityLanes />
 
@@ -1,6 +1,7 @@
 use crate::app::SequencerState;
 use crate::shared::models::Pattern;
-use crate::ui::state::GridUIState;
+use crate::shared::scale;
+use crate::ui::state::{GridUIState, NoteLabelMode};
 use leptos::prelude::*;
 
 #[component]
@@ -14,15 +15,15 @@ pub fn GridStep(track_idx: usize, step_idx: usize) -> impl IntoView {
     let playback_state = use_context::<ReadSignal<crate::ui::state::PlaybackState>>()
         .expect("PlaybackState context not found");
 
-    // Hardcode to Subtrack 0 for this milestone
-    let subtrack_id = 0;
+    // The subtrack currently shown for this track
+    let subtrack_id = move || sequencer_state.subtrack(track_idx);
 
     // Compute derived state - check if this step has an active trigger
     let is_active = Signal::derive(move || {
         pattern_signal.with(|p| {
             p.tracks
                 .get(track_idx)
-                .and_then(|t| t.subtracks.get(subtrack_id))
+                .and_then(|t| t.subtracks.get(subtrack_id()))
                 .and_then(|st| st.steps.get(step_idx))
                 .map(|s| s.trig_type != crate::shared::models::TrigType::None)
                 .unwrap_or(false)
@@ -105,12 +106,44 @@ pub fn GridStep(track_idx: usize, step_idx: usize) -> impl IntoView {
         )
     });
 
+    let label_mode =
+        use_context::<RwSignal<NoteLabelMode>>().expect("NoteLabelMode context not found");
+
+    // Active steps show a dot, their MIDI note or their scale degree
+    let label = Signal::derive(move || {
+        let mode = label_mode.get();
+        if !is_active.get() {
+            return "○".to_string();
+        }
+        if mode == NoteLabelMode::Dots {
+            return "●".to_string();
+        }
+        pattern_signal.with(|p| {
+            let note = p
+                .tracks
+                .get(track_idx)
+                .and_then(|t| t.subtracks.get(subtrack_id()))
+                .and_then(|st| st.steps.get(step_idx))
+                .map(|s| s.note)
+                .unwrap_or(60);
+            match mode {
+                NoteLabelMode::Degrees => scale::degree_label(&p.key, note),
+                _ => note.to_string(),
+            }
+        })
+    });
+
     // Derive span class signal
     let span_classes = Signal::derive(move || {
+        let size = if is_active.get() && label_mode.get() != NoteLabelMode::Dots {
+            "text-xs font-mono"
+        } else {
+            "text-lg"
+        };
         if is_active.get() {
-            "text-white text-lg"
+            format!("text-white {}", size)
         } else {
-            "text-zinc-600 text-lg"
+            format!("text-zinc-600 {}", size)
         }
     });
 
@@ -127,7 +160,7 @@ pub fn GridStep(track_idx: usize, step_idx: usize) -> impl IntoView {
             if let Some(step) = pattern
                 .tracks
                 .get_mut(track_idx)
-                .and_then(|t| t.subtracks.get_mut(subtrack_id))
+                .and_then(|t| t.subtracks.get_mut(subtrack_id()))
                 .and_then(|st| st.steps.get_mut(step_idx))
             {
                 // Toggle between None (inactive) and Note (active)
@@ -146,9 +179,9 @@ pub fn GridStep(track_idx: usize, step_idx: usize) -> impl IntoView {
             on:click=on_click
             on:dblclick=on_dblclick
         >
-            // Visual indicator: filled circle for active, empty for inactive
+            // Visual indicator: empty circle for inactive, label for active
             <span class=move || span_classes.get()>
-                {move || if is_active.get() { "●" } else { "○" }}
+                {move || label.get()}
             </span>
         </button>
     }
@@ -0,0 +1,48 @@
+use crate::services::audio::MeterLevel;
+use leptos::prelude::*;
+
+const METER_FLOOR_DB: f32 = -60.0;
+
+/// Meter height for a linear amplitude, 0.0 at -60 dB and 1.0 at 0 dBFS.
+pub fn meter_fraction(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        return 0.0;
+    }
+    let db = 20.0 * amplitude.log10();
+    ((db - METER_FLOOR_DB) / -METER_FLOOR_DB).clamp(0.0, 1.0)
+}
+
+/// Vertical peak/RMS meter with a hold line. Turns red when the peak clips.
+#[component]
+pub fn LevelMeter(
+    #[prop(into)]
+    level: Signal<MeterLevel>,
+    /// Tailwind size classes, e.g. "w-1.5 h-8"
+    #[prop(default = "w-1.5 h-8")]
+    class: &'static str,
+) -> impl IntoView {
+    let percent = |amplitude: f32| format!("{:.1}%", meter_fraction(amplitude) * 100.0);
+
+    view! {
+        <div class=format!("relative overflow-hidden rounded-sm bg-zinc-800 {}", class)>
+            <div
+                class="absolute bottom-0 left-0 right-0 bg-green-700"
+                style=move || format!("height: {};", percent(level.get().peak))
+            ></div>
+            <div
+                class="absolute bottom-0 left-0 right-0 bg-green-400"
+                style=move || format!("height: {};", percent(level.get().rms))
+            ></div>
+            <div
+                class=move || {
+                    if level.get().hold >= 1.0 {
+                        "absolute left-0 right-0 h-px bg-red-500"
+                    } else {
+                        "absolute left-0 right-0 h-px bg-zinc-300"
+                    }
+                }
+                style=move || format!("bottom: {};", percent(level.get().hold))
+            ></div>
+        </div>
+    }
+}
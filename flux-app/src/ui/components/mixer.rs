@@ -0,0 +1,52 @@
+use crate::app::AudioLevels;
+use crate::shared::models::Pattern;
+use crate::ui::components::level_meter::LevelMeter;
+use leptos::prelude::*;
+
+/// Live post-FX level of every track plus the master.
+#[component]
+pub fn Mixer() -> impl IntoView {
+    let pattern_signal = use_context::<ReadSignal<Pattern>>().expect("Pattern context not found");
+    let levels = use_context::<AudioLevels>().expect("AudioLevels context not found").levels;
+
+    let hold_db = |hold: f32| {
+        if hold <= 0.0 {
+            "-inf".to_string()
+        } else {
+            format!("{:.1}", 20.0 * hold.log10())
+        }
+    };
+
+    view! {
+        <div class="flex items-end gap-3">
+            <For
+                each=move || pattern_signal.with(|p| (0..p.tracks.len()).collect::<Vec<_>>())
+                key=|track_idx| *track_idx
+                children=move |track_idx| {
+                    let level = Signal::derive(move || {
+                        levels.with(|l| l.tracks.get(track_idx).copied().unwrap_or_default())
+                    });
+                    view! {
+                        <div class="flex flex-col items-center gap-1">
+                            <span class="text-[9px] font-mono text-zinc-500">
+                                {move || hold_db(level.get().hold)}
+                            </span>
+                            <LevelMeter level=level class="w-2 h-24" />
+                            <span class="text-[10px] text-zinc-400">{format!("T{}", track_idx + 1)}</span>
+                        </div>
+                    }
+                }
+            />
+
+            <div class="w-px h-24 bg-zinc-700 mx-1"></div>
+
+            <div class="flex flex-col items-center gap-1">
+                <span class="text-[9px] font-mono text-zinc-500">
+                    {move || hold_db(levels.get().master.hold)}
+                </span>
+                <LevelMeter level=Signal::derive(move || levels.get().master) class="w-3 h-24" />
+                <span class="text-[10px] font-medium text-zinc-300">"MST"</span>
+            </div>
+        </div>
+    }
+}
@@ -1,18 +1,31 @@
+pub mod analyzer_view;
+pub mod audio_settings_panel;
 pub mod collapsible_section;
 pub mod confirm_dialog;
+pub mod cpu_meter;
+pub mod debug_panel;
 pub mod form_controls;
 pub mod grid;
 pub mod grid_step;
 pub mod lfo_designer;
 pub mod lfo_draw;
+pub mod level_meter;
 pub mod machine_selector;
+pub mod mod_envelope_editor;
+pub mod mixer;
+pub mod mod_matrix_editor;
+pub mod pattern_bank;
 pub mod playhead_indicator;
 pub mod remove_track_button;
+pub mod song_panel;
 pub mod step_badge;
 pub mod step_editor_sidebar;
 pub mod step_inspector;
+pub mod subtrack_switcher;
 pub mod toolbar;
 pub mod track_controls;
+pub mod track_sample_selector;
+pub mod tuning_panel;
 pub mod velocity_lanes;
 pub use machine_selector::MachineSelector;
 pub use velocity_lanes::VelocityLanes;
@@ -0,0 +1,155 @@
+use crate::app::SequencerState;
+use crate::shared::models::{EnvelopeMode, ModEnvelope, Pattern};
+use crate::ui::components::form_controls::*;
+use leptos::prelude::*;
+
+/// Editor for one of the selected track's two modulation envelopes (track-level).
+#[component]
+pub fn ModEnvelopeEditor(
+    /// Envelope index (0 or 1)
+    index: usize,
+) -> impl IntoView {
+    let sequencer_state =
+        use_context::<SequencerState>().expect("SequencerState context not found");
+    let selected_step = sequencer_state.selected_step;
+    let pattern_signal = use_context::<ReadSignal<Pattern>>().expect("Pattern context not found");
+    let set_pattern_signal =
+        use_context::<WriteSignal<Pattern>>().expect("Pattern write signal not found");
+
+    let track_id = move || selected_step.get().map(|(t, _)| t).unwrap_or(0);
+
+    let envelope = move || {
+        pattern_signal.with(|p| {
+            p.tracks
+                .get(track_id())
+                .and_then(|t| t.mod_envelopes.get(index))
+                .copied()
+                .unwrap_or_default()
+        })
+    };
+
+    let update = move |f: &dyn Fn(&mut ModEnvelope)| {
+        let track_id = track_id();
+        set_pattern_signal.update(|p| {
+            if let Some(env) = p
+                .tracks
+                .get_mut(track_id)
+                .and_then(|t| t.mod_envelopes.get_mut(index))
+            {
+                f(env);
+            }
+        });
+    };
+
+    let is_adsr = move || envelope().mode == EnvelopeMode::Adsr;
+
+    view! {
+        <div class="flex flex-col gap-0.5 py-1 border-b border-zinc-800/50 last:border-b-0">
+            <InlineParam>
+                <ParamLabel text=if index == 0 { "Env 1 Mode" } else { "Env 2 Mode" } locked=Signal::derive(|| false) />
+                <Dropdown
+                    options=vec![("AD", "AD"), ("ADSR", "ADSR")]
+                    selected=Signal::derive(move || if is_adsr() { "ADSR" } else { "AD" }.to_string())
+                    on_change=move |val: String| {
+                        let mode = if val == "ADSR" { EnvelopeMode::Adsr } else { EnvelopeMode::Ad };
+                        update(&|env| env.mode = mode);
+                    }
+                />
+            </InlineParam>
+
+            <InlineParam>
+                <ParamLabel text="Attack" locked=Signal::derive(|| false) />
+                <NumberInput
+                    min="0"
+                    max="1"
+                    step="0.01"
+                    value=Signal::derive(move || format!("{:.2}", envelope().attack))
+                    on_input=move |val: f64| {
+                        let attack = val.clamp(0.0, 1.0) as f32;
+                        update(&|env| env.attack = attack);
+                    }
+                />
+            </InlineParam>
+
+            <InlineParam>
+                <ParamLabel text="Decay" locked=Signal::derive(|| false) />
+                <NumberInput
+                    min="0"
+                    max="1"
+                    step="0.01"
+                    value=Signal::derive(move || format!("{:.2}", envelope().decay))
+                    on_input=move |val: f64| {
+                        let decay = val.clamp(0.0, 1.0) as f32;
+                        update(&|env| env.decay = decay);
+                    }
+                />
+            </InlineParam>
+
+            <Show when=is_adsr>
+                <InlineParam>
+                    <ParamLabel text="Sustain" locked=Signal::derive(|| false) />
+                    <NumberInput
+                        min="0"
+                        max="1"
+                        step="0.01"
+                        value=Signal::derive(move || format!("{:.2}", envelope().sustain))
+                        on_input=move |val: f64| {
+                            let sustain = val.clamp(0.0, 1.0) as f32;
+                            update(&|env| env.sustain = sustain);
+                        }
+                    />
+                </InlineParam>
+
+                <InlineParam>
+                    <ParamLabel text="Release" locked=Signal::derive(|| false) />
+                    <NumberInput
+                        min="0"
+                        max="1"
+                        step="0.01"
+                        value=Signal::derive(move || format!("{:.2}", envelope().release))
+                        on_input=move |val: f64| {
+                            let release = val.clamp(0.0, 1.0) as f32;
+                            update(&|env| env.release = release);
+                        }
+                    />
+                </InlineParam>
+            </Show>
+
+            <InlineParam>
+                <ParamLabel text="Destination" locked=Signal::derive(|| false) />
+                <Dropdown
+                    options=vec![
+                        ("2", "Filter Freq"),
+                        ("3", "Resonance"),
+                        ("4", "Drive"),
+                        ("1", "Decay"),
+                        ("5", "Sustain"),
+                        ("8", "Volume"),
+                        ("9", "Pan"),
+                        ("6", "Reverb"),
+                        ("7", "Delay"),
+                    ]
+                    selected=Signal::derive(move || envelope().destination.to_string())
+                    on_change=move |val: String| {
+                        let destination = val.parse::<u8>().unwrap_or(2);
+                        update(&|env| env.destination = destination);
+                    }
+                />
+            </InlineParam>
+
+            <InlineParam>
+                <ParamLabel text="Depth" locked=Signal::derive(|| false) />
+                <NumberInput
+                    min="-1"
+                    max="1"
+                    step="0.01"
+                    value=Signal::derive(move || format!("{:.2}", envelope().depth))
+                    on_input=move |val: f64| {
+                        let depth = val.clamp(-1.0, 1.0) as f32;
+                        update(&|env| env.depth = depth);
+                    }
+                />
+            </InlineParam>
+        </div>
+    }
+}
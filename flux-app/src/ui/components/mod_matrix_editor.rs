@@ -0,0 +1,230 @@
+use crate::app::SequencerState;
+use crate::shared::models::{ModSlot, ModSource, Pattern};
+use crate::ui::components::form_controls::*;
+use leptos::prelude::*;
+
+// Mirror the backend's engine::domain
+const MAX_MOD_SLOTS: usize = 16;
+const PARAM_MOD_DEPTH_BASE: usize = 64; // Depth P-Lock of slot N is p_locks[64 + N]
+const DEPTH_LOCK_THRESHOLD: f32 = 0.001;
+
+fn source_key(source: ModSource) -> &'static str {
+    match source {
+        ModSource::Lfo(_) => "lfo",
+        ModSource::Envelope(0) => "env1",
+        ModSource::Envelope(_) => "env2",
+        ModSource::Velocity => "velocity",
+        ModSource::Note => "note",
+        ModSource::Random => "random",
+        ModSource::StepPosition => "step",
+    }
+}
+
+fn source_from_key(key: &str) -> ModSource {
+    match key {
+        "env1" => ModSource::Envelope(0),
+        "env2" => ModSource::Envelope(1),
+        "velocity" => ModSource::Velocity,
+        "note" => ModSource::Note,
+        "random" => ModSource::Random,
+        "step" => ModSource::StepPosition,
+        _ => ModSource::Lfo(0),
+    }
+}
+
+/// Routing editor for the selected track's mod matrix.
+/// Source, destination and depth are track-level; "Step Lock" P-Locks the depth on the selected step.
+#[component]
+pub fn ModMatrixEditor() -> impl IntoView {
+    let sequencer_state =
+        use_context::<SequencerState>().expect("SequencerState context not found");
+    let selected_step = sequencer_state.selected_step;
+    let pattern_signal = use_context::<ReadSignal<Pattern>>().expect("Pattern context not found");
+    let set_pattern_signal =
+        use_context::<WriteSignal<Pattern>>().expect("Pattern write signal not found");
+
+    let track_id = move || selected_step.get().map(|(t, _)| t).unwrap_or(0);
+
+    let slot_count = Signal::derive(move || {
+        pattern_signal.with(|p| p.tracks.get(track_id()).map(|t| t.mod_matrix.len()).unwrap_or(0))
+    });
+
+    let slot = move |slot_idx: usize| {
+        pattern_signal.with(|p| {
+            p.tracks
+                .get(track_id())
+                .and_then(|t| t.mod_matrix.get(slot_idx))
+                .copied()
+                .unwrap_or_default()
+        })
+    };
+
+    let update_slot = move |slot_idx: usize, f: &dyn Fn(&mut ModSlot)| {
+        let track_id = track_id();
+        set_pattern_signal.update(|p| {
+            if let Some(slot) = p
+                .tracks
+                .get_mut(track_id)
+                .and_then(|t| t.mod_matrix.get_mut(slot_idx))
+            {
+                f(slot);
+            }
+        });
+    };
+
+    let depth_lock = move |slot_idx: usize| {
+        let (track_id, step_idx) = selected_step.get()?;
+        pattern_signal.with(|p| {
+            p.tracks
+                .get(track_id)
+                .and_then(|t| t.subtracks.get(sequencer_state.subtrack(track_id)))
+                .and_then(|st| st.steps.get(step_idx))
+                .and_then(|s| s.p_locks[PARAM_MOD_DEPTH_BASE + slot_idx])
+        })
+    };
+
+    // Same rule as the sound parameters: a lock equal to the track value is removed
+    let on_lock_input = move |slot_idx: usize, val: f64| {
+        let Some((track_id, step_idx)) = selected_step.get() else {
+            return;
+        };
+        let clamped = val.clamp(-1.0, 1.0) as f32;
+        set_pattern_signal.update(|p| {
+            if let Some(track) = p.tracks.get_mut(track_id) {
+                let track_depth = track.mod_matrix.get(slot_idx).map(|s| s.depth).unwrap_or(0.0);
+                if let Some(step) = track
+                    .subtracks
+                    .get_mut(sequencer_state.subtrack(track_id))
+                    .and_then(|st| st.steps.get_mut(step_idx))
+                {
+                    step.p_locks[PARAM_MOD_DEPTH_BASE + slot_idx] =
+                        if (clamped - track_depth).abs() > DEPTH_LOCK_THRESHOLD {
+                            Some(clamped)
+                        } else {
+                            None
+                        };
+                }
+            }
+        });
+    };
+
+    let on_add = move |_| {
+        let track_id = track_id();
+        set_pattern_signal.update(|p| {
+            if let Some(track) = p.tracks.get_mut(track_id) {
+                if track.mod_matrix.len() < MAX_MOD_SLOTS {
+                    track.mod_matrix.push(ModSlot::default());
+                }
+            }
+        });
+    };
+
+    let on_remove = move |slot_idx: usize| {
+        let track_id = track_id();
+        set_pattern_signal.update(|p| {
+            if let Some(track) = p.tracks.get_mut(track_id) {
+                if slot_idx >= track.mod_matrix.len() {
+                    return;
+                }
+                track.mod_matrix.remove(slot_idx);
+                // Depth locks follow their slot
+                for step in track.subtracks.iter_mut().flat_map(|st| st.steps.iter_mut()) {
+                    let locks = &mut step.p_locks[PARAM_MOD_DEPTH_BASE..PARAM_MOD_DEPTH_BASE + MAX_MOD_SLOTS];
+                    locks[slot_idx..].rotate_left(1);
+                    locks[MAX_MOD_SLOTS - 1] = None;
+                }
+            }
+        });
+    };
+
+    view! {
+        <div class="flex flex-col gap-1">
+            {move || {
+                (0..slot_count.get()).map(|slot_idx| {
+                    view! {
+                        <div class="flex flex-col gap-0.5 py-1 border-b border-zinc-800/50 last:border-b-0">
+                            <div class="flex items-center gap-0.5">
+                                <Dropdown
+                                    options=vec![
+                                        ("lfo", "LFO 1"),
+                                        ("env1", "Env 1"),
+                                        ("env2", "Env 2"),
+                                        ("velocity", "Velocity"),
+                                        ("note", "Note"),
+                                        ("random", "Random"),
+                                        ("step", "Step Pos"),
+                                    ]
+                                    selected=Signal::derive(move || source_key(slot(slot_idx).source).to_string())
+                                    on_change=move |val: String| {
+                                        update_slot(slot_idx, &|s| s.source = source_from_key(&val));
+                                    }
+                                />
+                                <span class="text-[10px] text-zinc-500">"→"</span>
+                                <Dropdown
+                                    options=vec![
+                                        ("2", "Filter Freq"),
+                                        ("3", "Resonance"),
+                                        ("4", "Drive"),
+                                        ("1", "Decay"),
+                                        ("5", "Sustain"),
+                                        ("8", "Volume"),
+                                        ("9", "Pan"),
+                                        ("6", "Reverb"),
+                                        ("7", "Delay"),
+                                    ]
+                                    selected=Signal::derive(move || slot(slot_idx).destination.to_string())
+                                    on_change=move |val: String| {
+                                        let destination = val.parse::<u8>().unwrap_or(2);
+                                        update_slot(slot_idx, &|s| s.destination = destination);
+                                    }
+                                />
+                                <button
+                                    on:click=move |_| on_remove(slot_idx)
+                                    class="ml-auto w-4 h-4 flex items-center justify-center text-zinc-500 hover:text-red-400 hover:bg-red-500/10 transition-colors rounded text-sm font-bold"
+                                    title="Remove slot"
+                                >
+                                    "×"
+                                </button>
+                            </div>
+                            <InlineParam>
+                                <ParamLabel text="Depth" locked=Signal::derive(|| false) />
+                                <NumberInput
+                                    min="-1"
+                                    max="1"
+                                    step="0.01"
+                                    value=Signal::derive(move || format!("{:.2}", slot(slot_idx).depth))
+                                    on_input=move |val: f64| {
+                                        let depth = val.clamp(-1.0, 1.0) as f32;
+                                        update_slot(slot_idx, &|s| s.depth = depth);
+                                    }
+                                />
+                            </InlineParam>
+                            <InlineParam>
+                                <ParamLabel
+                                    text="Step Lock"
+                                    locked=Signal::derive(move || depth_lock(slot_idx).is_some())
+                                />
+                                <NumberInput
+                                    min="-1"
+                                    max="1"
+                                    step="0.01"
+                                    value=Signal::derive(move || {
+                                        format!("{:.2}", depth_lock(slot_idx).unwrap_or_else(|| slot(slot_idx).depth))
+                                    })
+                                    on_input=move |val: f64| on_lock_input(slot_idx, val)
+                                />
+                            </InlineParam>
+                        </div>
+                    }
+                }).collect::<Vec<_>>()
+            }}
+            <button
+                on:click=on_add
+                disabled=move || slot_count.get() >= MAX_MOD_SLOTS
+                class="text-[10px] text-zinc-400 hover:text-zinc-100 hover:bg-zinc-800/50 disabled:opacity-20 disabled:cursor-not-allowed rounded px-2 py-1 transition-colors"
+            >
+                "+ ADD SLOT"
+            </button>
+        </div>
+    }
+}
@@ -0,0 +1,100 @@
+use crate::app::ProjectState;
+use crate::shared::models::{PatternSwitch, PATTERNS_PER_BANK, PATTERN_BANKS};
+use crate::ui::state::PlaybackState;
+use leptos::prelude::*;
+
+const BANK_NAMES: [&str; PATTERN_BANKS] = ["A", "B", "C", "D", "E", "F", "G", "H"];
+
+/// Slot name as shown on the bank buttons, e.g. "A01" or "H16".
+pub fn slot_name(slot: usize) -> String {
+    let bank = BANK_NAMES.get(slot / PATTERNS_PER_BANK).copied().unwrap_or("?");
+    format!("{}{:02}", bank, slot % PATTERNS_PER_BANK + 1)
+}
+
+fn switch_value(switch: PatternSwitch) -> &'static str {
+    match switch {
+        PatternSwitch::EndOfPattern => "end",
+        PatternSwitch::Immediate => "now",
+        PatternSwitch::NextBar => "bar",
+    }
+}
+
+fn parse_switch(value: &str) -> PatternSwitch {
+    match value {
+        "now" => PatternSwitch::Immediate,
+        "bar" => PatternSwitch::NextBar,
+        _ => PatternSwitch::EndOfPattern,
+    }
+}
+
+/// Pattern banks A–H with 16 patterns each. Picking a pattern while playing queues it until
+/// its switch is due; the queued pattern blinks until the engine takes it over.
+#[component]
+pub fn PatternBank() -> impl IntoView {
+    let project = use_context::<ProjectState>().expect("ProjectState context not found");
+    let playback_state = use_context::<ReadSignal<PlaybackState>>().expect("PlaybackState context not found");
+    let bank = RwSignal::new(0usize); // Bank shown
+
+    // Follow the playing pattern to its bank
+    Effect::new(move |_| bank.set(project.current.get() / PATTERNS_PER_BANK));
+
+    view! {
+        <div class="flex items-center gap-1 mb-3">
+            <span class="text-[10px] font-medium uppercase tracking-tight text-zinc-400 mr-1">
+                {move || format!("Pattern {}", slot_name(project.current.get()))}
+            </span>
+            {BANK_NAMES.iter().enumerate().map(|(index, name)| {
+                let holds_current = move || project.current.get() / PATTERNS_PER_BANK == index;
+                view! {
+                    <button
+                        class=move || if bank.get() == index {
+                            "w-6 h-6 rounded text-[10px] font-mono bg-zinc-600 text-white"
+                        } else if holds_current() {
+                            "w-6 h-6 rounded text-[10px] font-mono bg-zinc-800 text-blue-400 hover:bg-zinc-700"
+                        } else {
+                            "w-6 h-6 rounded text-[10px] font-mono bg-zinc-800 text-zinc-400 hover:bg-zinc-700"
+                        }
+                        title=format!("Show bank {}", name)
+                        on:click=move |_| bank.set(index)
+                    >
+                        {*name}
+                    </button>
+                }
+            }).collect::<Vec<_>>()}
+            <div class="w-2"></div>
+            {(0..PATTERNS_PER_BANK).map(|index| {
+                let slot = move || bank.get() * PATTERNS_PER_BANK + index;
+                view! {
+                    <button
+                        class=move || {
+                            let slot = slot();
+                            if project.current.get() == slot {
+                                "w-7 h-6 rounded text-[10px] font-mono bg-blue-500 text-white"
+                            } else if project.queued.get() == Some(slot) {
+                                "w-7 h-6 rounded text-[10px] font-mono bg-amber-500 text-black animate-pulse"
+                            } else if project.has_pattern(slot) {
+                                "w-7 h-6 rounded text-[10px] font-mono bg-zinc-700 text-zinc-200 hover:bg-zinc-600"
+                            } else {
+                                "w-7 h-6 rounded text-[10px] font-mono bg-zinc-800 text-zinc-500 hover:bg-zinc-700"
+                            }
+                        }
+                        title=move || format!("Play and edit pattern {}", slot_name(slot()))
+                        on:click=move |_| project.select(slot(), playback_state.get_untracked().is_playing)
+                    >
+                        {format!("{:02}", index + 1)}
+                    </button>
+                }
+            }).collect::<Vec<_>>()}
+            <select
+                prop:value=move || switch_value(project.switch_mode.get())
+                on:change=move |ev| project.switch_mode.set(parse_switch(&event_target_value(&ev)))
+                class="ml-2 h-6 bg-zinc-800 text-zinc-300 text-[10px] rounded px-1 border border-zinc-700 focus:outline-none focus:ring-2 focus:ring-blue-500"
+                title="When a pattern picked during playback takes over"
+            >
+                <option value="end">"END OF PATTERN"</option>
+                <option value="bar">"NEXT BAR"</option>
+                <option value="now">"IMMEDIATE"</option>
+            </select>
+        </div>
+    }
+}
@@ -13,12 +13,11 @@ pub fn RemoveTrackButton(
     let set_pattern_signal = use_context::<WriteSignal<Pattern>>()
         .expect("Pattern write signal not found");
 
-    // Check if this track has any data (active steps)
+    // Check if this track has any data (active steps on any subtrack)
     let has_data = move || {
         pattern_signal.with(|pattern| {
             pattern.tracks.get(track_idx)
-                .and_then(|t| t.subtracks.get(0))
-                .map(|st| st.steps.iter().any(|s| s.trig_type != TrigType::None))
+                .map(|t| t.subtracks.iter().flat_map(|st| st.steps.iter()).any(|s| s.trig_type != TrigType::None))
                 .unwrap_or(false)
         })
     };
@@ -68,6 +67,15 @@ pub fn do_remove_track(track_idx: usize, set_pattern_signal: WriteSignal<Pattern
         }
     });
 
+    // Subtrack choices follow their tracks
+    if let Some(sequencer_state) = use_context::<crate::app::SequencerState>() {
+        sequencer_state.subtracks.update(|subtracks| {
+            if track_idx < subtracks.len() {
+                subtracks.remove(track_idx);
+            }
+        });
+    }
+
     // Clear selected step if it became invalid
     if let Some(selected_step) = use_context::<RwSignal<Option<(usize, usize)>>>() {
         if let Some((selected_track, _)) = selected_step.get() {
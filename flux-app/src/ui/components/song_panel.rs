@@ -0,0 +1,258 @@
+use crate::app::ProjectState;
+use crate::shared::models::{Pattern, Song, SongRow, PATTERN_SLOTS};
+use crate::ui::components::pattern_bank::slot_name;
+use crate::ui::state::PlaybackState;
+use leptos::ev;
+use leptos::prelude::*;
+
+const TRACK_COUNT: usize = 16; // Mute toggles per row
+
+// Parse an optional number field; empty clears the override
+fn parse_override<T: std::str::FromStr>(value: &str) -> Option<T> {
+    value.trim().parse().ok()
+}
+
+/// Song mode arrangement: rows of pattern slots with repeats, length and tempo overrides, mutes
+/// and loop points. The song is saved with the project.
+#[component]
+pub fn SongPanel(visible: RwSignal<bool>) -> impl IntoView {
+    let pattern_signal = use_context::<ReadSignal<Pattern>>().expect("Pattern context not found");
+    let project = use_context::<ProjectState>().expect("ProjectState context not found");
+    let song = project.song;
+    let playback_state = use_context::<ReadSignal<PlaybackState>>().expect("PlaybackState context not found");
+
+    let update_song = move |f: &dyn Fn(&mut Song)| song.update(|song| f(song));
+    let update_row = move |row: usize, f: &dyn Fn(&mut SongRow)| {
+        song.update(|song| {
+            if let Some(row) = song.rows.get_mut(row) {
+                f(row);
+            }
+        });
+    };
+    let row_count = move || song.with(|song| song.rows.len());
+
+    // New rows repeat the last one's pattern, the first one plays the pattern being edited
+    let add_row = move |_: ev::MouseEvent| {
+        let current = project.current.get_untracked();
+        update_song(&|song| {
+            let pattern = song.rows.last().map_or(current, |row| row.pattern);
+            let whole_song_loops = song.loop_end + 1 >= song.rows.len();
+            song.rows.push(SongRow { pattern, ..SongRow::default() });
+            if whole_song_loops {
+                song.loop_end = song.rows.len() - 1;
+            }
+        });
+    };
+
+    let remove_row = move |row: usize| {
+        update_song(&|song| {
+            if row < song.rows.len() {
+                song.rows.remove(row);
+            }
+            let last = song.rows.len().saturating_sub(1);
+            song.loop_start = song.loop_start.min(last);
+            song.loop_end = song.loop_end.min(last);
+        });
+    };
+
+    let handle_escape = move |ev: ev::KeyboardEvent| {
+        if ev.key() == "Escape" && visible.get() {
+            visible.set(false);
+        }
+    };
+    window_event_listener(ev::keydown, handle_escape);
+
+    let field_class = "w-14 bg-zinc-800 text-zinc-50 text-xs rounded px-1 py-0.5 border border-zinc-700 focus:outline-none focus:ring-2 focus:ring-blue-500";
+
+    view! {
+        <Show when=move || visible.get()>
+            <div
+                class="fixed inset-0 bg-black/50 flex items-center justify-center z-50"
+                on:click=move |_| visible.set(false)
+            >
+                <div
+                    class="bg-zinc-900 border border-zinc-700 rounded-lg p-6 w-[48rem] max-h-[80vh] flex flex-col gap-3"
+                    on:click=|e| e.stop_propagation()
+                >
+                    <h3 class="text-lg font-medium text-zinc-50">"Song"</h3>
+
+                    <div class="flex items-center gap-4">
+                        <label class="flex items-center gap-2">
+                            <input
+                                type="checkbox"
+                                prop:checked=move || song.with(|song| song.enabled)
+                                on:change=move |ev| {
+                                    let enabled = event_target_checked(&ev);
+                                    update_song(&|song| song.enabled = enabled);
+                                }
+                            />
+                            <span class="text-[10px] font-medium uppercase tracking-tight text-zinc-400">"Song Mode"</span>
+                        </label>
+                        <label class="flex items-center gap-2">
+                            <input
+                                type="checkbox"
+                                prop:checked=move || song.with(|song| song.loop_enabled)
+                                on:change=move |ev| {
+                                    let enabled = event_target_checked(&ev);
+                                    update_song(&|song| song.loop_enabled = enabled);
+                                }
+                            />
+                            <span class="text-[10px] font-medium uppercase tracking-tight text-zinc-400">"Loop Rows"</span>
+                        </label>
+                        <input
+                            type="number"
+                            min="1"
+                            prop:value=move || song.with(|song| (song.loop_start + 1).to_string())
+                            on:change=move |ev| {
+                                if let Ok(row) = event_target_value(&ev).parse::<usize>() {
+                                    update_song(&|song| song.loop_start = row.saturating_sub(1).min(song.loop_end));
+                                }
+                            }
+                            disabled=move || song.with(|song| !song.loop_enabled)
+                            class=field_class
+                            title="First row of the loop"
+                        />
+                        <span class="text-[10px] text-zinc-500">"TO"</span>
+                        <input
+                            type="number"
+                            min="1"
+                            prop:value=move || song.with(|song| (song.loop_end + 1).to_string())
+                            on:change=move |ev| {
+                                if let Ok(row) = event_target_value(&ev).parse::<usize>() {
+                                    update_song(&|song| {
+                                        song.loop_end = row.saturating_sub(1).clamp(song.loop_start, song.rows.len().saturating_sub(1));
+                                    });
+                                }
+                            }
+                            disabled=move || song.with(|song| !song.loop_enabled)
+                            class=field_class
+                            title="Last row of the loop; playback stops after the last row without a loop"
+                        />
+                    </div>
+
+                    <div class="flex flex-col gap-1 overflow-y-auto">
+                        <div class="flex items-center gap-2 text-[10px] font-medium uppercase tracking-tight text-zinc-500">
+                            <span class="w-6">"#"</span>
+                            <span class="w-14">"Pattern"</span>
+                            <span class="w-14">"Repeats"</span>
+                            <span class="w-14">"Length"</span>
+                            <span class="w-14">"BPM"</span>
+                            <span>"Mutes"</span>
+                        </div>
+                        {move || (0..row_count()).map(|row| {
+                            let value = move |f: fn(&SongRow) -> String| {
+                                song.with(|song| song.rows.get(row).map(f).unwrap_or_default())
+                            };
+                            let playing = move || {
+                                let state = playback_state.get();
+                                state.is_playing && state.song_row == Some(row)
+                            };
+                            view! {
+                                <div class=move || if playing() {
+                                    "flex items-center gap-2 rounded bg-blue-500/20"
+                                } else {
+                                    "flex items-center gap-2 rounded"
+                                }>
+                                    <span class="w-6 text-xs font-mono text-zinc-400">{row + 1}</span>
+                                    <select
+                                        prop:value=move || value(|r| r.pattern.to_string())
+                                        on:change=move |ev| {
+                                            if let Ok(pattern) = event_target_value(&ev).parse::<usize>() {
+                                                update_row(row, &|r| r.pattern = pattern.min(PATTERN_SLOTS - 1));
+                                            }
+                                        }
+                                        class=field_class
+                                        title="Pattern slot played by the row"
+                                    >
+                                        {(0..PATTERN_SLOTS).map(|slot| view! {
+                                            <option value=slot.to_string()>{slot_name(slot)}</option>
+                                        }).collect::<Vec<_>>()}
+                                    </select>
+                                    <input
+                                        type="number"
+                                        min="1"
+                                        prop:value=move || value(|r| r.repeats.to_string())
+                                        on:change=move |ev| {
+                                            if let Ok(repeats) = event_target_value(&ev).parse::<u32>() {
+                                                update_row(row, &|r| r.repeats = repeats.max(1));
+                                            }
+                                        }
+                                        class=field_class
+                                        title="Passes through the pattern"
+                                    />
+                                    <input
+                                        type="number"
+                                        min="1"
+                                        placeholder="16"
+                                        prop:value=move || value(|r| r.length.map(|l| l.to_string()).unwrap_or_default())
+                                        on:change=move |ev| {
+                                            let length = parse_override::<u32>(&event_target_value(&ev)).map(|l| l.max(1));
+                                            update_row(row, &|r| r.length = length);
+                                        }
+                                        class=field_class
+                                        title="Steps per pass, empty for the pattern's length"
+                                    />
+                                    <input
+                                        type="number"
+                                        min="20"
+                                        max="300"
+                                        placeholder=move || pattern_signal.with(|p| p.bpm.to_string())
+                                        prop:value=move || value(|r| r.tempo.map(|t| t.to_string()).unwrap_or_default())
+                                        on:change=move |ev| {
+                                            let tempo = parse_override::<f32>(&event_target_value(&ev)).map(|t| t.clamp(20.0, 300.0));
+                                            update_row(row, &|r| r.tempo = tempo);
+                                        }
+                                        class=field_class
+                                        title="Tempo while the row plays, empty for the pattern's"
+                                    />
+                                    <div class="flex gap-0.5">
+                                        {(0..TRACK_COUNT).map(|track| {
+                                            let muted = move || song.with(|song| {
+                                                song.rows.get(row).is_some_and(|r| r.mutes[track])
+                                            });
+                                            view! {
+                                                <button
+                                                    class=move || if muted() {
+                                                        "w-4 h-4 rounded-sm text-[8px] font-mono bg-red-500/70 text-white"
+                                                    } else {
+                                                        "w-4 h-4 rounded-sm text-[8px] font-mono bg-zinc-800 text-zinc-500 hover:bg-zinc-700"
+                                                    }
+                                                    title=format!("Mute track {} in this row", track + 1)
+                                                    on:click=move |_| update_row(row, &|r| r.mutes[track] = !r.mutes[track])
+                                                >
+                                                    {track + 1}
+                                                </button>
+                                            }
+                                        }).collect::<Vec<_>>()}
+                                    </div>
+                                    <button
+                                        class="w-6 h-6 bg-zinc-800 hover:bg-zinc-700 border border-zinc-700 rounded text-xs text-zinc-300 transition-colors"
+                                        title="Remove this row"
+                                        on:click=move |_| remove_row(row)
+                                    >
+                                        "-"
+                                    </button>
+                                </div>
+                            }
+                        }).collect::<Vec<_>>()}
+                        <button
+                            class="self-start px-3 py-1 bg-zinc-800 hover:bg-zinc-700 rounded text-xs font-medium text-zinc-300 transition-colors"
+                            on:click=add_row
+                        >
+                            "+ ROW"
+                        </button>
+                    </div>
+
+                    <div class="flex gap-2 justify-end">
+                        <button
+                            class="px-4 py-2 bg-zinc-800 hover:bg-zinc-700 rounded text-sm text-zinc-300 transition-colors"
+                            on:click=move |_| visible.set(false)
+                        >
+                            "Close"
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -1,37 +1,39 @@
 use crate::app::SequencerState;
-use crate::shared::models::Pattern;
+use crate::shared::models::{Chord, ChordKind, Pattern};
+use crate::shared::scale;
 use crate::ui::components::collapsible_section::CollapsibleSection;
 use crate::ui::components::form_controls::*;
+use crate::ui::components::lfo_designer::LfoDesigner;
+use crate::ui::components::mod_envelope_editor::ModEnvelopeEditor;
+use crate::ui::components::mod_matrix_editor::ModMatrixEditor;
+use crate::ui::components::track_sample_selector::TrackSampleSelector;
 use leptos::prelude::*;
 
-/// Calculate track statistics (active steps count, P-Lock count)
-/// Note: Examines only the primary subtrack (index 0) as per current single-subtrack design
+/// Calculate track statistics (active steps count, P-Lock count), summed over all subtracks
 fn calculate_track_stats(track: &crate::shared::models::Track) -> (usize, usize) {
-    let active_steps = track
-        .subtracks
-        .get(0)
-        .map(|st| {
-            st.steps
-                .iter()
-                .filter(|s| s.trig_type != crate::shared::models::TrigType::None)
-                .count()
-        })
-        .unwrap_or(0);
-
-    let p_lock_count = track
-        .subtracks
-        .get(0)
-        .map(|st| {
-            st.steps
-                .iter()
-                .map(|s| s.p_locks.iter().filter(|p| p.is_some()).count())
-                .sum::<usize>()
-        })
-        .unwrap_or(0);
+    let steps = || track.subtracks.iter().flat_map(|st| st.steps.iter());
+    let active_steps = steps()
+        .filter(|s| s.trig_type != crate::shared::models::TrigType::None)
+        .count();
+
+    let p_lock_count = steps()
+        .map(|s| s.p_locks.iter().filter(|p| p.is_some()).count())
+        .sum::<usize>();
 
     (active_steps, p_lock_count)
 }
 
+// Readout of an arpeggiator parameter, decoded like the backend's engine::arpeggiator
+fn arp_value_label(param_idx: usize, value: f32) -> String {
+    let pick = |len: usize| (value.clamp(0.0, 1.0) * (len - 1) as f32).round() as usize;
+    match param_idx {
+        16 => ["Up", "Down", "Up-Down", "Random", "As Played"][pick(5)].to_string(),
+        17 => ["1/32", "1/16T", "1/16", "1/8T", "1/8", "1/4"][pick(6)].to_string(),
+        18 => format!("{} oct", pick(4) + 1),
+        _ => format!("{:.0}%", value.clamp(0.05, 1.0) * 100.0),
+    }
+}
+
 // Helper to extract track_id from selected_step
 fn get_track_id_from_selection(selected_step: RwSignal<Option<(usize, usize)>>) -> usize {
     selected_step
@@ -57,7 +59,7 @@ pub fn StepEditorSidebar() -> impl IntoView {
             pattern_signal.with(|p| {
                 p.tracks
                     .get(track_id)
-                    .and_then(|t| t.subtracks.get(0))
+                    .and_then(|t| t.subtracks.get(sequencer_state.subtrack(track_id)))
                     .and_then(|st| st.steps.get(step_idx))
                     .map(|s| s.note as f64)
                     .unwrap_or(60.0)
@@ -67,13 +69,17 @@ pub fn StepEditorSidebar() -> impl IntoView {
         }
     });
 
-    // Note change handler
+    // Note change handler, snapped to the pattern key. A one-step nudge moves to the
+    // next note of the key in that direction, a typed note to the nearest one.
     let on_note_change = move |val: f64| {
         if let Some((track_id, step_idx)) = selected_step.get() {
-            let clamped = (val.round() as u8).clamp(0, 127);
+            let typed = val.round() as i32;
+            let delta = typed - note_value.get_untracked() as i32;
+            let direction = if delta.abs() == 1 { delta } else { 0 };
+            let clamped = pattern_signal.with_untracked(|p| scale::snap(&p.key, typed, direction));
             set_pattern_signal.update(|pattern| {
                 if let Some(track) = pattern.tracks.get_mut(track_id) {
-                    if let Some(subtrack) = track.subtracks.get_mut(0) {
+                    if let Some(subtrack) = track.subtracks.get_mut(sequencer_state.subtrack(track_id)) {
                         if let Some(step) = subtrack.steps.get_mut(step_idx) {
                             step.note = clamped;
                         }
@@ -83,13 +89,56 @@ pub fn StepEditorSidebar() -> impl IntoView {
         }
     };
 
+    // Get current chord
+    let chord_value = Signal::derive(move || {
+        if let Some((track_id, step_idx)) = selected_step.get() {
+            pattern_signal.with(|p| {
+                p.tracks
+                    .get(track_id)
+                    .and_then(|t| t.subtracks.get(sequencer_state.subtrack(track_id)))
+                    .and_then(|st| st.steps.get(step_idx))
+                    .map(|s| s.chord.clone())
+                    .unwrap_or_default()
+            })
+        } else {
+            Chord::default()
+        }
+    });
+
+    // Chord change handler, shared by the chord controls
+    let update_chord = move |edit: &dyn Fn(&mut Chord)| {
+        if let Some((track_id, step_idx)) = selected_step.get() {
+            set_pattern_signal.update(|pattern| {
+                if let Some(track) = pattern.tracks.get_mut(track_id) {
+                    if let Some(subtrack) = track.subtracks.get_mut(sequencer_state.subtrack(track_id)) {
+                        if let Some(step) = subtrack.steps.get_mut(step_idx) {
+                            edit(&mut step.chord);
+                        }
+                    }
+                }
+            });
+        }
+    };
+    let on_chord_kind_change = move |val: String| {
+        update_chord(&|chord| chord.kind = scale::chord_kind_from_name(&val));
+    };
+    let on_inversion_change = move |val: f64| {
+        update_chord(&|chord| chord.inversion = val.round().clamp(0.0, 7.0) as u8);
+    };
+    let on_voicing_change = move |val: String| {
+        update_chord(&|chord| chord.voicing = scale::voicing_from_name(&val));
+    };
+    let on_intervals_change = move |val: String| {
+        update_chord(&|chord| chord.intervals = scale::parse_intervals(&val));
+    };
+
     // Get current velocity value
     let velocity_value = Signal::derive(move || {
         if let Some((track_id, step_idx)) = selected_step.get() {
             pattern_signal.with(|p| {
                 p.tracks
                     .get(track_id)
-                    .and_then(|t| t.subtracks.get(0))
+                    .and_then(|t| t.subtracks.get(sequencer_state.subtrack(track_id)))
                     .and_then(|st| st.steps.get(step_idx))
                     .map(|s| s.velocity as f64)
                     .unwrap_or(100.0)
@@ -105,7 +154,7 @@ pub fn StepEditorSidebar() -> impl IntoView {
             let clamped = (val.round() as u8).clamp(0, 127);
             set_pattern_signal.update(|pattern| {
                 if let Some(track) = pattern.tracks.get_mut(track_id) {
-                    if let Some(subtrack) = track.subtracks.get_mut(0) {
+                    if let Some(subtrack) = track.subtracks.get_mut(sequencer_state.subtrack(track_id)) {
                         if let Some(step) = subtrack.steps.get_mut(step_idx) {
                             step.velocity = clamped;
                         }
@@ -121,7 +170,7 @@ pub fn StepEditorSidebar() -> impl IntoView {
             pattern_signal.with(|p| {
                 p.tracks
                     .get(track_id)
-                    .and_then(|t| t.subtracks.get(0))
+                    .and_then(|t| t.subtracks.get(sequencer_state.subtrack(track_id)))
                     .and_then(|st| st.steps.get(step_idx))
                     .map(|s| s.length as f64)
                     .unwrap_or(1.0)
@@ -137,7 +186,7 @@ pub fn StepEditorSidebar() -> impl IntoView {
             let clamped = (val as f32).clamp(0.1, 4.0);
             set_pattern_signal.update(|pattern| {
                 if let Some(track) = pattern.tracks.get_mut(track_id) {
-                    if let Some(subtrack) = track.subtracks.get_mut(0) {
+                    if let Some(subtrack) = track.subtracks.get_mut(sequencer_state.subtrack(track_id)) {
                         if let Some(step) = subtrack.steps.get_mut(step_idx) {
                             step.length = clamped;
                         }
@@ -153,7 +202,7 @@ pub fn StepEditorSidebar() -> impl IntoView {
             pattern_signal.with(|p| {
                 p.tracks
                     .get(track_id)
-                    .and_then(|t| t.subtracks.get(0))
+                    .and_then(|t| t.subtracks.get(sequencer_state.subtrack(track_id)))
                     .and_then(|st| st.steps.get(step_idx))
                     .map(|s| s.condition.prob as f64)
                     .unwrap_or(100.0)
@@ -169,7 +218,7 @@ pub fn StepEditorSidebar() -> impl IntoView {
             let clamped = (val.round() as u8).clamp(0, 100);
             set_pattern_signal.update(|pattern| {
                 if let Some(track) = pattern.tracks.get_mut(track_id) {
-                    if let Some(subtrack) = track.subtracks.get_mut(0) {
+                    if let Some(subtrack) = track.subtracks.get_mut(sequencer_state.subtrack(track_id)) {
                         if let Some(step) = subtrack.steps.get_mut(step_idx) {
                             step.condition.prob = clamped;
                         }
@@ -185,7 +234,7 @@ pub fn StepEditorSidebar() -> impl IntoView {
             pattern_signal.with(|p| {
                 p.tracks
                     .get(track_id)
-                    .and_then(|t| t.subtracks.get(0))
+                    .and_then(|t| t.subtracks.get(sequencer_state.subtrack(track_id)))
                     .and_then(|st| st.steps.get(step_idx))
                     .map(|s| s.micro_timing as f64)
                     .unwrap_or(0.0)
@@ -201,7 +250,7 @@ pub fn StepEditorSidebar() -> impl IntoView {
             let clamped = (val.round() as i8).clamp(-23, 23);
             set_pattern_signal.update(|pattern| {
                 if let Some(track) = pattern.tracks.get_mut(track_id) {
-                    if let Some(subtrack) = track.subtracks.get_mut(0) {
+                    if let Some(subtrack) = track.subtracks.get_mut(sequencer_state.subtrack(track_id)) {
                         if let Some(step) = subtrack.steps.get_mut(step_idx) {
                             step.micro_timing = clamped;
                         }
@@ -211,24 +260,43 @@ pub fn StepEditorSidebar() -> impl IntoView {
         }
     };
 
-    // Sound parameter definitions (8 parameters from Inspector)
+    // Sound parameter definitions (9 parameters from Inspector)
+    // (Parameter ID, label) - IDs index p_locks/default_params and match the backend's engine::domain
     let sound_params = [
-        "Tuning",
-        "Filter Freq",
-        "Resonance",
-        "Drive",
-        "Decay",
-        "Sustain",
-        "Reverb",
-        "Delay",
+        (10, "Tuning"),
+        (11, "Coarse Tune"),
+        (2, "Filter Freq"),
+        (3, "Resonance"),
+        (4, "Drive"),
+        (1, "Decay"),
+        (5, "Sustain"),
+        (6, "Reverb"),
+        (7, "Delay"),
     ];
 
+    // Arpeggiator parameters (P-Lockable like the sound parameters)
+    let arp_params = [(16, "Arp Mode"), (17, "Arp Rate"), (18, "Arp Octaves"), (19, "Arp Gate")];
+
+    let arp_enabled = Signal::derive(move || {
+        let track_id = get_track_id_from_selection(selected_step);
+        pattern_signal.with(|p| p.tracks.get(track_id).is_some_and(|t| t.arp_enabled))
+    });
+
+    let toggle_arp = move |_| {
+        let track_id = get_track_id_from_selection(selected_step);
+        set_pattern_signal.update(|p| {
+            if let Some(track) = p.tracks.get_mut(track_id) {
+                track.arp_enabled = !track.arp_enabled;
+            }
+        });
+    };
+
     // Get sound parameter value (P-Lock or track default)
     let get_param_value = move |param_idx: usize| {
         if let Some((track_id, step_idx)) = selected_step.get() {
             pattern_signal.with(|p| {
                 if let Some(track) = p.tracks.get(track_id) {
-                    if let Some(subtrack) = track.subtracks.get(0) {
+                    if let Some(subtrack) = track.subtracks.get(sequencer_state.subtrack(track_id)) {
                         if let Some(step) = subtrack.steps.get(step_idx) {
                             // Check P-Lock first, fallback to track default
                             return step.p_locks.get(param_idx).and_then(|p| *p).unwrap_or_else(
@@ -250,7 +318,7 @@ pub fn StepEditorSidebar() -> impl IntoView {
             pattern_signal.with(|p| {
                 p.tracks
                     .get(track_id)
-                    .and_then(|t| t.subtracks.get(0))
+                    .and_then(|t| t.subtracks.get(sequencer_state.subtrack(track_id)))
                     .and_then(|st| st.steps.get(step_idx))
                     .and_then(|s| s.p_locks.get(param_idx))
                     .map(|p| p.is_some())
@@ -267,7 +335,7 @@ pub fn StepEditorSidebar() -> impl IntoView {
             let clamped = val.clamp(0.0, 1.0) as f32;
             set_pattern_signal.update(|pattern| {
                 if let Some(track) = pattern.tracks.get_mut(track_id) {
-                    if let Some(subtrack) = track.subtracks.get_mut(0) {
+                    if let Some(subtrack) = track.subtracks.get_mut(sequencer_state.subtrack(track_id)) {
                         if let Some(step) = subtrack.steps.get_mut(step_idx) {
                             // Check if value differs from track default
                             let track_default =
@@ -296,7 +364,7 @@ pub fn StepEditorSidebar() -> impl IntoView {
             pattern_signal.with(|p| {
                 p.tracks
                     .get(track_id)
-                    .and_then(|t| t.subtracks.get(0))
+                    .and_then(|t| t.subtracks.get(sequencer_state.subtrack(track_id)))
                     .and_then(|st| st.steps.get(step_idx))
                     .map(|s| s.p_locks.iter().filter(|p| p.is_some()).count())
                     .unwrap_or(0)
@@ -325,6 +393,20 @@ pub fn StepEditorSidebar() -> impl IntoView {
         })
     });
 
+    let lfo_designer_points = Signal::derive(move || {
+        let track_id = get_track_id_from_selection(selected_step);
+        pattern_signal.with(|p| {
+            p.tracks
+                .get(track_id)
+                .and_then(|t| t.lfos.get(0))
+                .and_then(|l| match &l.shape {
+                    crate::shared::models::LFOShape::Designer(points) => Some(points.clone()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| vec![0.0; 16])
+        })
+    });
+
     let lfo_amount = Signal::derive(move || {
         let track_id = get_track_id_from_selection(selected_step);
         pattern_signal.with(|p| {
@@ -369,6 +451,13 @@ pub fn StepEditorSidebar() -> impl IntoView {
                         "Triangle" => crate::shared::models::LFOShape::Triangle,
                         "Square" => crate::shared::models::LFOShape::Square,
                         "Random" => crate::shared::models::LFOShape::Random,
+                        // Keep a drawn shape when re-selecting Designer
+                        "Designer" => match &lfo.shape {
+                            crate::shared::models::LFOShape::Designer(points) => {
+                                crate::shared::models::LFOShape::Designer(points.clone())
+                            }
+                            _ => crate::shared::models::LFOShape::Designer(vec![0.0; 16]),
+                        },
                         _ => crate::shared::models::LFOShape::Triangle,
                     };
                 }
@@ -376,6 +465,17 @@ pub fn StepEditorSidebar() -> impl IntoView {
         });
     };
 
+    let on_designer_change = Callback::new(move |points: Vec<f32>| {
+        let track_id = get_track_id_from_selection(selected_step);
+        set_pattern_signal.update(|p| {
+            if let Some(track) = p.tracks.get_mut(track_id) {
+                if let Some(lfo) = track.lfos.get_mut(0) {
+                    lfo.shape = crate::shared::models::LFOShape::Designer(points);
+                }
+            }
+        });
+    });
+
     let on_amount_change = move |val: f64| {
         let clamped = val.clamp(-1.0, 1.0) as f32;
         let track_id = get_track_id_from_selection(selected_step);
@@ -426,7 +526,15 @@ pub fn StepEditorSidebar() -> impl IntoView {
                                             "EDITING STEP"
                                         </div>
                                         <div class="text-xs font-bold text-zinc-100">
-                                            {move || format!("Track {} - Step {}", track_id + 1, step_idx + 1)}
+                                            {move || {
+                                                // Name the subtrack once the track has several
+                                                let layered = pattern_signal.with(|p| p.tracks.get(track_id).is_some_and(|t| t.subtracks.len() > 1));
+                                                if layered {
+                                                    format!("Track {}.{} - Step {}", track_id + 1, sequencer_state.subtrack(track_id) + 1, step_idx + 1)
+                                                } else {
+                                                    format!("Track {} - Step {}", track_id + 1, step_idx + 1)
+                                                }
+                                            }}
                                         </div>
                                     </div>
                                     <button
@@ -454,8 +562,61 @@ pub fn StepEditorSidebar() -> impl IntoView {
                                             value=Signal::derive(move || format!("{}", note_value.get() as u8))
                                             on_input=on_note_change
                                         />
+                                        <span class="text-[10px] font-mono text-zinc-500 w-10 text-right">
+                                            {move || {
+                                                let note = note_value.get() as u8;
+                                                pattern_signal.with(|p| format!("{} {}", scale::note_name(note), scale::degree_label(&p.key, note)))
+                                            }}
+                                        </span>
                                     </InlineParam>
 
+                                    <InlineParam>
+                                        <ParamLabel text="Chord" locked=Signal::derive(|| false) />
+                                        <Dropdown
+                                            options=scale::CHORD_KINDS.iter().map(|&(_, name)| (name, name)).collect()
+                                            selected=Signal::derive(move || scale::chord_kind_name(chord_value.get().kind).to_string())
+                                            on_change=on_chord_kind_change
+                                        />
+                                    </InlineParam>
+
+                                    <Show when=move || chord_value.get().kind == ChordKind::Custom>
+                                        <InlineParam>
+                                            <ParamLabel text="Intervals" locked=Signal::derive(|| false) />
+                                            <input
+                                                type="text"
+                                                placeholder="3 7 10"
+                                                title="Semitones above the root, separated by spaces"
+                                                prop:value=move || {
+                                                    chord_value.get().intervals.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(" ")
+                                                }
+                                                on:change=move |ev| on_intervals_change(event_target_value(&ev))
+                                                class="w-24 text-[10px] bg-zinc-800 border border-zinc-700 rounded px-1 py-0.5 text-zinc-50 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-zinc-900"
+                                            />
+                                        </InlineParam>
+                                    </Show>
+
+                                    <Show when=move || chord_value.get().kind != ChordKind::Single>
+                                        <InlineParam>
+                                            <ParamLabel text="Inversion" locked=Signal::derive(|| false) />
+                                            <NumberInput
+                                                min="0"
+                                                max="7"
+                                                step="1"
+                                                value=Signal::derive(move || format!("{}", chord_value.get().inversion))
+                                                on_input=on_inversion_change
+                                            />
+                                        </InlineParam>
+
+                                        <InlineParam>
+                                            <ParamLabel text="Voicing" locked=Signal::derive(|| false) />
+                                            <Dropdown
+                                                options=scale::VOICINGS.iter().map(|&(_, name)| (name, name)).collect()
+                                                selected=Signal::derive(move || scale::voicing_name(chord_value.get().voicing).to_string())
+                                                on_change=on_voicing_change
+                                            />
+                                        </InlineParam>
+                                    </Show>
+
                                     <InlineParam>
                                         <ParamLabel text="Velocity" locked=Signal::derive(|| false) />
                                         <NumberInput
@@ -506,7 +667,7 @@ pub fn StepEditorSidebar() -> impl IntoView {
                                     default_open=true
                                     badge_count=p_lock_count
                                 >
-                                    {sound_params.iter().enumerate().map(|(idx, &name)| {
+                                    {sound_params.iter().map(|&(idx, name)| {
                                         view! {
                                             <InlineParam>
                                                 <ParamLabel
@@ -527,6 +688,48 @@ pub fn StepEditorSidebar() -> impl IntoView {
                                     }).collect::<Vec<_>>()}
                                 </CollapsibleSection>
 
+                                <CollapsibleSection
+                                    title="ARPEGGIATOR"
+                                    default_open=false
+                                >
+                                    <InlineParam>
+                                        <ParamLabel text="Track Arp" locked=Signal::derive(|| false) />
+                                        <button
+                                            class=move || if arp_enabled.get() {
+                                                "px-2 py-0.5 rounded text-[10px] font-medium bg-blue-500 text-white"
+                                            } else {
+                                                "px-2 py-0.5 rounded text-[10px] font-medium bg-zinc-800 text-zinc-400 hover:bg-zinc-700"
+                                            }
+                                            title="Note trigs and held MIDI input notes on this track's channel play through the arpeggiator"
+                                            on:click=toggle_arp
+                                        >
+                                            {move || if arp_enabled.get() { "ON" } else { "OFF" }}
+                                        </button>
+                                    </InlineParam>
+                                    {arp_params.iter().map(|&(idx, name)| {
+                                        view! {
+                                            <InlineParam>
+                                                <ParamLabel
+                                                    text=name
+                                                    locked=Signal::derive(move || is_param_locked(idx))
+                                                />
+                                                <NumberInput
+                                                    min="0"
+                                                    max="1"
+                                                    step="0.01"
+                                                    value=Signal::derive(move || format!("{:.2}", get_param_value(idx)))
+                                                    on_input=move |val| {
+                                                        handle_param_input(idx, val);
+                                                    }
+                                                />
+                                                <span class="text-[10px] font-mono text-zinc-500 w-16 text-right">
+                                                    {move || arp_value_label(idx, get_param_value(idx))}
+                                                </span>
+                                            </InlineParam>
+                                        }
+                                    }).collect::<Vec<_>>()}
+                                </CollapsibleSection>
+
                                 <CollapsibleSection
                                     title="LFO"
                                     default_open=false
@@ -539,12 +742,24 @@ pub fn StepEditorSidebar() -> impl IntoView {
                                                 ("Triangle", "△"),
                                                 ("Square", "▭"),
                                                 ("Random", "※"),
+                                                ("Designer", "✎"),
                                             ]
                                             selected=lfo_shape
                                             on_change=on_shape_change
                                         />
                                     </InlineParam>
 
+                                    <Show when=move || lfo_shape.get() == "Designer">
+                                        <div class="px-1 py-1">
+                                            <LfoDesigner
+                                                track_id=Signal::derive(move || get_track_id_from_selection(selected_step))
+                                                lfo_index=Signal::derive(|| 0usize)
+                                                value=lfo_designer_points
+                                                on_change=on_designer_change
+                                            />
+                                        </div>
+                                    </Show>
+
                                     <InlineParam>
                                         <ParamLabel text="Amount" locked=Signal::derive(|| false) />
                                         <NumberInput
@@ -573,14 +788,40 @@ pub fn StepEditorSidebar() -> impl IntoView {
                                             options=vec![
                                                 ("74", "Filter Cutoff"),
                                                 ("71", "Resonance"),
-                                                ("1", "Mod Wheel"),
+                                                ("75", "Decay"),
+                                                ("7", "Volume"),
                                                 ("10", "Pan"),
+                                                ("91", "Reverb Send"),
+                                                ("94", "Delay Send"),
+                                                ("1", "Mod Wheel"),
                                             ]
                                             selected=lfo_destination
                                             on_change=on_destination_change
                                         />
                                     </InlineParam>
                                 </CollapsibleSection>
+
+                                <CollapsibleSection
+                                    title="SAMPLE"
+                                    default_open=false
+                                >
+                                    <TrackSampleSelector />
+                                </CollapsibleSection>
+
+                                <CollapsibleSection
+                                    title="ENVELOPES"
+                                    default_open=false
+                                >
+                                    <ModEnvelopeEditor index=0 />
+                                    <ModEnvelopeEditor index=1 />
+                                </CollapsibleSection>
+
+                                <CollapsibleSection
+                                    title="MOD MATRIX"
+                                    default_open=false
+                                >
+                                    <ModMatrixEditor />
+                                </CollapsibleSection>
                                 </div>
                             </div>
                         </div>
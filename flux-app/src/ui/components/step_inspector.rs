@@ -18,10 +18,12 @@ pub fn StepInspector() -> impl IntoView {
     let on_pitch_change = move |ev| {
         let val = event_target_value(&ev).parse::<f32>().unwrap_or(60.0);
         if let Some((track_id, step_idx)) = selected.get() {
+            let subtrack = state.subtrack(track_id);
             spawn_local(async move {
                 // Construct args object manually or use serde_wasm_bindgen
                 let args = serde_wasm_bindgen::to_value(&serde_json::json!({
                     "trackId": track_id,
+                    "subtrack": subtrack,
                     "stepIdx": step_idx,
                     "paramId": 0, // PARAM_PITCH (0 is hardcoded for now)
                     "value": val
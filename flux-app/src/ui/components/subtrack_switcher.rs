@@ -0,0 +1,105 @@
+use crate::app::SequencerState;
+use crate::shared::models::{Pattern, Subtrack, TrigType, MAX_SUBTRACKS};
+use leptos::prelude::*;
+
+/// Subtrack tabs of the selected track. The grid, velocity lanes and step editor show the
+/// chosen subtrack; all of a track's subtracks play in parallel, each on its own voice.
+#[component]
+pub fn SubtrackSwitcher() -> impl IntoView {
+    let pattern_signal = use_context::<ReadSignal<Pattern>>()
+        .expect("Pattern context not found");
+    let set_pattern_signal = use_context::<WriteSignal<Pattern>>()
+        .expect("Pattern write signal not found");
+    let sequencer_state = use_context::<SequencerState>()
+        .expect("SequencerState context not found");
+
+    let track_id = move || sequencer_state.selected_step.get().map(|(track, _)| track).unwrap_or(0);
+    let subtrack_count = move || {
+        pattern_signal.with(|p| p.tracks.get(track_id()).map(|t| t.subtracks.len()).unwrap_or(0))
+    };
+    let current = move || sequencer_state.subtrack(track_id()).min(subtrack_count().saturating_sub(1));
+
+    // New subtracks play on the lowest voice ID the track doesn't use yet
+    let add_subtrack = move |_: leptos::ev::MouseEvent| {
+        let track_id = track_id();
+        let mut added = None;
+        set_pattern_signal.update(|p| {
+            if let Some(track) = p.tracks.get_mut(track_id) {
+                if track.subtracks.len() >= MAX_SUBTRACKS {
+                    return;
+                }
+                let voice_id = (0..MAX_SUBTRACKS)
+                    .find(|id| track.subtracks.iter().all(|st| st.voice_id != *id))
+                    .unwrap_or(0);
+                track.subtracks.push(Subtrack { voice_id, ..Subtrack::default() });
+                added = Some(track.subtracks.len() - 1);
+            }
+        });
+        if let Some(subtrack) = added {
+            sequencer_state.set_subtrack(track_id, subtrack);
+        }
+    };
+
+    // Remove the shown subtrack; a track always keeps one
+    let remove_subtrack = move |_: leptos::ev::MouseEvent| {
+        let track_id = track_id();
+        let subtrack = current();
+        set_pattern_signal.update(|p| {
+            if let Some(track) = p.tracks.get_mut(track_id) {
+                if track.subtracks.len() > 1 {
+                    track.subtracks.remove(subtrack);
+                }
+            }
+        });
+        sequencer_state.set_subtrack(track_id, subtrack.saturating_sub(1));
+    };
+
+    view! {
+        <div class="mt-2 flex items-center gap-1">
+            <span class="text-[10px] font-medium uppercase tracking-tight text-zinc-400">
+                {move || format!("T{} Subtracks", track_id() + 1)}
+            </span>
+            {move || {
+                (0..subtrack_count()).map(|subtrack| {
+                    let has_trigs = move || pattern_signal.with(|p| {
+                        p.tracks
+                            .get(track_id())
+                            .and_then(|t| t.subtracks.get(subtrack))
+                            .is_some_and(|st| st.steps.iter().any(|s| s.trig_type != TrigType::None))
+                    });
+                    view! {
+                        <button
+                            class=move || if current() == subtrack {
+                                "w-6 h-6 rounded text-[10px] font-mono bg-blue-500 text-white"
+                            } else if has_trigs() {
+                                "w-6 h-6 rounded text-[10px] font-mono bg-zinc-700 text-zinc-200 hover:bg-zinc-600"
+                            } else {
+                                "w-6 h-6 rounded text-[10px] font-mono bg-zinc-800 text-zinc-500 hover:bg-zinc-700"
+                            }
+                            title="Show and edit this subtrack"
+                            on:click=move |_| sequencer_state.set_subtrack(track_id(), subtrack)
+                        >
+                            {subtrack + 1}
+                        </button>
+                    }
+                }).collect::<Vec<_>>()
+            }}
+            <button
+                class="w-6 h-6 bg-zinc-800 hover:bg-zinc-700 border border-zinc-700 rounded text-xs text-zinc-300 transition-colors disabled:opacity-20 disabled:cursor-not-allowed"
+                disabled=move || { subtrack_count() >= MAX_SUBTRACKS }
+                title="Add a subtrack, layered on its own voice"
+                on:click=add_subtrack
+            >
+                "+"
+            </button>
+            <button
+                class="w-6 h-6 bg-zinc-800 hover:bg-zinc-700 border border-zinc-700 rounded text-xs text-zinc-300 transition-colors disabled:opacity-20 disabled:cursor-not-allowed"
+                disabled=move || { subtrack_count() <= 1 }
+                title="Remove the shown subtrack"
+                on:click=remove_subtrack
+            >
+                "-"
+            </button>
+        </div>
+    }
+}
@@ -1,12 +1,47 @@
 use leptos::prelude::*;
 use wasm_bindgen::prelude::*;
 use crate::ui::tauri::{safe_invoke, safe_dialog_save, safe_dialog_open, TauriError};
+use crate::ui::components::audio_settings_panel::AudioSettingsPanel;
+use crate::ui::components::cpu_meter::CpuMeter;
+use crate::ui::components::debug_panel::DebugPanel;
+use crate::ui::components::song_panel::SongPanel;
+use crate::ui::components::tuning_panel::TuningPanel;
+use crate::ui::tauri::safe_listen_event;
+use crate::services::audio::{AudioStatus, RecordOptions, RecordSource, RecordStart, SampleInfo, StreamState};
+
+/// Recording lengths offered in the toolbar; 0 records until stopped.
+const RECORD_BARS: [u32; 5] = [0, 1, 2, 4, 8];
+
+/// Select value for a record source, parsed back by `parse_record_source`.
+fn record_source_value(source: RecordSource) -> String {
+    match source {
+        RecordSource::Input => "input".to_string(),
+        RecordSource::Master => "master".to_string(),
+        RecordSource::Track(track) => format!("track:{}", track),
+        RecordSource::Send(bus) => format!("send:{}", bus),
+    }
+}
+
+fn parse_record_source(value: &str) -> RecordSource {
+    match value.split_once(':') {
+        Some(("track", track)) => track.parse().map(RecordSource::Track).unwrap_or(RecordSource::Input),
+        Some(("send", bus)) => bus.parse().map(RecordSource::Send).unwrap_or(RecordSource::Input),
+        _ if value == "master" => RecordSource::Master,
+        _ => RecordSource::Input,
+    }
+}
 
 #[derive(serde::Serialize)]
-struct LoadPatternArgs {
+struct LoadProjectArgs {
     path: String,
 }
 
+#[derive(serde::Serialize)]
+struct ExportAudioArgs {
+    pattern: crate::shared::models::Pattern,
+    directory: String,
+}
+
 #[derive(serde::Serialize)]
 struct DialogFilter {
     name: String,
@@ -30,17 +65,98 @@ struct OpenDialogOptions {
 #[component]
 pub fn Toolbar() -> impl IntoView {
     let pattern_signal = use_context::<ReadSignal<crate::shared::models::Pattern>>().expect("Pattern context not found");
-    let set_pattern_signal = use_context::<WriteSignal<crate::shared::models::Pattern>>().expect("Pattern context not found");
+    let project = use_context::<crate::app::ProjectState>().expect("ProjectState context not found");
     let playback_state = use_context::<ReadSignal<crate::ui::state::PlaybackState>>().expect("PlaybackState context not found");
+    let show_audio_settings = RwSignal::new(false);
+    let show_debug_log = RwSignal::new(false);
+    let show_tuning = RwSignal::new(false);
+    let show_song = RwSignal::new(false);
+    let audio_status = RwSignal::new(None::<AudioStatus>);
+    let is_recording = RwSignal::new(false); // Armed or capturing
+    let record_bars = RwSignal::new(0u32);
+    let record_source = RwSignal::new(RecordSource::Input);
+
+    // Initial state, then follow restarts and fallbacks from the device thread
+    Effect::new(move |_| {
+        leptos::task::spawn_local(async move {
+            if let Some(status) = crate::services::audio::get_audio_status().await {
+                audio_status.set(Some(status));
+            }
+            safe_listen_event("audio-status", move |status: AudioStatus| {
+                audio_status.set(Some(status));
+            }).await;
+        });
+    });
+
+    // Bar-length recordings finish on their own
+    Effect::new(move |_| {
+        leptos::task::spawn_local(async move {
+            safe_listen_event("sample-recorded", move |_: SampleInfo| {
+                is_recording.set(false);
+            }).await;
+        });
+    });
+
+    // Manual records from now until stopped; bar lengths arm for the next bar.
+    // Resampling always starts and stops on a bar line (handled by the backend).
+    let toggle_recording = move |_| {
+        if is_recording.get_untracked() {
+            is_recording.set(false);
+            leptos::task::spawn_local(async {
+                crate::services::audio::stop_recording().await;
+            });
+            return;
+        }
+        let bars = record_bars.get_untracked();
+        let options = RecordOptions {
+            source: record_source.get_untracked(),
+            start: if bars == 0 { RecordStart::Now } else { RecordStart::NextBar },
+            bars: if bars == 0 { None } else { Some(bars) },
+        };
+        is_recording.set(true);
+        leptos::task::spawn_local(async move {
+            if let Err(msg) = crate::services::audio::start_recording(options).await {
+                web_sys::console::error_1(&format!("start_recording failed: {}", msg).into());
+                is_recording.set(false);
+            }
+        });
+    };
+
+    let audio_indicator_class = move || match audio_status.get().map(|s| s.state) {
+        Some(StreamState::Running) => "w-2 h-2 rounded-full bg-green-500",
+        Some(StreamState::Fallback) | Some(StreamState::Recovering) => "w-2 h-2 rounded-full bg-amber-500",
+        Some(StreamState::Stopped) => "w-2 h-2 rounded-full bg-red-500",
+        None => "w-2 h-2 rounded-full bg-zinc-600",
+    };
+
+    let audio_title = move || match audio_status.get() {
+        Some(status) => {
+            let device = status
+                .device
+                .map(|d| format!("{} / {} @ {} Hz", d.host, d.device, d.sample_rate))
+                .unwrap_or_else(|| "No output".to_string());
+            let state = match status.state {
+                StreamState::Running => "Running",
+                StreamState::Fallback => "Fallback",
+                StreamState::Recovering => "Recovering",
+                StreamState::Stopped => "Stopped",
+            };
+            match status.message {
+                Some(msg) => format!("{}: {} ({})", state, device, msg),
+                None => format!("{}: {}", state, device),
+            }
+        }
+        None => "Audio output device, sample rate and buffer size".to_string(),
+    };
 
     let save_project = move |_| {
         leptos::task::spawn_local(async move {
             let options = SaveDialogOptions {
                 filters: vec![DialogFilter {
-                    name: "Flux Pattern".to_string(),
+                    name: "Flux Project".to_string(),
                     extensions: vec!["flux".to_string()],
                 }],
-                default_path: Some("pattern.flux".to_string()),
+                default_path: Some("project.flux".to_string()),
             };
 
             let options_js = match serde_wasm_bindgen::to_value(&options) {
@@ -53,28 +169,28 @@ pub fn Toolbar() -> impl IntoView {
 
             match safe_dialog_save(options_js).await {
                 Ok(Some(path)) => {
-                    // Capture pattern state once to ensure consistency across both saves
-                    let current_pattern = pattern_signal.get_untracked();
+                    // Capture project state once to ensure consistency across both saves
+                    let current_project = project.project();
 
                     #[derive(serde::Serialize)]
                     struct Args {
-                        pattern: crate::shared::models::Pattern,
+                        project: crate::shared::models::Project,
                         path: String,
                     }
 
                     let args = match serde_wasm_bindgen::to_value(&Args {
-                        pattern: current_pattern.clone(),
+                        project: current_project.clone(),
                         path: path.clone(),
                     }) {
                         Ok(v) => v,
                         Err(e) => {
-                            web_sys::console::error_1(&format!("Failed to serialize save_pattern args: {:?}", e).into());
+                            web_sys::console::error_1(&format!("Failed to serialize save_project args: {:?}", e).into());
                             return;
                         }
                     };
 
                     // Note: Errors are logged to console only, no user-facing notifications
-                    match safe_invoke("save_pattern", args).await {
+                    match safe_invoke("save_project", args).await {
                         Ok(_) => {},
                         Err(TauriError::NotAvailable) => {
                             web_sys::console::log_1(&"Tauri not available - save command disabled".into());
@@ -84,10 +200,10 @@ pub fn Toolbar() -> impl IntoView {
                         }
                     }
 
-                    // Also save to last_pattern.flux for auto-load (using same pattern state)
+                    // Also save to last_pattern.flux for auto-load (using same project state)
                     if !path.ends_with("last_pattern.flux") {
                          let auto_args = match serde_wasm_bindgen::to_value(&Args {
-                            pattern: current_pattern.clone(),
+                            project: current_project.clone(),
                             path: "last_pattern.flux".to_string(),
                         }) {
                             Ok(v) => v,
@@ -97,7 +213,7 @@ pub fn Toolbar() -> impl IntoView {
                             }
                         };
 
-                        match safe_invoke("save_pattern", auto_args).await {
+                        match safe_invoke("save_project", auto_args).await {
                             Ok(_) => {},
                             Err(TauriError::NotAvailable) => {
                                 web_sys::console::log_1(&"Tauri not available - auto-save command disabled".into());
@@ -125,7 +241,7 @@ pub fn Toolbar() -> impl IntoView {
         leptos::task::spawn_local(async move {
              let options = OpenDialogOptions {
                 filters: vec![DialogFilter {
-                    name: "Flux Pattern".to_string(),
+                    name: "Flux Project".to_string(),
                     extensions: vec!["flux".to_string()],
                 }],
                 multiple: false,
@@ -142,25 +258,25 @@ pub fn Toolbar() -> impl IntoView {
 
             match safe_dialog_open(options_js).await {
                 Ok(Some(path)) => {
-                     let args = match serde_wasm_bindgen::to_value(&LoadPatternArgs {
+                     let args = match serde_wasm_bindgen::to_value(&LoadProjectArgs {
                         path,
                     }) {
                         Ok(v) => v,
                         Err(e) => {
-                            web_sys::console::error_1(&format!("Failed to serialize load_pattern args: {:?}", e).into());
+                            web_sys::console::error_1(&format!("Failed to serialize load_project args: {:?}", e).into());
                             return;
                         }
                     };
 
                     // Note: Errors are logged to console only, no user-facing notifications
-                    match safe_invoke("load_pattern", args).await {
+                    match safe_invoke("load_project", args).await {
                         Ok(result) => {
-                            match serde_wasm_bindgen::from_value::<crate::shared::models::Pattern>(result) {
-                                Ok(loaded_pattern) => {
-                                    set_pattern_signal.set(loaded_pattern);
+                            match serde_wasm_bindgen::from_value::<crate::shared::models::Project>(result) {
+                                Ok(loaded_project) => {
+                                    project.load(loaded_project);
                                 },
                                 Err(e) => {
-                                    web_sys::console::error_1(&format!("Failed to deserialize pattern: {:?}", e).into());
+                                    web_sys::console::error_1(&format!("Failed to deserialize project: {:?}", e).into());
                                 }
                             }
                         },
@@ -185,6 +301,59 @@ pub fn Toolbar() -> impl IntoView {
         });
     };
 
+    let export_audio = move |_| {
+        leptos::task::spawn_local(async move {
+            let options = OpenDialogOptions {
+                filters: vec![],
+                multiple: false,
+                directory: true,
+            };
+
+            let options_js = match serde_wasm_bindgen::to_value(&options) {
+                Ok(v) => v,
+                Err(e) => {
+                    web_sys::console::error_1(&format!("Failed to serialize dialog options: {:?}", e).into());
+                    return;
+                }
+            };
+
+            match safe_dialog_open(options_js).await {
+                Ok(Some(directory)) => {
+                    let args = match serde_wasm_bindgen::to_value(&ExportAudioArgs {
+                        pattern: pattern_signal.get_untracked(),
+                        directory,
+                    }) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            web_sys::console::error_1(&format!("Failed to serialize export_audio args: {:?}", e).into());
+                            return;
+                        }
+                    };
+
+                    // Renders master + per-track stems + send buses with the backend defaults
+                    match safe_invoke("export_audio", args).await {
+                        Ok(_) => {},
+                        Err(TauriError::NotAvailable) => {
+                            web_sys::console::log_1(&"Tauri not available - export command disabled".into());
+                        },
+                        Err(TauriError::InvokeFailed(msg)) => {
+                            web_sys::console::error_1(&format!("Export command failed: {}", msg).into());
+                        }
+                    }
+                },
+                Ok(None) => {
+                    // User cancelled the dialog
+                },
+                Err(TauriError::NotAvailable) => {
+                    web_sys::console::log_1(&"Tauri not available - export dialog disabled".into());
+                },
+                Err(TauriError::InvokeFailed(msg)) => {
+                    web_sys::console::error_1(&format!("Export dialog failed: {}", msg).into());
+                }
+            }
+        });
+    };
+
     view! {
         <div class="flex items-center gap-2">
             <button
@@ -199,6 +368,47 @@ pub fn Toolbar() -> impl IntoView {
             >
                 LOAD
             </button>
+            <button
+                on:click=export_audio
+                class="h-10 px-4 bg-zinc-800 hover:bg-zinc-700 rounded-md text-sm font-medium text-zinc-300 transition-colors active:scale-95 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-zinc-950"
+                title="Export master mixdown and per-track stems (WAV)"
+            >
+                EXPORT
+            </button>
+            <button
+                on:click=move |_| show_audio_settings.set(true)
+                class="h-10 px-4 flex items-center gap-2 bg-zinc-800 hover:bg-zinc-700 rounded-md text-sm font-medium text-zinc-300 transition-colors active:scale-95 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-zinc-950"
+                title=audio_title
+            >
+                <span class=audio_indicator_class></span>
+                AUDIO
+            </button>
+            <button
+                on:click=move |_| show_tuning.set(true)
+                class="h-10 px-4 bg-zinc-800 hover:bg-zinc-700 rounded-md text-sm font-medium text-zinc-300 transition-colors active:scale-95 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-zinc-950"
+                title=move || format!("Project tuning: {}", pattern_signal.with(|p| p.tuning.name.clone()))
+            >
+                TUNING
+            </button>
+            <button
+                on:click=move |_| show_song.set(true)
+                class=move || if project.song.with(|song| song.enabled) {
+                    "h-10 px-4 bg-blue-600 hover:bg-blue-500 rounded-md text-sm font-medium text-white transition-colors active:scale-95 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-zinc-950"
+                } else {
+                    "h-10 px-4 bg-zinc-800 hover:bg-zinc-700 rounded-md text-sm font-medium text-zinc-300 transition-colors active:scale-95 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-zinc-950"
+                }
+                title="Song mode arrangement"
+            >
+                SONG
+            </button>
+            <CpuMeter />
+            <button
+                on:click=move |_| show_debug_log.set(true)
+                class="h-10 px-3 bg-zinc-800 hover:bg-zinc-700 rounded-md text-xs font-medium text-zinc-400 transition-colors active:scale-95 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-zinc-950"
+                title="Audio and MIDI thread log"
+            >
+                LOG
+            </button>
 
             <div class="w-px h-6 bg-zinc-700 mx-2"></div>
 
@@ -235,6 +445,60 @@ pub fn Toolbar() -> impl IntoView {
             >
                 "■"
             </button>
+
+            <div class="w-px h-6 bg-zinc-700 mx-2"></div>
+
+            <select
+                prop:value=move || record_source_value(record_source.get())
+                on:change=move |ev| record_source.set(parse_record_source(&event_target_value(&ev)))
+                disabled=move || is_recording.get()
+                class="h-10 px-2 bg-zinc-800 text-zinc-300 text-sm rounded-md border border-zinc-700 disabled:opacity-50 focus:outline-none focus:ring-2 focus:ring-blue-500"
+                title="Record the audio input, or resample the master, a track or a send bus"
+            >
+                <option value="input">"INPUT"</option>
+                <option value="master">"MASTER"</option>
+                {move || pattern_signal.with(|p| p.tracks.iter().enumerate().map(|(i, _)| {
+                    view! {
+                        <option value=record_source_value(RecordSource::Track(i))>{format!("TRACK {}", i + 1)}</option>
+                    }
+                }).collect::<Vec<_>>())}
+                <option value=record_source_value(RecordSource::Send(0))>"REVERB"</option>
+                <option value=record_source_value(RecordSource::Send(1))>"DELAY"</option>
+            </select>
+            <select
+                prop:value=move || record_bars.get().to_string()
+                on:change=move |ev| record_bars.set(event_target_value(&ev).parse().unwrap_or(0))
+                disabled=move || is_recording.get()
+                class="h-10 px-2 bg-zinc-800 text-zinc-300 text-sm rounded-md border border-zinc-700 disabled:opacity-50 focus:outline-none focus:ring-2 focus:ring-blue-500"
+                title="Recording length: manual, or synced to the next bar"
+            >
+                {RECORD_BARS.iter().map(|&bars| {
+                    let text = match bars {
+                        0 => "MANUAL".to_string(),
+                        1 => "1 BAR".to_string(),
+                        n => format!("{} BARS", n),
+                    };
+                    view! { <option value=bars.to_string()>{text}</option> }
+                }).collect::<Vec<_>>()}
+            </select>
+            <button
+                on:click=toggle_recording
+                class=move || {
+                    if is_recording.get() {
+                        "h-10 px-4 bg-red-600 hover:bg-red-500 rounded-md text-sm font-medium text-white transition-colors active:scale-95 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-zinc-950 animate-pulse"
+                    } else {
+                        "h-10 px-4 bg-zinc-800 hover:bg-zinc-700 rounded-md text-sm font-medium text-red-400 transition-colors active:scale-95 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-zinc-950"
+                    }
+                }
+                title="Record into the sample pool"
+            >
+                "● REC"
+            </button>
+
+            <AudioSettingsPanel visible=show_audio_settings />
+            <DebugPanel visible=show_debug_log />
+            <TuningPanel visible=show_tuning />
+            <SongPanel visible=show_song />
         </div>
     }
 }
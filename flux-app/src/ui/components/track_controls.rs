@@ -1,5 +1,9 @@
 use leptos::prelude::*;
-use crate::shared::models::{Pattern, Track, MachineType};
+use crate::app::SequencerState;
+use crate::shared::models::{Pattern, Track, MachineType, ScaleMode, TrigType};
+use crate::shared::scale::{self, NOTE_NAMES, SCALE_MODES};
+use crate::ui::components::form_controls::Dropdown;
+use crate::ui::state::NoteLabelMode;
 
 #[component]
 pub fn TrackControls() -> impl IntoView {
@@ -7,6 +11,10 @@ pub fn TrackControls() -> impl IntoView {
         .expect("Pattern context not found");
     let set_pattern_signal = use_context::<WriteSignal<Pattern>>()
         .expect("Pattern write signal not found");
+    let sequencer_state = use_context::<SequencerState>()
+        .expect("SequencerState context not found");
+    let label_mode = use_context::<RwSignal<NoteLabelMode>>()
+        .expect("NoteLabelMode context not found");
 
     let add_track = move |_| {
         set_pattern_signal.update(|pattern| {
@@ -20,6 +28,26 @@ pub fn TrackControls() -> impl IntoView {
 
     let track_count = move || pattern_signal.with(|p| p.tracks.len());
 
+    // Move every trig of the selected track, all subtracks, by scale degrees
+    let transpose = move |degrees: i32| {
+        let track_id = sequencer_state.selected_step.get_untracked().map(|(track, _)| track).unwrap_or(0);
+        set_pattern_signal.update(|pattern| {
+            let key = pattern.key.clone();
+            if let Some(track) = pattern.tracks.get_mut(track_id) {
+                let steps = track.subtracks.iter_mut().flat_map(|st| st.steps.iter_mut());
+                for step in steps.filter(|s| s.trig_type != TrigType::None) {
+                    step.note = scale::transpose(&key, step.note, degrees);
+                }
+            }
+        });
+    };
+
+    let root_options = NOTE_NAMES.iter().map(|name| (*name, *name)).collect::<Vec<_>>();
+    let mode_options = SCALE_MODES.iter().map(|(_, name)| (*name, *name)).collect::<Vec<_>>();
+    let label_options = vec![("dots", "Dots"), ("notes", "MIDI Notes"), ("degrees", "Scale Degrees")];
+
+    let is_custom = move || pattern_signal.with(|p| p.key.mode == ScaleMode::Custom);
+
     view! {
         <div class="mt-3 flex items-center gap-3">
             <button
@@ -31,6 +59,84 @@ pub fn TrackControls() -> impl IntoView {
             <span class="text-xs text-zinc-500 font-mono">
                 {move || format!("{} tracks", track_count())}
             </span>
+
+            // Pattern key: note entry and transposition snap to it
+            <div class="flex items-center gap-1 ml-auto">
+                <span class="text-[10px] font-medium uppercase tracking-tight text-zinc-400">"Key"</span>
+                <Dropdown
+                    options=root_options
+                    selected=Signal::derive(move || pattern_signal.with(|p| NOTE_NAMES[p.key.root as usize % 12].to_string()))
+                    on_change=move |name| {
+                        if let Some(root) = NOTE_NAMES.iter().position(|n| *n == name) {
+                            set_pattern_signal.update(|p| p.key.root = root as u8);
+                        }
+                    }
+                />
+                <Dropdown
+                    options=mode_options
+                    selected=Signal::derive(move || pattern_signal.with(|p| scale::mode_name(p.key.mode).to_string()))
+                    on_change=move |name| {
+                        set_pattern_signal.update(|p| p.key.mode = scale::mode_from_name(&name));
+                    }
+                />
+            </div>
+
+            <Show when=is_custom>
+                <div class="flex items-center gap-0.5">
+                    {(0..12).map(|interval| {
+                        let is_on = move || pattern_signal.with(|p| interval == 0 || p.key.custom[interval]);
+                        view! {
+                            <button
+                                class=move || if is_on() {
+                                    "w-6 h-6 rounded text-[9px] font-mono bg-blue-500 text-white"
+                                } else {
+                                    "w-6 h-6 rounded text-[9px] font-mono bg-zinc-800 text-zinc-500 hover:bg-zinc-700"
+                                }
+                                disabled=interval == 0
+                                title="Toggle this pitch class, counted from the root"
+                                on:click=move |_| set_pattern_signal.update(|p| p.key.custom[interval] = !p.key.custom[interval])
+                            >
+                                {move || pattern_signal.with(|p| NOTE_NAMES[(p.key.root as usize + interval) % 12])}
+                            </button>
+                        }
+                    }).collect::<Vec<_>>()}
+                </div>
+            </Show>
+
+            <div class="flex items-center gap-1">
+                <span class="text-[10px] font-medium uppercase tracking-tight text-zinc-400">"Transpose"</span>
+                <button
+                    class="w-6 h-6 bg-zinc-800 hover:bg-zinc-700 border border-zinc-700 rounded text-xs text-zinc-300 transition-colors"
+                    title="Selected track down one scale degree"
+                    on:click=move |_| transpose(-1)
+                >
+                    "-"
+                </button>
+                <button
+                    class="w-6 h-6 bg-zinc-800 hover:bg-zinc-700 border border-zinc-700 rounded text-xs text-zinc-300 transition-colors"
+                    title="Selected track up one scale degree"
+                    on:click=move |_| transpose(1)
+                >
+                    "+"
+                </button>
+            </div>
+
+            <div class="flex items-center gap-1">
+                <span class="text-[10px] font-medium uppercase tracking-tight text-zinc-400">"Steps Show"</span>
+                <Dropdown
+                    options=label_options
+                    selected=Signal::derive(move || match label_mode.get() {
+                        NoteLabelMode::Dots => "dots".to_string(),
+                        NoteLabelMode::Notes => "notes".to_string(),
+                        NoteLabelMode::Degrees => "degrees".to_string(),
+                    })
+                    on_change=move |value| label_mode.set(match value.as_str() {
+                        "notes" => NoteLabelMode::Notes,
+                        "degrees" => NoteLabelMode::Degrees,
+                        _ => NoteLabelMode::Dots,
+                    })
+                />
+            </div>
         </div>
     }
 }
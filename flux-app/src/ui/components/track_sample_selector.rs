@@ -0,0 +1,88 @@
+use crate::app::{SamplePool, SequencerState};
+use crate::services::audio::delete_sample;
+use crate::shared::models::Pattern;
+use crate::ui::components::form_controls::*;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+/// Track-level sample choice. Note trigs play the sample instead of the oscillator.
+#[component]
+pub fn TrackSampleSelector() -> impl IntoView {
+    let sequencer_state =
+        use_context::<SequencerState>().expect("SequencerState context not found");
+    let selected_step = sequencer_state.selected_step;
+    let pattern_signal = use_context::<ReadSignal<Pattern>>().expect("Pattern context not found");
+    let set_pattern_signal =
+        use_context::<WriteSignal<Pattern>>().expect("Pattern write signal not found");
+    let samples = use_context::<SamplePool>().expect("SamplePool context not found").samples;
+
+    let track_id = move || selected_step.get().map(|(t, _)| t).unwrap_or(0);
+
+    let track_sample = move || {
+        pattern_signal.with(|p| p.tracks.get(track_id()).and_then(|t| t.sample))
+    };
+
+    let set_track_sample = move |sample: Option<u16>| {
+        let track_id = track_id();
+        set_pattern_signal.update(|p| {
+            if let Some(track) = p.tracks.get_mut(track_id) {
+                track.sample = sample;
+            }
+        });
+    };
+
+    let selected_info = move || {
+        let id = track_sample()?;
+        samples.with(|samples| samples.iter().find(|s| s.id == id).cloned())
+    };
+
+    // Remove from the pool; every track using it goes back to the oscillator
+    let on_delete = move |_| {
+        let Some(id) = track_sample() else {
+            return;
+        };
+        set_pattern_signal.update(|p| {
+            for track in p.tracks.iter_mut().filter(|t| t.sample == Some(id)) {
+                track.sample = None;
+            }
+        });
+        samples.update(|samples| samples.retain(|s| s.id != id));
+        spawn_local(async move { delete_sample(id).await });
+    };
+
+    view! {
+        <div class="flex flex-col gap-0.5">
+            <InlineParam>
+                <ParamLabel text="Sample" locked=Signal::derive(|| false) />
+                <select
+                    prop:value=move || track_sample().map(|id| id.to_string()).unwrap_or_default()
+                    on:change=move |ev| set_track_sample(event_target_value(&ev).parse().ok())
+                    class="w-32 bg-zinc-800 text-zinc-50 text-[10px] rounded px-1 py-0.5 border border-zinc-700 focus:outline-none focus:ring-1 focus:ring-blue-500"
+                >
+                    <option value="">"Off (Synth)"</option>
+                    {move || samples.get().into_iter().map(|sample| {
+                        view! {
+                            <option value=sample.id.to_string()>
+                                {format!("{:03} {}", sample.id + 1, sample.name)}
+                            </option>
+                        }
+                    }).collect::<Vec<_>>()}
+                </select>
+            </InlineParam>
+            {move || selected_info().map(|info| view! {
+                <div class="flex items-center justify-between px-1">
+                    <span class="text-[10px] font-mono text-zinc-500">
+                        {format!("{:.2}s @ {} Hz", info.duration_secs, info.sample_rate)}
+                    </span>
+                    <button
+                        on:click=on_delete
+                        class="text-[10px] text-zinc-500 hover:text-red-400 transition-colors"
+                        title="Delete from the sample pool"
+                    >
+                        "DELETE"
+                    </button>
+                </div>
+            })}
+        </div>
+    }
+}
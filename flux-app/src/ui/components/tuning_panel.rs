@@ -0,0 +1,221 @@
+use crate::services::audio::load_tuning;
+use crate::shared::models::{MidiRetuning, Pattern, Tuning};
+use crate::ui::tauri::{safe_dialog_open, TauriError};
+use leptos::ev;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+#[derive(serde::Serialize)]
+struct DialogFilter {
+    name: String,
+    extensions: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+struct OpenDialogOptions {
+    filters: Vec<DialogFilter>,
+    multiple: bool,
+    directory: bool,
+}
+
+// Ask for one file with `extension`; None if cancelled or unavailable
+async fn pick_file(name: &str, extension: &str) -> Option<String> {
+    let options = OpenDialogOptions {
+        filters: vec![DialogFilter { name: name.to_string(), extensions: vec![extension.to_string()] }],
+        multiple: false,
+        directory: false,
+    };
+    let options_js = serde_wasm_bindgen::to_value(&options).ok()?;
+    match safe_dialog_open(options_js).await {
+        Ok(path) => path,
+        Err(TauriError::NotAvailable) => None,
+        Err(TauriError::InvokeFailed(msg)) => {
+            web_sys::console::error_1(&format!("Open dialog failed: {}", msg).into());
+            None
+        }
+    }
+}
+
+fn retuning_value(mode: MidiRetuning) -> &'static str {
+    match mode {
+        MidiRetuning::Off => "off",
+        MidiRetuning::PitchBend => "bend",
+        MidiRetuning::Mts => "mts",
+    }
+}
+
+fn parse_retuning(value: &str) -> MidiRetuning {
+    match value {
+        "off" => MidiRetuning::Off,
+        "mts" => MidiRetuning::Mts,
+        _ => MidiRetuning::PitchBend,
+    }
+}
+
+/// Project tuning: Scala scale and keyboard mapping, and how MIDI output follows it.
+/// The tuning is saved with the pattern.
+#[component]
+pub fn TuningPanel(visible: RwSignal<bool>) -> impl IntoView {
+    let pattern_signal = use_context::<ReadSignal<Pattern>>().expect("Pattern context not found");
+    let set_pattern_signal = use_context::<WriteSignal<Pattern>>().expect("Pattern context not found");
+    let scl_path = RwSignal::new(None::<String>);
+    let kbm_path = RwSignal::new(None::<String>);
+    let error = RwSignal::new(None::<String>);
+
+    // Replace the scale and mapping, keeping the MIDI settings
+    let apply_tuning = move |tuning: Tuning| {
+        set_pattern_signal.update(|p| {
+            p.tuning = Tuning {
+                midi_retuning: p.tuning.midi_retuning,
+                midi_bend_range: p.tuning.midi_bend_range,
+                ..tuning
+            };
+        });
+    };
+
+    let reload = move || {
+        let Some(scl) = scl_path.get_untracked() else {
+            return;
+        };
+        let kbm = kbm_path.get_untracked();
+        spawn_local(async move {
+            match load_tuning(scl, kbm).await {
+                Ok(tuning) => {
+                    error.set(None);
+                    apply_tuning(tuning);
+                }
+                Err(msg) => error.set(Some(msg)),
+            }
+        });
+    };
+
+    let load_scale = move |_| {
+        spawn_local(async move {
+            if let Some(path) = pick_file("Scala Scale", "scl").await {
+                scl_path.set(Some(path));
+                reload();
+            }
+        });
+    };
+
+    let load_mapping = move |_| {
+        spawn_local(async move {
+            if let Some(path) = pick_file("Scala Keyboard Mapping", "kbm").await {
+                kbm_path.set(Some(path));
+                reload();
+            }
+        });
+    };
+
+    let reset = move |_| {
+        scl_path.set(None);
+        kbm_path.set(None);
+        error.set(None);
+        apply_tuning(Tuning::default());
+    };
+
+    let handle_escape = move |ev: ev::KeyboardEvent| {
+        if ev.key() == "Escape" && visible.get() {
+            visible.set(false);
+        }
+    };
+    window_event_listener(ev::keydown, handle_escape);
+
+    view! {
+        <Show when=move || visible.get()>
+            <div
+                class="fixed inset-0 bg-black/50 flex items-center justify-center z-50"
+                on:click=move |_| visible.set(false)
+            >
+                <div
+                    class="bg-zinc-900 border border-zinc-700 rounded-lg p-6 w-[28rem] flex flex-col gap-3"
+                    on:click=|e| e.stop_propagation()
+                >
+                    <h3 class="text-lg font-medium text-zinc-50">"Tuning"</h3>
+
+                    <div class="text-[10px] font-mono text-zinc-500">
+                        {move || pattern_signal.with(|p| {
+                            let tuning = &p.tuning;
+                            format!(
+                                "{} - {} DEGREES, PERIOD {:.2} CENTS, KEY {} = {:.2} HZ",
+                                tuning.name,
+                                tuning.cents.len(),
+                                tuning.cents.last().copied().unwrap_or(1200.0),
+                                tuning.keyboard.reference_note,
+                                tuning.keyboard.reference_freq,
+                            )
+                        })}
+                    </div>
+
+                    <div class="flex gap-2">
+                        <button
+                            class="px-3 py-1 bg-zinc-800 hover:bg-zinc-700 rounded text-xs font-medium text-zinc-300 transition-colors"
+                            on:click=load_scale
+                        >
+                            "LOAD .SCL"
+                        </button>
+                        <button
+                            class="px-3 py-1 bg-zinc-800 hover:bg-zinc-700 disabled:opacity-50 rounded text-xs font-medium text-zinc-300 transition-colors"
+                            disabled=move || scl_path.get().is_none()
+                            title="Keyboard mapping for the loaded scale"
+                            on:click=load_mapping
+                        >
+                            "LOAD .KBM"
+                        </button>
+                        <button
+                            class="px-3 py-1 bg-zinc-800 hover:bg-zinc-700 rounded text-xs font-medium text-zinc-300 transition-colors"
+                            on:click=reset
+                        >
+                            "12-TET"
+                        </button>
+                    </div>
+
+                    <label class="flex items-center justify-between gap-4">
+                        <span class="text-[10px] font-medium uppercase tracking-tight text-zinc-400">"MIDI Output"</span>
+                        <select
+                            prop:value=move || pattern_signal.with(|p| retuning_value(p.tuning.midi_retuning))
+                            on:change=move |ev| {
+                                let mode = parse_retuning(&event_target_value(&ev));
+                                set_pattern_signal.update(|p| p.tuning.midi_retuning = mode);
+                            }
+                            class="w-56 bg-zinc-800 text-zinc-50 text-xs rounded px-2 py-1 border border-zinc-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 focus:ring-offset-zinc-900"
+                        >
+                            <option value="off">"Plain notes"</option>
+                            <option value="bend">"Pitch bend"</option>
+                            <option value="mts">"MIDI Tuning Standard"</option>
+                        </select>
+                    </label>
+
+                    <label class="flex items-center justify-between gap-4">
+                        <span class="text-[10px] font-medium uppercase tracking-tight text-zinc-400">"Bend Range (semitones)"</span>
+                        <input
+                            type="number"
+                            min="1"
+                            max="48"
+                            prop:value=move || pattern_signal.with(|p| p.tuning.midi_bend_range.to_string())
+                            on:change=move |ev| {
+                                if let Ok(range) = event_target_value(&ev).parse::<u8>() {
+                                    set_pattern_signal.update(|p| p.tuning.midi_bend_range = range.clamp(1, 48));
+                                }
+                            }
+                            class="w-56 bg-zinc-800 text-zinc-50 text-xs rounded px-2 py-1 border border-zinc-700 focus:outline-none focus:ring-2 focus:ring-blue-500"
+                        />
+                    </label>
+
+                    {move || error.get().map(|msg| view! {
+                        <div class="text-xs text-red-400">{msg}</div>
+                    })}
+
+                    <div class="flex gap-2 justify-end">
+                        <button
+                            class="px-4 py-2 bg-zinc-800 hover:bg-zinc-700 rounded text-sm text-zinc-300 transition-colors"
+                            on:click=move |_| visible.set(false)
+                        >
+                            "Close"
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
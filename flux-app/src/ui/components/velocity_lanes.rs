@@ -1,3 +1,4 @@
+use crate::app::SequencerState;
 use crate::shared::models::Pattern;
 use leptos::prelude::*;
 
@@ -5,12 +6,13 @@ use leptos::prelude::*;
 fn get_velocity_value(
     pattern: &crate::shared::models::Pattern,
     track_idx: usize,
+    subtrack_idx: usize,
     step_idx: usize,
 ) -> u8 {
     pattern
         .tracks
         .get(track_idx)
-        .and_then(|track| track.subtracks.get(0))
+        .and_then(|track| track.subtracks.get(subtrack_idx))
         .and_then(|subtrack| subtrack.steps.get(step_idx))
         .map(|step| step.velocity)  // Read from velocity field directly
         .unwrap_or(100)
@@ -20,12 +22,13 @@ fn get_velocity_value(
 fn is_step_active(
     pattern: &crate::shared::models::Pattern,
     track_idx: usize,
+    subtrack_idx: usize,
     step_idx: usize,
 ) -> bool {
     pattern
         .tracks
         .get(track_idx)
-        .and_then(|track| track.subtracks.get(0))
+        .and_then(|track| track.subtracks.get(subtrack_idx))
         .and_then(|subtrack| subtrack.steps.get(step_idx))
         .map(|step| step.trig_type != crate::shared::models::TrigType::None)
         .unwrap_or(false)
@@ -38,6 +41,8 @@ pub fn VelocityLanes() -> impl IntoView {
         .expect("Pattern context not found");
     let set_pattern_signal = use_context::<WriteSignal<Pattern>>()
         .expect("Pattern write signal not found");
+    let sequencer_state = use_context::<SequencerState>()
+        .expect("SequencerState context not found");
 
     // Drag state
     let (drag_state, set_drag_state) = signal::<Option<(usize, usize)>>(None);
@@ -58,7 +63,7 @@ pub fn VelocityLanes() -> impl IntoView {
                             if let Some(step) = pattern
                                 .tracks
                                 .get_mut(t_idx)
-                                .and_then(|t| t.subtracks.get_mut(0))
+                                .and_then(|t| t.subtracks.get_mut(sequencer_state.subtrack(t_idx)))
                                 .and_then(|st| st.steps.get_mut(s_idx))
                             {
                                 step.velocity = new_velocity;
@@ -82,8 +87,8 @@ pub fn VelocityLanes() -> impl IntoView {
 
             // Velocity grid - uses same CSS Grid as step grid
             // Grid columns: [track-labels] [step-1] [step-2] ... [step-16]
-            // Track label column: 100px (matches step grid for alignment)
-            <div style="display: grid; grid-template-columns: 100px repeat(16, 40px); gap: 2px;">
+            // Track label column: 112px (matches step grid for alignment)
+            <div style="display: grid; grid-template-columns: 112px repeat(16, 40px); gap: 2px;">
                 <For
                     each=move || {
                         pattern_signal.with(|p| (0..p.tracks.len()).collect::<Vec<_>>())
@@ -105,13 +110,13 @@ pub fn VelocityLanes() -> impl IntoView {
                                 children=move |step_idx| {
                                     let value_signal = Signal::derive(move || {
                                         pattern_signal.with(|p| {
-                                            get_velocity_value(p, track_idx, step_idx)
+                                            get_velocity_value(p, track_idx, sequencer_state.subtrack(track_idx), step_idx)
                                         })
                                     });
 
                                     let is_active = Signal::derive(move || {
                                         pattern_signal.with(|p| {
-                                            is_step_active(p, track_idx, step_idx)
+                                            is_step_active(p, track_idx, sequencer_state.subtrack(track_idx), step_idx)
                                         })
                                     });
 
@@ -127,7 +132,7 @@ pub fn VelocityLanes() -> impl IntoView {
                                                     ev.prevent_default();
                                                     set_drag_state.set(Some((track_idx, step_idx)));
                                                     set_drag_start_y.set(Some(ev.client_y() as f64));
-                                                    let current_value = pattern_signal.with(|p| get_velocity_value(p, track_idx, step_idx));
+                                                    let current_value = pattern_signal.with(|p| get_velocity_value(p, track_idx, sequencer_state.subtrack(track_idx), step_idx));
                                                     set_drag_start_value.set(Some(current_value));
                                                 }
                                             >
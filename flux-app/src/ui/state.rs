@@ -4,7 +4,9 @@ use serde::{Deserialize, Serialize};
 pub struct PlaybackState {
     pub is_playing: bool,
     pub current_position: usize,        // 0-15
+    pub skipped_steps: usize,           // Steps passed since the previous update without one of their own
     pub triggered_tracks: Vec<bool>,    // Which tracks fired this step
+    pub song_row: Option<usize>,        // Song row playing, None outside song mode
 }
 
 #[derive(Clone, Debug)]
@@ -46,3 +48,12 @@ impl GridUIState {
         });
     }
 }
+
+/// What active grid steps show.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NoteLabelMode {
+    #[default]
+    Dots,
+    Notes,   // MIDI note numbers
+    Degrees, // Scale degrees in the pattern key
+}
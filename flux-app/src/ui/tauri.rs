@@ -156,18 +156,21 @@ pub async fn set_lfo_designer_value(track_id: usize, lfo_index: usize, step: usi
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")] // Tauri expects camelCase argument names
 pub struct ToggleStepArgs {
     pub track_id: usize,
+    pub subtrack: usize,
     pub step_idx: usize,
 }
 
-pub async fn toggle_step(track_id: usize, step_idx: usize) {
+pub async fn toggle_step(track_id: usize, subtrack: usize, step_idx: usize) {
     if !is_tauri_available() {
         return; // Silent - feature disabled in browser mode
     }
 
     let args = match serde_wasm_bindgen::to_value(&ToggleStepArgs {
         track_id,
+        subtrack,
         step_idx,
     }) {
         Ok(v) => v,
@@ -182,6 +185,107 @@ pub async fn toggle_step(track_id: usize, step_idx: usize) {
     }
 }
 
+#[derive(Serialize)]
+pub struct UpdatePatternArgs {
+    pub slot: usize,
+    pub pattern: crate::shared::models::Pattern,
+}
+
+/// Ship a bank slot's whole pattern to the engines (the playing one is hot-swapped at the next step)
+pub async fn update_pattern(slot: usize, pattern: crate::shared::models::Pattern) {
+    if !is_tauri_available() {
+        return; // Silent - feature disabled in browser mode
+    }
+
+    let args = match serde_wasm_bindgen::to_value(&UpdatePatternArgs { slot, pattern }) {
+        Ok(v) => v,
+        Err(e) => {
+            web_sys::console::error_1(&format!("Failed to serialize update_pattern args: {:?}", e).into());
+            return;
+        }
+    };
+
+    if let Err(e) = invoke_with_error("update_pattern", args).await {
+        web_sys::console::error_1(&format!("update_pattern failed: {:?}", e).into());
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")] // Tauri expects camelCase argument names
+pub struct UpdateTrackArgs {
+    pub slot: usize,
+    pub track_id: usize,
+    pub track: crate::shared::models::Track,
+}
+
+/// Ship a single track of a bank slot's pattern to the engines (hot-swapped at the next step)
+pub async fn update_track(slot: usize, track_id: usize, track: crate::shared::models::Track) {
+    if !is_tauri_available() {
+        return; // Silent - feature disabled in browser mode
+    }
+
+    let args = match serde_wasm_bindgen::to_value(&UpdateTrackArgs { slot, track_id, track }) {
+        Ok(v) => v,
+        Err(e) => {
+            web_sys::console::error_1(&format!("Failed to serialize update_track args: {:?}", e).into());
+            return;
+        }
+    };
+
+    if let Err(e) = invoke_with_error("update_track", args).await {
+        web_sys::console::error_1(&format!("update_track failed: {:?}", e).into());
+    }
+}
+
+#[derive(Serialize)]
+pub struct QueuePatternArgs {
+    pub slot: usize,
+    pub switch: crate::shared::models::PatternSwitch,
+}
+
+/// Queue a switch to a bank slot the engines already hold
+pub async fn queue_pattern(slot: usize, switch: crate::shared::models::PatternSwitch) {
+    if !is_tauri_available() {
+        return; // Silent - feature disabled in browser mode
+    }
+
+    let args = match serde_wasm_bindgen::to_value(&QueuePatternArgs { slot, switch }) {
+        Ok(v) => v,
+        Err(e) => {
+            web_sys::console::error_1(&format!("Failed to serialize queue_pattern args: {:?}", e).into());
+            return;
+        }
+    };
+
+    if let Err(e) = invoke_with_error("queue_pattern", args).await {
+        web_sys::console::error_1(&format!("queue_pattern failed: {:?}", e).into());
+    }
+}
+
+#[derive(Serialize)]
+pub struct UpdateSongArgs {
+    pub song: crate::shared::models::Song,
+}
+
+/// Ship the song to the engines
+pub async fn update_song(song: crate::shared::models::Song) {
+    if !is_tauri_available() {
+        return; // Silent - feature disabled in browser mode
+    }
+
+    let args = match serde_wasm_bindgen::to_value(&UpdateSongArgs { song }) {
+        Ok(v) => v,
+        Err(e) => {
+            web_sys::console::error_1(&format!("Failed to serialize update_song args: {:?}", e).into());
+            return;
+        }
+    };
+
+    if let Err(e) = invoke_with_error("update_song", args).await {
+        web_sys::console::error_1(&format!("update_song failed: {:?}", e).into());
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct TauriEvent<T> {
     #[allow(dead_code)]